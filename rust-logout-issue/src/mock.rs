@@ -0,0 +1,75 @@
+//! Mock connection pool shared by the `problem` and `solution` binaries
+//!
+//! Reproduces how deadpool-runtime fails: its cleanup thread arms a Tokio timer
+//! after the runtime is gone and panics with
+//! "A Tokio 1.x context was found, but it is being shutdown."
+
+use std::any::Any;
+use std::future::Future;
+use std::sync::mpsc;
+use std::task::{Context, Waker};
+use std::time::Duration;
+use tokio::runtime::Handle;
+
+/// Outcome of the connection pool's deferred cleanup: `Err` holds the panic message
+pub type PoolCloseResult = Result<(), String>;
+
+/// Simulates deadpool-runtime: dropping the pool closes its connections on a
+/// blocking thread, which then has to go back to the Tokio runtime to finish
+///
+/// `cleanup_guard` is held by that thread until it is done, so a task gate
+/// passed in here sees the pool as outstanding until its cleanup has finished.
+pub struct MockConnectionPool<G: Send + 'static = ()> {
+    runtime: Handle,
+    closed_tx: mpsc::Sender<PoolCloseResult>,
+    cleanup_guard: Option<G>,
+}
+
+impl<G: Send + 'static> MockConnectionPool<G> {
+    pub fn new(
+        runtime: Handle,
+        closed_tx: mpsc::Sender<PoolCloseResult>,
+        cleanup_guard: G,
+    ) -> Self {
+        Self {
+            runtime,
+            closed_tx,
+            cleanup_guard: Some(cleanup_guard),
+        }
+    }
+}
+
+impl<G: Send + 'static> Drop for MockConnectionPool<G> {
+    fn drop(&mut self) {
+        let runtime = self.runtime.clone();
+        let closed_tx = self.closed_tx.clone();
+        let guard = self.cleanup_guard.take();
+        std::thread::spawn(move || {
+            let _guard = guard;
+
+            // Closing sqlite connections is blocking work
+            std::thread::sleep(Duration::from_millis(100));
+
+            // Like deadpool-runtime, go back to the runtime the pool was created on
+            // and arm a timer. Tokio panics here if that runtime was shut down.
+            let result = std::panic::catch_unwind(move || {
+                let _context = runtime.enter();
+                let mut recycle_timeout = Box::pin(tokio::time::sleep(Duration::from_millis(1)));
+                let _ = recycle_timeout
+                    .as_mut()
+                    .poll(&mut Context::from_waker(Waker::noop()));
+            });
+            let _ = closed_tx.send(result.map_err(|panic| panic_message(&*panic)));
+        });
+    }
+}
+
+fn panic_message(panic: &(dyn Any + Send)) -> String {
+    if let Some(message) = panic.downcast_ref::<&str>() {
+        message.to_string()
+    } else if let Some(message) = panic.downcast_ref::<String>() {
+        message.clone()
+    } else {
+        "unknown panic".to_string()
+    }
+}
@@ -8,20 +8,27 @@
 //! In a real scenario with deadpool-runtime, this would cause:
 //! "thread 'main' panicked at deadpool-runtime-0.1.4/src/lib.rs:101:22:
 //!  there is no reactor running, must be called from the context of a Tokio 1.x runtime"
+//!
+//! `MockConnectionPool` reproduces this: its cleanup thread arms a Tokio timer
+//! after the runtime is gone and panics with
+//! "A Tokio 1.x context was found, but it is being shutdown."
 
-use std::sync::{Arc, Mutex};
+use std::sync::{mpsc, Mutex};
 use std::time::Duration;
 use tokio::runtime::Runtime;
 
+mod mock;
+use mock::{MockConnectionPool, PoolCloseResult};
 
 /// Simulates a Matrix SDK client with long-running async tasks
 struct MockMatrixClient {
     // Simulates deadpool connection pool tasks
     _background_tasks: Vec<tokio::task::JoinHandle<()>>,
+    _pool: MockConnectionPool,
 }
 
 impl MockMatrixClient {
-    fn new(runtime: &Runtime) -> Self {
+    fn new(runtime: &Runtime, pool_closed: mpsc::Sender<PoolCloseResult>) -> Self {
         let mut tasks = Vec::new();
 
         // Spawn several background tasks that simulate deadpool-runtime behavior
@@ -38,6 +45,7 @@ impl MockMatrixClient {
 
         Self {
             _background_tasks: tasks,
+            _pool: MockConnectionPool::new(runtime.handle().clone(), pool_closed, ()),
         }
     }
 
@@ -57,12 +65,6 @@ fn initialize_runtime() {
     *TOKIO_RUNTIME.lock().unwrap() = Some(rt);
 }
 
-fn get_runtime() -> Arc<Runtime> {
-    // In real code, this would return a reference to the static runtime
-    // For this demo, we create a temporary one
-    Arc::new(Runtime::new().unwrap())
-}
-
 /// PROBLEMATIC APPROACH: Logout without proper async task cleanup
 async fn problematic_logout() -> anyhow::Result<()> {
     log::info!("=== Starting PROBLEMATIC logout ===");
@@ -91,6 +93,7 @@ async fn problematic_logout() -> anyhow::Result<()> {
         rt.shutdown_background();
         log::error!("Runtime shut down - any remaining async tasks will panic!");
     }
+    drop(rt_guard);
 
     // In a real scenario with deadpool-runtime, the panic would occur here
     // because deadpool tasks try to access the now-closed runtime
@@ -102,16 +105,19 @@ async fn problematic_logout() -> anyhow::Result<()> {
     Ok(())
 }
 
-fn setup_client() {
+/// Installs a fresh runtime and client; the receiver reports how the client's pool closed
+fn setup_client() -> mpsc::Receiver<PoolCloseResult> {
     initialize_runtime();
 
     let rt = TOKIO_RUNTIME.lock().unwrap();
     let rt_ref = rt.as_ref().unwrap();
 
-    let client = MockMatrixClient::new(rt_ref);
+    let (pool_closed_tx, pool_closed_rx) = mpsc::channel();
+    let client = MockMatrixClient::new(rt_ref, pool_closed_tx);
     *CLIENT.lock().unwrap() = Some(client);
 
     log::info!("Mock Matrix client initialized with background tasks");
+    pool_closed_rx
 }
 
 fn main() {
@@ -128,7 +134,7 @@ fn main() {
     println!("  'there is no reactor running, must be called from");
     println!("   the context of a Tokio 1.x runtime'\n");
 
-    setup_client();
+    let pool_closed = setup_client();
 
     // Give background tasks time to start
     std::thread::sleep(Duration::from_millis(200));
@@ -141,8 +147,37 @@ fn main() {
         }
     });
 
-    println!("\n⚠️  In a real scenario with deadpool-runtime, the program would");
-    println!("have panicked during or after runtime shutdown.\n");
+    match pool_closed.recv_timeout(Duration::from_secs(1)) {
+        Ok(Err(panic)) => println!("\n💥 The mock connection pool panicked: {}", panic),
+        Ok(Ok(())) => println!("\n(The mock connection pool happened to close in time)"),
+        Err(_) => println!("\n(The mock connection pool never reported back)"),
+    }
+
+    println!("\n⚠️  With the real deadpool-runtime, the program panics");
+    println!("during or after runtime shutdown in the same way.\n");
     println!("The issue: shutdown_background() doesn't wait for async tasks,");
     println!("causing a race condition where tasks try to use a closed runtime.\n");
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_problematic_logout_panics_in_pool_cleanup() {
+        let pool_closed = setup_client();
+        tokio::time::sleep(Duration::from_millis(100)).await;
+
+        problematic_logout().await.unwrap();
+
+        let closed = pool_closed
+            .recv_timeout(Duration::from_secs(2))
+            .expect("Pool cleanup should report back");
+        let panic = closed.expect_err("Runtime was shut down under the pool's cleanup");
+        assert!(
+            panic.contains("shutdown"),
+            "Unexpected panic message: {}",
+            panic
+        );
+    }
+}
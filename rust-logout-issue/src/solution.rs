@@ -3,22 +3,28 @@
 //! This module shows the correct approach:
 //! 1. Use a state machine to manage logout phases
 //! 2. After dropping the client, wait for cleanup confirmation
-//! 3. This wait period gives async tasks time to complete properly
+//! 3. The wait ends when a `TaskGate` sees the client's last async task exit
 //! 4. Only then shutdown the runtime safely
 //!
 //! This prevents the deadpool-runtime panic by ensuring proper task lifecycle.
 
-use std::sync::{Arc, Mutex};
-use std::time::Duration;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{mpsc, Mutex};
+use std::time::{Duration, Instant};
 use tokio::runtime::Runtime;
-use tokio::sync::oneshot;
+use tokio::sync::{watch, Notify};
+
+mod mock;
+use mock::{MockConnectionPool, PoolCloseResult};
 
 /// Logout state machine states
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 enum LogoutState {
     Idle,
     PreChecking,
-    StoppngSyncService,
+    StoppingSyncService,
     LoggingOutFromServer,
     PointOfNoReturn,
     CleaningAppState,
@@ -28,18 +34,119 @@ enum LogoutState {
     Failed(String),
 }
 
+impl LogoutState {
+    /// `Completed` and `Failed` end a logout; no further transition follows
+    fn is_terminal(&self) -> bool {
+        matches!(self, LogoutState::Completed | LogoutState::Failed(_))
+    }
+
+    /// The legal phase graph: each phase leads to the next one, and any
+    /// unfinished logout may fail
+    fn can_transition(from: &LogoutState, to: &LogoutState) -> bool {
+        use LogoutState::*;
+        match (from, to) {
+            (from, _) if from.is_terminal() => false,
+            (_, Failed(_)) => true,
+            (Idle, PreChecking)
+            | (PreChecking, StoppingSyncService)
+            | (StoppingSyncService, LoggingOutFromServer)
+            | (LoggingOutFromServer, PointOfNoReturn)
+            | (PointOfNoReturn, CleaningAppState)
+            | (CleaningAppState, ShuttingDownTasks)
+            | (ShuttingDownTasks, RestartingRuntime)
+            | (RestartingRuntime, Completed) => true,
+            _ => false,
+        }
+    }
+}
+
+/// A transition that skips, repeats or reverses a phase
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct IllegalTransition {
+    from: LogoutState,
+    to: LogoutState,
+}
+
+impl std::fmt::Display for IllegalTransition {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Illegal logout transition from {:?} to {:?}", self.from, self.to)
+    }
+}
+
+impl std::error::Error for IllegalTransition {}
+
+/// Counts outstanding tasks and lets the logout wait until all of them have exited
+///
+/// Every task holds a [`TaskGuard`] from `enter()`; `wait_drained()` resolves
+/// the moment the last guard is dropped, however long that takes.
+struct TaskGate {
+    outstanding: AtomicUsize,
+    drained: Notify,
+}
+
+impl TaskGate {
+    const fn new() -> Self {
+        Self {
+            outstanding: AtomicUsize::new(0),
+            drained: Notify::const_new(),
+        }
+    }
+
+    /// Registers a task; it counts as outstanding until the guard is dropped
+    fn enter(&self) -> TaskGuard<'_> {
+        self.outstanding.fetch_add(1, Ordering::SeqCst);
+        TaskGuard { gate: self }
+    }
+
+    fn outstanding(&self) -> usize {
+        self.outstanding.load(Ordering::SeqCst)
+    }
+
+    /// Resolves once no task is outstanding
+    async fn wait_drained(&self) {
+        loop {
+            // Register interest before checking, so a guard dropped in between isn't missed
+            let drained = self.drained.notified();
+            tokio::pin!(drained);
+            drained.as_mut().enable();
+
+            if self.outstanding() == 0 {
+                return;
+            }
+            drained.await;
+        }
+    }
+}
+
+/// Marks one task as outstanding on its [`TaskGate`] until dropped
+struct TaskGuard<'a> {
+    gate: &'a TaskGate,
+}
+
+impl Drop for TaskGuard<'_> {
+    fn drop(&mut self) {
+        if self.gate.outstanding.fetch_sub(1, Ordering::SeqCst) == 1 {
+            self.gate.drained.notify_waiters();
+        }
+    }
+}
+
 /// Simulates a Matrix SDK client with long-running async tasks
 struct MockMatrixClient {
     background_tasks: Vec<tokio::task::JoinHandle<()>>,
+    _pool: MockConnectionPool<TaskGuard<'static>>,
 }
 
 impl MockMatrixClient {
-    fn new(runtime: &Runtime) -> Self {
+    fn new(runtime: &Runtime, pool_closed: mpsc::Sender<PoolCloseResult>) -> Self {
         let mut tasks = Vec::new();
 
         // Spawn several background tasks that simulate deadpool-runtime behavior
         for i in 0..5 {
+            let guard = CLIENT_TASKS.enter();
             let handle = runtime.spawn(async move {
+                // Released when the task is aborted and its future dropped
+                let _guard = guard;
                 loop {
                     // Simulate periodic database connection pool maintenance
                     tokio::time::sleep(Duration::from_millis(100)).await;
@@ -51,6 +158,12 @@ impl MockMatrixClient {
 
         Self {
             background_tasks: tasks,
+            // The pool's cleanup thread counts as a client task until it finishes
+            _pool: MockConnectionPool::new(
+                runtime.handle().clone(),
+                pool_closed,
+                CLIENT_TASKS.enter(),
+            ),
         }
     }
 
@@ -74,94 +187,266 @@ impl Drop for MockMatrixClient {
 // Global state (simulating robrix's static variables)
 static CLIENT: Mutex<Option<MockMatrixClient>> = Mutex::new(None);
 static TOKIO_RUNTIME: Mutex<Option<Runtime>> = Mutex::new(None);
-
-/// Logout state machine
-struct LogoutStateMachine {
-    state: Arc<Mutex<LogoutState>>,
+/// Tasks owned by the client (background tasks, pool cleanup) that must exit before shutdown
+static CLIENT_TASKS: TaskGate = TaskGate::new();
+
+/// Tunables for a [`LogoutStateMachine`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct LogoutConfig {
+    /// How long `CleaningAppState` waits for async tasks to drain before giving up
+    cleanup_timeout: Duration,
 }
 
-impl LogoutStateMachine {
-    fn new() -> Self {
+impl Default for LogoutConfig {
+    fn default() -> Self {
         Self {
-            state: Arc::new(Mutex::new(LogoutState::Idle)),
+            cleanup_timeout: Duration::from_secs(2),
         }
     }
+}
 
-    fn transition_to(&self, new_state: LogoutState, message: &str, progress: u8) {
-        *self.state.lock().unwrap() = new_state.clone();
-        log::info!("[{}%] {:?}: {}", progress, new_state, message);
+/// Future returned by a logout step
+type StepFuture = Pin<Box<dyn Future<Output = anyhow::Result<()>> + Send>>;
+
+/// The work done while the machine is in one phase, given the machine's config
+type StepAction = Box<dyn Fn(LogoutConfig) -> StepFuture + Send + Sync>;
+
+/// One phase of the logout: the state it enters, how it is reported, and what it does
+struct LogoutStep {
+    state: LogoutState,
+    message: &'static str,
+    progress: u8,
+    action: StepAction,
+}
+
+/// Assembles a [`LogoutStateMachine`] from its steps
+///
+/// Starts out with the default steps; `step` swaps the action of a phase, so
+/// tests can replace slow or global-state steps with fast fakes.
+struct LogoutStateMachineBuilder {
+    steps: Vec<LogoutStep>,
+    config: LogoutConfig,
+}
+
+impl LogoutStateMachineBuilder {
+    /// Builder holding the default steps, in order
+    fn new() -> Self {
+        let builder = Self {
+            steps: Vec::new(),
+            config: LogoutConfig::default(),
+        };
+        builder
+            .phase(LogoutState::PreChecking, "Checking prerequisites", 10, |_| async {
+                tokio::time::sleep(Duration::from_millis(50)).await;
+                Ok(())
+            })
+            .phase(LogoutState::StoppingSyncService, "Stopping sync service", 20, |_| async {
+                tokio::time::sleep(Duration::from_millis(50)).await;
+                Ok(())
+            })
+            .phase(
+                LogoutState::LoggingOutFromServer,
+                "Logging out from server",
+                30,
+                |_| logout_from_server(),
+            )
+            .phase(LogoutState::PointOfNoReturn, "Point of no return reached", 50, |_| async {
+                Ok(())
+            })
+            .phase(
+                LogoutState::CleaningAppState,
+                "Cleaning application state",
+                70,
+                |config: LogoutConfig| clean_app_state(config.cleanup_timeout),
+            )
+            .phase(
+                LogoutState::ShuttingDownTasks,
+                "Shutting down background tasks",
+                80,
+                |_| shut_down_runtime(),
+            )
+            .phase(
+                LogoutState::RestartingRuntime,
+                "Restarting Matrix runtime",
+                90,
+                |_| restart_runtime(),
+            )
     }
 
-    async fn execute(&self) -> anyhow::Result<()> {
-        log::info!("=== Starting SAFE logout with state machine ===");
+    /// Appends a phase
+    fn phase<F, Fut>(
+        mut self,
+        state: LogoutState,
+        message: &'static str,
+        progress: u8,
+        action: F,
+    ) -> Self
+    where
+        F: Fn(LogoutConfig) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = anyhow::Result<()>> + Send + 'static,
+    {
+        self.steps.push(LogoutStep {
+            state,
+            message,
+            progress,
+            action: Box::new(move |config| Box::pin(action(config))),
+        });
+        self
+    }
 
-        // Step 1: Pre-checks
-        self.transition_to(LogoutState::PreChecking, "Checking prerequisites", 10);
-        tokio::time::sleep(Duration::from_millis(50)).await;
+    /// Replaces the action of the phase entering `state`, keeping its place and message
+    ///
+    /// # Panics
+    /// If no phase enters `state`.
+    #[cfg(test)]
+    fn step<F, Fut>(mut self, state: LogoutState, action: F) -> Self
+    where
+        F: Fn(LogoutConfig) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = anyhow::Result<()>> + Send + 'static,
+    {
+        let step = self
+            .steps
+            .iter_mut()
+            .find(|step| {
+                std::mem::discriminant(&step.state) == std::mem::discriminant(&state)
+            })
+            .unwrap_or_else(|| panic!("No logout phase enters {:?}", state));
+        step.action = Box::new(move |config| Box::pin(action(config)));
+        self
+    }
 
-        // Step 2: Stop sync service (simulated)
-        self.transition_to(LogoutState::StoppingSyncService, "Stopping sync service", 20);
-        tokio::time::sleep(Duration::from_millis(50)).await;
+    /// Replaces the default [`LogoutConfig`]
+    fn config(mut self, config: LogoutConfig) -> Self {
+        self.config = config;
+        self
+    }
 
-        // Step 3: Server logout
-        self.transition_to(LogoutState::LoggingOutFromServer, "Logging out from server", 30);
-        let client = CLIENT.lock().unwrap().take();
-        if let Some(client) = client.as_ref() {
-            client.logout().await;
+    fn build(self) -> LogoutStateMachine {
+        let (state, _) = watch::channel((LogoutState::Idle, 0));
+        LogoutStateMachine {
+            state,
+            steps: self.steps,
+            config: self.config,
         }
+    }
+}
 
-        // Step 4: Point of no return
-        self.transition_to(LogoutState::PointOfNoReturn, "Point of no return reached", 50);
+/// Default step: logs the client out on the server, keeping it alive for now
+async fn logout_from_server() -> anyhow::Result<()> {
+    // Take the client so the lock isn't held across the await, then put it back
+    let client = CLIENT.lock().unwrap().take();
+    if let Some(client) = client {
+        client.logout().await;
+        *CLIENT.lock().unwrap() = Some(client);
+    }
+    Ok(())
+}
 
-        // Step 5: Clean app state (drop client)
-        self.transition_to(LogoutState::CleaningAppState, "Cleaning application state", 70);
+/// Default step: drops the client and waits for cleanup confirmation
+async fn clean_app_state(cleanup_timeout: Duration) -> anyhow::Result<()> {
+    // KEY DIFFERENCE: We drop the client here
+    drop(CLIENT.lock().unwrap().take());
+    log::info!("Client dropped, starting cleanup wait period...");
+    let started = Instant::now();
+
+    // CRITICAL: Wait until every task the client owned has actually exited
+    // before we shutdown the runtime
+    tokio::time::timeout(cleanup_timeout, CLIENT_TASKS.wait_drained())
+        .await
+        .map_err(|_| anyhow::anyhow!("Cleanup timeout after {:?}", started.elapsed()))?;
+    log::info!("Received cleanup confirmation - async tasks have completed");
+    Ok(())
+}
 
-        // KEY DIFFERENCE: We drop the client here
-        drop(client);
-        log::info!("Client dropped, starting cleanup wait period...");
+/// Default step: NOW it's safe to shutdown background tasks
+async fn shut_down_runtime() -> anyhow::Result<()> {
+    if let Some(rt) = TOKIO_RUNTIME.lock().unwrap().take() {
+        log::info!("Shutting down runtime (async tasks already completed)");
+        rt.shutdown_background();
+    }
+    Ok(())
+}
 
-        // CRITICAL: Wait for cleanup confirmation
-        // This gives async tasks time to complete before we shutdown the runtime
-        let (tx, rx) = oneshot::channel::<bool>();
+/// Default step: starts a fresh runtime for the next login
+async fn restart_runtime() -> anyhow::Result<()> {
+    let new_rt = Runtime::new()?;
+    *TOKIO_RUNTIME.lock().unwrap() = Some(new_rt);
+    Ok(())
+}
 
-        // Simulate app state cleanup (in real robrix, this triggers UI cleanup)
-        tokio::spawn(async move {
-            // Simulate some cleanup work
-            tokio::time::sleep(Duration::from_millis(200)).await;
-            log::info!("App state cleanup completed");
-            let _ = tx.send(true);
-        });
+/// Logout state machine
+struct LogoutStateMachine {
+    /// Current state and progress percentage, published on every transition
+    state: watch::Sender<(LogoutState, u8)>,
+    steps: Vec<LogoutStep>,
+    /// Handed to every step's action
+    config: LogoutConfig,
+}
 
-        // Wait for cleanup with timeout
-        match tokio::time::timeout(Duration::from_secs(2), rx).await {
-            Ok(Ok(_)) => {
-                log::info!("Received cleanup confirmation - async tasks had time to complete");
-            }
-            Ok(Err(e)) => {
-                return Err(anyhow::anyhow!("Cleanup channel error: {}", e));
-            }
-            Err(_) => {
-                return Err(anyhow::anyhow!("Cleanup timeout"));
-            }
-        }
+impl LogoutStateMachine {
+    /// State machine running the default steps with the default [`LogoutConfig`]
+    #[cfg(test)]
+    fn new() -> Self {
+        Self::builder().build()
+    }
+
+    fn builder() -> LogoutStateMachineBuilder {
+        LogoutStateMachineBuilder::new()
+    }
+
+    /// The state the machine is in right now
+    fn state(&self) -> LogoutState {
+        self.state.borrow().0.clone()
+    }
 
-        // Step 6: NOW it's safe to shutdown background tasks
-        self.transition_to(LogoutState::ShuttingDownTasks, "Shutting down background tasks", 80);
+    /// Follows the state and progress percentage, e.g. to drive a progress bar
+    ///
+    /// The receiver only holds the latest value, so a slow subscriber may skip
+    /// intermediate phases, but it always sees the terminal `Completed` or
+    /// `Failed` state since nothing is published after it.
+    fn subscribe(&self) -> watch::Receiver<(LogoutState, u8)> {
+        self.state.subscribe()
+    }
 
-        let mut rt_guard = TOKIO_RUNTIME.lock().unwrap();
-        if let Some(rt) = rt_guard.take() {
-            log::info!("Shutting down runtime (async tasks already completed)");
-            rt.shutdown_background();
+    /// Moves to `new_state`, rejecting any jump [`LogoutState::can_transition`] forbids
+    ///
+    /// A rejected transition leaves the current state untouched.
+    fn transition_to(
+        &self,
+        new_state: LogoutState,
+        message: &str,
+        progress: u8,
+    ) -> Result<(), IllegalTransition> {
+        let current = self.state();
+        if !LogoutState::can_transition(&current, &new_state) {
+            return Err(IllegalTransition {
+                from: current,
+                to: new_state,
+            });
         }
+        log::info!("[{}%] {:?}: {}", progress, new_state, message);
+        self.state.send_replace((new_state, progress));
+        Ok(())
+    }
 
-        // Step 7: Restart runtime
-        self.transition_to(LogoutState::RestartingRuntime, "Restarting Matrix runtime", 90);
-        let new_rt = Runtime::new()?;
-        *rt_guard = Some(new_rt);
-        drop(rt_guard);
+    /// Runs every step in order, stopping at the first one that fails
+    ///
+    /// A failed step moves the machine to [`LogoutState::Failed`] and none of
+    /// the later steps run, so a failure before `ShuttingDownTasks` leaves the
+    /// runtime untouched.
+    async fn execute(&self) -> anyhow::Result<()> {
+        log::info!("=== Starting SAFE logout with state machine ===");
 
-        // Step 8: Complete
-        self.transition_to(LogoutState::Completed, "Logout ́c͛ompleted successfully", 100);
+        for step in &self.steps {
+            self.transition_to(step.state.clone(), step.message, step.progress)?;
+            if let Err(e) = (step.action)(self.config).await {
+                let failed = LogoutState::Failed(e.to_string());
+                self.transition_to(failed, "Logout aborted", step.progress)?;
+                return Err(e);
+            }
+        }
+
+        self.transition_to(LogoutState::Completed, "Logout completed successfully", 100)?;
 
         log::info!("=== Logout complete - NO PANICS! ===");
         Ok(())
@@ -173,16 +458,19 @@ fn initialize_runtime() {
     *TOKIO_RUNTIME.lock().unwrap() = Some(rt);
 }
 
-fn setup_client() {
+/// Installs a fresh runtime and client; the receiver reports how the client's pool closed
+fn setup_client() -> mpsc::Receiver<PoolCloseResult> {
     initialize_runtime();
 
     let rt = TOKIO_RUNTIME.lock().unwrap();
     let rt_ref = rt.as_ref().unwrap();
 
-    let client = MockMatrixClient::new(rt_ref);
+    let (pool_closed_tx, pool_closed_rx) = mpsc::channel();
+    let client = MockMatrixClient::new(rt_ref, pool_closed_tx);
     *CLIENT.lock().unwrap() = Some(client);
 
     log::info!("Mock Matrix client initialized with background tasks");
+    pool_closed_rx
 }
 
 fn main() {
@@ -197,20 +485,37 @@ fn main() {
     println!("Key improvements:");
     println!("  1. State machine manages logout phases clearly");
     println!("  2. After dropping client, we WAIT for cleanup confirmation");
-    println!("  3. This wait gives async tasks time to complete properly");
+    println!("  3. The wait lasts until every client task has actually exited");
     println!("  4. Only after confirmation do we shutdown the runtime\n");
 
-    setup_client();
-
-    // Give background tasks time to start
-    std::thread::sleep(Duration::from_millis(200));
+    let pool_closed = setup_client();
 
     // Perform safe logout using state machine
     let rt = Runtime::new().unwrap();
     rt.block_on(async {
-        let state_machine = LogoutStateMachine::new();
+        let state_machine = LogoutStateMachine::builder()
+            // Give async tasks up to 2 seconds to drain before the runtime goes
+            .config(LogoutConfig {
+                cleanup_timeout: Duration::from_secs(2),
+            })
+            .build();
+
+        // What a UI progress bar would subscribe to
+        let mut progress = state_machine.subscribe();
+        let progress_bar = tokio::spawn(async move {
+            while progress.changed().await.is_ok() {
+                let (state, percent) = progress.borrow_and_update().clone();
+                println!("  Progress: {:>3}% {:?}", percent, state);
+                if state.is_terminal() {
+                    break;
+                }
+            }
+        });
 
-        match state_machine.execute().await {
+        let result = state_machine.execute().await;
+        progress_bar.await.ok();
+
+        match result {
             Ok(_) => {
                 println!("\n✅ Logout succeeded without any panics!");
                 println!("\nThe key difference: We waited for async task cleanup");
@@ -218,16 +523,29 @@ fn main() {
             }
             Err(e) => {
                 log::error!("Logout failed: {}", e);
+                if let LogoutState::Failed(reason) = state_machine.state() {
+                    println!("\n❌ Logout stopped before shutting down the runtime: {}", reason);
+                }
             }
         }
     });
 
-    println!("� Success! No deadpool-runtime panic occurred.\n");
+    match pool_closed.recv_timeout(Duration::from_secs(1)) {
+        Ok(Ok(())) => println!("✅ Success! No deadpool-runtime panic occurred.\n"),
+        Ok(Err(panic)) => println!("❌ The mock connection pool panicked: {}\n", panic),
+        Err(_) => println!("❌ The mock connection pool never reported back\n"),
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::Arc;
+    use tokio::sync::oneshot;
+
+    /// Serializes tests whose default steps touch `CLIENT` and `CLIENT_TASKS`
+    static GLOBAL_STATE: tokio::sync::Mutex<()> = tokio::sync::Mutex::const_new(());
 
     #[tokio::test]
     async fn test_state_machine_logout() {
@@ -237,13 +555,18 @@ mod tests {
             .try_init()
             .ok();
 
-        setup_client();
-        tokio::time::sleep(Duration::from_millis(100)).await;
+        let _globals = GLOBAL_STATE.lock().await;
+        let pool_closed = setup_client();
 
         let state_machine = LogoutStateMachine::new();
         let result = state_machine.execute().await;
 
         assert!(result.is_ok(), "Logout should succeed without panic");
+        assert_eq!(
+            pool_closed.recv_timeout(Duration::from_secs(2)),
+            Ok(Ok(())),
+            "Pool cleanup must finish before the runtime is shut down"
+        );
     }
 
     #[test]
@@ -251,12 +574,279 @@ mod tests {
         let sm = LogoutStateMachine::new();
 
         // Verify initial stat
-        let state = sm.state.lock().unwrap().clone();
+        let state = sm.state();
         assert!(matches!(state, LogoutState::Idle));
 
         // Test transition
-        sm.transition_to(LogoutState::PreChecking, "Test", 10);
-        let state = sm.state.lock().unwrap().clone();
+        sm.transition_to(LogoutState::PreChecking, "Test", 10).unwrap();
+        let state = sm.state();
         assert!(matches!(state, LogoutState::PreChecking));
     }
+
+    #[tokio::test]
+    async fn test_injected_steps_run_in_order() {
+        let order = Arc::new(Mutex::new(Vec::new()));
+        let mut builder = LogoutStateMachine::builder();
+        let phases = [
+            LogoutState::PreChecking,
+            LogoutState::StoppingSyncService,
+            LogoutState::LoggingOutFromServer,
+            LogoutState::PointOfNoReturn,
+            LogoutState::CleaningAppState,
+            LogoutState::ShuttingDownTasks,
+            LogoutState::RestartingRuntime,
+        ];
+        for (i, phase) in phases.into_iter().enumerate() {
+            let order = order.clone();
+            builder = builder.step(phase, move |_| {
+                order.lock().unwrap().push(i);
+                async { Ok(()) }
+            });
+        }
+
+        let sm = builder.build();
+        sm.execute().await.unwrap();
+
+        assert_eq!(*order.lock().unwrap(), (0..7).collect::<Vec<_>>());
+        assert_eq!(sm.state(), LogoutState::Completed);
+    }
+
+    #[tokio::test]
+    async fn test_failing_server_logout_stops_before_runtime_shutdown() {
+        let runtime_shut_down = Arc::new(AtomicBool::new(false));
+        let runtime_shut_down_clone = runtime_shut_down.clone();
+
+        let sm = LogoutStateMachine::builder()
+            .step(LogoutState::PreChecking, |_| async { Ok(()) })
+            .step(LogoutState::StoppingSyncService, |_| async { Ok(()) })
+            .step(LogoutState::LoggingOutFromServer, |_| async {
+                Err(anyhow::anyhow!("Server rejected logout"))
+            })
+            .step(LogoutState::ShuttingDownTasks, move |_| {
+                runtime_shut_down_clone.store(true, Ordering::SeqCst);
+                async { Ok(()) }
+            })
+            .build();
+
+        let result = sm.execute().await;
+
+        assert!(result.is_err(), "Logout should report the failure");
+        let state = sm.state();
+        assert!(
+            matches!(&state, LogoutState::Failed(msg) if msg == "Server rejected logout"),
+            "Unexpected state {:?}",
+            state
+        );
+        assert!(
+            !runtime_shut_down.load(Ordering::SeqCst),
+            "Runtime must not be shut down after a failed step"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_watch_channel_reports_every_phase() {
+        let mut builder = LogoutStateMachine::builder();
+        for phase in [
+            LogoutState::PreChecking,
+            LogoutState::StoppingSyncService,
+            LogoutState::LoggingOutFromServer,
+            LogoutState::PointOfNoReturn,
+            LogoutState::CleaningAppState,
+            LogoutState::ShuttingDownTasks,
+            LogoutState::RestartingRuntime,
+        ] {
+            // Give the subscriber a chance to observe each phase
+            builder = builder.step(phase, |_| async {
+                tokio::time::sleep(Duration::from_millis(10)).await;
+                Ok(())
+            });
+        }
+        let sm = builder.build();
+
+        let mut rx = sm.subscribe();
+        assert_eq!(*rx.borrow(), (LogoutState::Idle, 0));
+        let collector = tokio::spawn(async move {
+            let mut seen = Vec::new();
+            while rx.changed().await.is_ok() {
+                let (state, progress) = rx.borrow_and_update().clone();
+                let done = state.is_terminal();
+                seen.push((state, progress));
+                if done {
+                    break;
+                }
+            }
+            seen
+        });
+
+        sm.execute().await.unwrap();
+        let seen = collector.await.unwrap();
+
+        assert_eq!(
+            seen,
+            vec![
+                (LogoutState::PreChecking, 10),
+                (LogoutState::StoppingSyncService, 20),
+                (LogoutState::LoggingOutFromServer, 30),
+                (LogoutState::PointOfNoReturn, 50),
+                (LogoutState::CleaningAppState, 70),
+                (LogoutState::ShuttingDownTasks, 80),
+                (LogoutState::RestartingRuntime, 90),
+                (LogoutState::Completed, 100),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_watch_channel_ends_in_failed() {
+        let sm = LogoutStateMachine::builder()
+            .step(LogoutState::PreChecking, |_| async {
+                Err(anyhow::anyhow!("No network"))
+            })
+            .build();
+        let rx = sm.subscribe();
+
+        sm.execute().await.unwrap_err();
+
+        let (state, progress) = rx.borrow().clone();
+        assert!(state.is_terminal());
+        assert_eq!(state, LogoutState::Failed("No network".to_string()));
+        assert_eq!(progress, 10);
+    }
+
+    #[test]
+    fn test_out_of_order_transition_is_rejected() {
+        let sm = LogoutStateMachine::new();
+
+        let result = sm.transition_to(LogoutState::LoggingOutFromServer, "Skipping ahead", 30);
+        assert_eq!(
+            result,
+            Err(IllegalTransition {
+                from: LogoutState::Idle,
+                to: LogoutState::LoggingOutFromServer,
+            })
+        );
+        assert_eq!(sm.state(), LogoutState::Idle, "State must not change");
+
+        sm.transition_to(LogoutState::PreChecking, "Test", 10).unwrap();
+        assert!(sm.transition_to(LogoutState::Idle, "Going back", 0).is_err());
+        assert!(sm.transition_to(LogoutState::PreChecking, "Repeating", 10).is_err());
+
+        sm.transition_to(LogoutState::Failed("Test".to_string()), "Test", 10)
+            .unwrap();
+        assert!(
+            sm.transition_to(LogoutState::StoppingSyncService, "After failure", 20)
+                .is_err(),
+            "Failed is terminal"
+        );
+    }
+
+    #[test]
+    fn test_happy_path_transitions_are_accepted() {
+        let sm = LogoutStateMachine::new();
+        let happy_path = [
+            LogoutState::PreChecking,
+            LogoutState::StoppingSyncService,
+            LogoutState::LoggingOutFromServer,
+            LogoutState::PointOfNoReturn,
+            LogoutState::CleaningAppState,
+            LogoutState::ShuttingDownTasks,
+            LogoutState::RestartingRuntime,
+            LogoutState::Completed,
+        ];
+
+        for state in happy_path {
+            sm.transition_to(state.clone(), "Test", 0).unwrap();
+            assert_eq!(sm.state(), state);
+        }
+        assert!(!LogoutState::can_transition(
+            &LogoutState::Completed,
+            &LogoutState::Failed("Too late".to_string())
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_cleanup_timeout_is_configurable() {
+        let _globals = GLOBAL_STATE.lock().await;
+        // A client task that outlives the configured timeout
+        let _slow_task = CLIENT_TASKS.enter();
+
+        let cleanup_timeout = Duration::from_millis(50);
+        let runtime_shut_down = Arc::new(AtomicBool::new(false));
+        let runtime_shut_down_clone = runtime_shut_down.clone();
+
+        let sm = LogoutStateMachine::builder()
+            .config(LogoutConfig { cleanup_timeout })
+            .step(LogoutState::PreChecking, |_| async { Ok(()) })
+            .step(LogoutState::StoppingSyncService, |_| async { Ok(()) })
+            .step(LogoutState::LoggingOutFromServer, |_| async { Ok(()) })
+            .step(LogoutState::ShuttingDownTasks, move |_| {
+                runtime_shut_down_clone.store(true, Ordering::SeqCst);
+                async { Ok(()) }
+            })
+            .build();
+
+        let started = Instant::now();
+        let error = sm.execute().await.unwrap_err().to_string();
+
+        assert!(
+            error.starts_with("Cleanup timeout after"),
+            "Unexpected error: {}",
+            error
+        );
+        assert!(started.elapsed() < Duration::from_secs(1));
+        assert!(matches!(sm.state(), LogoutState::Failed(_)));
+        assert!(!runtime_shut_down.load(Ordering::SeqCst));
+    }
+
+    #[test]
+    fn test_default_logout_config() {
+        assert_eq!(
+            LogoutConfig::default().cleanup_timeout,
+            Duration::from_secs(2)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_task_gate_drains_exactly_when_last_task_exits() {
+        static GATE: TaskGate = TaskGate::new();
+
+        let mut releases = Vec::new();
+        for _ in 0..3 {
+            let (release_tx, release_rx) = oneshot::channel::<()>();
+            let guard = GATE.enter();
+            tokio::spawn(async move {
+                let _guard = guard;
+                let _ = release_rx.await;
+            });
+            releases.push(Some(release_tx));
+        }
+        assert_eq!(GATE.outstanding(), 3);
+
+        let waiter = tokio::spawn(GATE.wait_drained());
+        // Release in an arbitrary order; the waiter must hold out until the last one
+        for release in [2, 0] {
+            releases[release].take().unwrap().send(()).unwrap();
+            for _ in 0..10 {
+                tokio::task::yield_now().await;
+            }
+            assert!(!waiter.is_finished(), "Tasks are still outstanding");
+        }
+        assert_eq!(GATE.outstanding(), 1);
+
+        releases[1].take().unwrap().send(()).unwrap();
+        tokio::time::timeout(Duration::from_secs(1), waiter)
+            .await
+            .expect("Gate should drain once the last task exits")
+            .unwrap();
+        assert_eq!(GATE.outstanding(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_task_gate_with_no_tasks_is_drained() {
+        static GATE: TaskGate = TaskGate::new();
+
+        tokio::time::timeout(Duration::from_millis(100), GATE.wait_drained())
+            .await
+            .expect("An empty gate is already drained");
+    }
 }
@@ -1,4 +1,6 @@
-use anyhow::{Context, Result};
+use anyhow::Result;
+use std::collections::BTreeMap;
+use std::fmt;
 use std::sync::{Arc, Mutex, Weak};
 use std::time::Duration;
 use tokio::sync::oneshot;
@@ -32,16 +34,112 @@ impl Drop for ClientInner {
     }
 }
 
-fn bootstrap_leaky_client() -> (Arc<ClientInner>, oneshot::Receiver<()>) {
+/// Why `wait_for_cleanup` gave up on the drop signal.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum CleanupFailure {
+    /// The signal did not arrive within the cleanup timeout
+    TimedOut,
+    /// The sender was dropped without ever signaling
+    SenderDropped,
+}
+
+/// Logout cleanup did not finish; carries how many strong refs are still alive.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct CleanupError {
+    cause: CleanupFailure,
+    remaining_strong: usize,
+}
+
+impl fmt::Display for CleanupError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let what = match self.cause {
+            CleanupFailure::TimedOut => "cleanup wait timed out",
+            CleanupFailure::SenderDropped => "drop signal sender went away without signaling",
+        };
+        write!(
+            f,
+            "{what} ({} strong references still alive)",
+            self.remaining_strong
+        )
+    }
+}
+
+impl std::error::Error for CleanupError {}
+
+/// Tracks which named tasks currently hold a strong `Arc<ClientInner>`.
+#[derive(Clone, Default)]
+struct TaskRegistry {
+    holding: Arc<Mutex<BTreeMap<String, bool>>>,
+}
+
+impl TaskRegistry {
+    /// Marks `name` as holding a strong reference until the guard is dropped.
+    fn hold(&self, name: &str) -> HoldGuard {
+        self.set(name, true);
+        HoldGuard {
+            registry: self.clone(),
+            name: name.to_owned(),
+        }
+    }
+
+    fn set(&self, name: &str, holding: bool) {
+        self.holding
+            .lock()
+            .unwrap()
+            .insert(name.to_owned(), holding);
+    }
+
+    /// Names of tasks that still hold a strong reference, in sorted order.
+    fn holders(&self) -> Vec<String> {
+        self.holding
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|(_, holding)| **holding)
+            .map(|(name, _)| name.clone())
+            .collect()
+    }
+}
+
+/// Clears a task's "holding" flag when the strong reference it guards goes away.
+struct HoldGuard {
+    registry: TaskRegistry,
+    name: String,
+}
+
+impl Drop for HoldGuard {
+    fn drop(&mut self) {
+        self.registry.set(&self.name, false);
+    }
+}
+
+fn bootstrap_leaky_client() -> (Arc<ClientInner>, oneshot::Receiver<()>, TaskRegistry) {
     let (inner, drop_rx) = ClientInner::new(42);
-    spawn_background_tasks(inner.clone());
-    (inner, drop_rx)
+    let registry = TaskRegistry::default();
+    spawn_background_tasks(inner.clone(), &registry);
+    (inner, drop_rx, registry)
 }
 
-fn spawn_background_tasks(inner: Arc<ClientInner>) {
+fn spawn_background_tasks(inner: Arc<ClientInner>, registry: &TaskRegistry) {
     for task_id in 0..3 {
+        spawn_tracked_task(task_id, &inner, registry, true);
+    }
+}
+
+/// Spawns a task that either leaks a strong `Arc` for its whole lifetime or
+/// only upgrades a `Weak` per iteration, recording which one in `registry`.
+fn spawn_tracked_task(
+    task_id: usize,
+    inner: &Arc<ClientInner>,
+    registry: &TaskRegistry,
+    leak: bool,
+) {
+    let name = format!("task-{task_id}");
+    if leak {
         let task_inner = inner.clone();
+        let guard = registry.hold(&name);
         tokio::spawn(async move {
+            let _guard = guard;
             loop {
                 tokio::time::sleep(Duration::from_millis(80)).await;
                 // Each task owns a strong Arc, preventing ClientInner::drop from running.
@@ -51,22 +149,46 @@ fn spawn_background_tasks(inner: Arc<ClientInner>) {
                 );
             }
         });
+    } else {
+        let weak = Arc::downgrade(inner);
+        let registry = registry.clone();
+        registry.set(&name, false);
+        tokio::spawn(async move {
+            loop {
+                tokio::time::sleep(Duration::from_millis(80)).await;
+                let Some(state) = weak.upgrade() else { break };
+                let _guard = registry.hold(&name);
+                log::info!(
+                    "task {task_id} briefly upgraded Weak for client {}",
+                    state.id
+                );
+            }
+        });
     }
 }
 
-async fn wait_for_cleanup(rx: oneshot::Receiver<()>) -> Result<()> {
-    tokio::time::timeout(Duration::from_millis(CLEANUP_TIMEOUT_MS), rx)
-        .await
-        .context("logout stalled: client drop signal never arrived")?
-        .context("drop sender dropped before sending signal")
+async fn wait_for_cleanup<T>(
+    rx: oneshot::Receiver<()>,
+    weak: &Weak<T>,
+) -> Result<(), CleanupError> {
+    let cause = match tokio::time::timeout(Duration::from_millis(CLEANUP_TIMEOUT_MS), rx).await {
+        Ok(Ok(())) => return Ok(()),
+        Ok(Err(_)) => CleanupFailure::SenderDropped,
+        Err(_) => CleanupFailure::TimedOut,
+    };
+    // Either way, report who is still holding on
+    Err(CleanupError {
+        cause,
+        remaining_strong: weak.strong_count(),
+    })
 }
 
-fn install_leaky_client() -> (oneshot::Receiver<()>, Weak<ClientInner>) {
-    let (client, drop_rx) = bootstrap_leaky_client();
+fn install_leaky_client() -> (oneshot::Receiver<()>, Weak<ClientInner>, TaskRegistry) {
+    let (client, drop_rx, registry) = bootstrap_leaky_client();
     let weak = Arc::downgrade(&client);
     log::info!("Strong count before logout: {}", Arc::strong_count(&client));
     drop(client);
-    (drop_rx, weak)
+    (drop_rx, weak, registry)
 }
 
 #[tokio::main(flavor = "current_thread")]
@@ -77,18 +199,19 @@ async fn main() -> Result<()> {
 
     log::info!("Demonstrating how background tasks retaining Arc<Inner> block logout cleanup");
 
-    let (drop_rx, weak) = install_leaky_client();
+    let (drop_rx, weak, registry) = install_leaky_client();
 
-    match wait_for_cleanup(drop_rx).await {
+    match wait_for_cleanup(drop_rx, &weak).await {
         Ok(_) => {
             log::info!("Unexpected success: cleanup notification arrived");
         }
         Err(err) => {
-            log::error!("Logout failed: {err:#}");
+            log::error!("Logout failed: {err}");
             log::error!(
                 "Strong count after dropping last user handle: {}",
-                weak.strong_count()
+                err.remaining_strong
             );
+            log::error!("Tasks still holding the Arc: {:?}", registry.holders());
             log::error!("Drop handler never fired because background tasks leaked the Arc");
         }
     }
@@ -104,8 +227,8 @@ mod tests {
 
     #[tokio::test(flavor = "current_thread")]
     async fn logout_times_out_when_tasks_hold_arc() {
-        let (drop_rx, weak) = install_leaky_client();
-        let result = wait_for_cleanup(drop_rx).await;
+        let (drop_rx, weak, _registry) = install_leaky_client();
+        let result = wait_for_cleanup(drop_rx, &weak).await;
         assert!(
             result.is_err(),
             "cleanup should time out when Arc is leaked"
@@ -115,4 +238,33 @@ mod tests {
             "background tasks should still own the Arc"
         );
     }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn cleanup_error_reports_leaked_strong_count() {
+        let (drop_rx, weak, _registry) = install_leaky_client();
+        let err = wait_for_cleanup(drop_rx, &weak)
+            .await
+            .expect_err("cleanup should time out when Arc is leaked");
+        assert_eq!(err.cause, CleanupFailure::TimedOut);
+        // One strong clone per background task
+        assert_eq!(err.remaining_strong, 3);
+        assert!(err.to_string().contains("3 strong references"));
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn registry_names_the_leaking_tasks() {
+        let (client, drop_rx) = ClientInner::new(7);
+        let registry = TaskRegistry::default();
+        spawn_tracked_task(0, &client, &registry, true);
+        spawn_tracked_task(1, &client, &registry, false);
+        spawn_tracked_task(2, &client, &registry, true);
+        let weak = Arc::downgrade(&client);
+        drop(client);
+
+        let err = wait_for_cleanup(drop_rx, &weak)
+            .await
+            .expect_err("two leaking tasks should block cleanup");
+        assert_eq!(err.remaining_strong, 2);
+        assert_eq!(registry.holders(), vec!["task-0", "task-2"]);
+    }
 }
@@ -1,9 +1,13 @@
-use anyhow::{Context, Result};
+use anyhow::Result;
+use std::fmt;
 use std::sync::{Arc, Mutex, Weak};
 use std::time::Duration;
-use tokio::sync::oneshot;
+use tokio::sync::{broadcast, oneshot};
+use tokio::task::JoinHandle;
+use tokio_util::sync::CancellationToken;
 
 const CLEANUP_TIMEOUT_MS: u64 = 500;
+const TASK_PERIOD_MS: u64 = 80;
 
 /// Inner Matrix client state that background tasks touch.
 /// When the last Arc disappears we send a cleanup confirmation.
@@ -32,51 +36,210 @@ impl Drop for ClientInner {
     }
 }
 
-fn bootstrap_supervised_client() -> (Arc<ClientInner>, oneshot::Receiver<()>) {
-    let (inner, drop_rx) = ClientInner::new(1337);
-    spawn_background_tasks(&inner);
-    (inner, drop_rx)
+/// Why `wait_for_cleanup` gave up on the drop signal.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum CleanupFailure {
+    /// The signal did not arrive within the cleanup timeout
+    TimedOut,
+    /// The sender was dropped without ever signaling
+    SenderDropped,
 }
 
-fn spawn_background_tasks(inner: &Arc<ClientInner>) {
-    for task_id in 0..3 {
-        let weak = Arc::downgrade(inner);
-        tokio::spawn(async move {
-            loop {
+/// Logout cleanup did not finish; carries how many strong refs are still alive.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct CleanupError {
+    cause: CleanupFailure,
+    remaining_strong: usize,
+}
+
+impl fmt::Display for CleanupError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let what = match self.cause {
+            CleanupFailure::TimedOut => "cleanup wait timed out",
+            CleanupFailure::SenderDropped => "drop signal sender went away without signaling",
+        };
+        write!(
+            f,
+            "{what} ({} strong references still alive)",
+            self.remaining_strong
+        )
+    }
+}
+
+impl std::error::Error for CleanupError {}
+
+/// Why a supervised task left its loop.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ExitReason {
+    /// The last strong reference was dropped and `Weak::upgrade` failed
+    ClientDropped,
+    /// The shutdown token was cancelled
+    Cancelled,
+}
+
+/// Lifecycle events published by a `Supervisor`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum TaskEvent {
+    Exited { task_id: usize, reason: ExitReason },
+}
+
+/// Spawns tasks that only ever hold a `Weak<T>`.
+///
+/// Each iteration upgrades the `Weak`, runs the task's closure with a borrow of
+/// the state, and drops the temporary `Arc` before sleeping. Once the last
+/// strong reference is gone the upgrade fails and the task exits on its own.
+///
+/// The sleep between iterations also races a `CancellationToken`, so an
+/// explicit shutdown wakes every task immediately instead of waiting up to a
+/// full period for the next failed upgrade.
+struct Supervisor<T> {
+    target: Weak<T>,
+    period: Duration,
+    shutdown: CancellationToken,
+    events: broadcast::Sender<TaskEvent>,
+    handles: Vec<JoinHandle<()>>,
+}
+
+impl<T: Send + Sync + 'static> Supervisor<T> {
+    fn new(target: Weak<T>, period: Duration) -> Self {
+        Self {
+            target,
+            period,
+            shutdown: CancellationToken::new(),
+            events: broadcast::channel(16).0,
+            handles: Vec::new(),
+        }
+    }
+
+    /// Lets tasks exit as soon as `token` is cancelled.
+    fn with_shutdown(mut self, token: CancellationToken) -> Self {
+        self.shutdown = token;
+        self
+    }
+
+    /// Receives a `TaskEvent::Exited` for every task that leaves after this call.
+    fn subscribe(&self) -> broadcast::Receiver<TaskEvent> {
+        self.events.subscribe()
+    }
+
+    /// Spawns a task that calls `tick` once per period while the target is alive.
+    fn spawn<F>(&mut self, task_id: usize, mut tick: F)
+    where
+        F: FnMut(&T) + Send + 'static,
+    {
+        let weak = self.target.clone();
+        let period = self.period;
+        let shutdown = self.shutdown.clone();
+        let events = self.events.clone();
+        self.handles.push(tokio::spawn(async move {
+            let reason = loop {
                 match weak.upgrade() {
-                    Some(state) => {
-                        log::info!(
-                            "task {task_id} borrowed Arc for client {} and released it before await",
-                            state.id
-                        );
-                    }
+                    // The Arc lives only for this statement, never across the await
+                    Some(state) => tick(&state),
                     None => {
                         log::info!(
                             "task {task_id} noticed client drop, exiting without holding Arc"
                         );
-                        break;
+                        break ExitReason::ClientDropped;
                     }
                 }
 
-                tokio::time::sleep(Duration::from_millis(80)).await;
-            }
+                tokio::select! {
+                    _ = shutdown.cancelled() => {
+                        log::info!("task {task_id} cancelled, exiting without waiting for drop");
+                        break ExitReason::Cancelled;
+                    }
+                    _ = tokio::time::sleep(period) => {}
+                }
+            };
+            // Nobody listening is fine; the event is purely for observability
+            let _ = events.send(TaskEvent::Exited { task_id, reason });
+        }));
+    }
+
+    /// Join handles of every task spawned so far.
+    fn into_handles(self) -> Vec<JoinHandle<()>> {
+        self.handles
+    }
+}
+
+fn bootstrap_supervised_client(
+    shutdown: &CancellationToken,
+) -> (
+    Arc<ClientInner>,
+    oneshot::Receiver<()>,
+    Supervisor<ClientInner>,
+) {
+    let (inner, drop_rx) = ClientInner::new(1337);
+    let supervisor = spawn_background_tasks(&inner, shutdown.clone());
+    (inner, drop_rx, supervisor)
+}
+
+fn spawn_background_tasks(
+    inner: &Arc<ClientInner>,
+    shutdown: CancellationToken,
+) -> Supervisor<ClientInner> {
+    let mut supervisor =
+        Supervisor::new(Arc::downgrade(inner), Duration::from_millis(TASK_PERIOD_MS))
+            .with_shutdown(shutdown);
+    for task_id in 0..3 {
+        supervisor.spawn(task_id, move |state| {
+            log::info!(
+                "task {task_id} borrowed Arc for client {} and released it before await",
+                state.id
+            );
         });
     }
+    supervisor
 }
 
-async fn wait_for_cleanup(rx: oneshot::Receiver<()>) -> Result<()> {
-    tokio::time::timeout(Duration::from_millis(CLEANUP_TIMEOUT_MS), rx)
-        .await
-        .context("cleanup wait timed out")?
-        .context("drop sender dropped before signaling")
+async fn wait_for_cleanup<T>(
+    rx: oneshot::Receiver<()>,
+    weak: &Weak<T>,
+) -> Result<(), CleanupError> {
+    let cause = match tokio::time::timeout(Duration::from_millis(CLEANUP_TIMEOUT_MS), rx).await {
+        Ok(Ok(())) => return Ok(()),
+        Ok(Err(_)) => CleanupFailure::SenderDropped,
+        Err(_) => CleanupFailure::TimedOut,
+    };
+    // Either way, report who is still holding on
+    Err(CleanupError {
+        cause,
+        remaining_strong: weak.strong_count(),
+    })
 }
 
-fn install_supervised_client() -> (oneshot::Receiver<()>, Weak<ClientInner>) {
-    let (client, drop_rx) = bootstrap_supervised_client();
+/// Waits up to `timeout` for every task to exit on its own, then aborts the
+/// stragglers. Returns how many tasks had to be force-killed.
+async fn shutdown_or_abort(handles: Vec<JoinHandle<()>>, timeout: Duration) -> usize {
+    let deadline = tokio::time::Instant::now() + timeout;
+    let mut aborted = 0;
+    for (task_id, mut handle) in handles.into_iter().enumerate() {
+        match tokio::time::timeout_at(deadline, &mut handle).await {
+            Ok(Ok(())) => {}
+            Ok(Err(err)) => log::warn!("task {task_id} failed during shutdown: {err}"),
+            Err(_) => {
+                log::warn!("task {task_id} ignored shutdown, aborting it");
+                handle.abort();
+                // Aborting drops the task's future and with it any Arc it was holding
+                let _ = handle.await;
+                aborted += 1;
+            }
+        }
+    }
+    aborted
+}
+
+fn install_supervised_client() -> (
+    oneshot::Receiver<()>,
+    Weak<ClientInner>,
+    Vec<JoinHandle<()>>,
+) {
+    let (client, drop_rx, supervisor) = bootstrap_supervised_client(&CancellationToken::new());
     log::info!("Strong count before logout: {}", Arc::strong_count(&client));
     let weak = Arc::downgrade(&client);
     drop(client);
-    (drop_rx, weak)
+    (drop_rx, weak, supervisor.into_handles())
 }
 
 #[tokio::main(flavor = "current_thread")]
@@ -87,15 +250,40 @@ async fn main() -> Result<()> {
 
     log::info!("Running the supervised logout that uses Weak references in tasks");
 
-    let (drop_rx, weak) = install_supervised_client();
-    wait_for_cleanup(drop_rx).await?;
+    let (drop_rx, weak, handles) = install_supervised_client();
+    wait_for_cleanup(drop_rx, &weak).await?;
 
     log::info!(
         "Cleanup confirmed in time. Remaining strong references: {}",
         weak.strong_count()
     );
 
-    tokio::time::sleep(Duration::from_millis(200)).await;
+    let aborted = shutdown_or_abort(handles, Duration::from_millis(CLEANUP_TIMEOUT_MS)).await;
+    log::info!("All supervised tasks exited ({aborted} force-aborted)");
+
+    log::info!("Running the cancellable logout that signals tasks before dropping the client");
+    let shutdown = CancellationToken::new();
+    let (client, drop_rx, supervisor) = bootstrap_supervised_client(&shutdown);
+    let mut events = supervisor.subscribe();
+    let started = tokio::time::Instant::now();
+    shutdown.cancel();
+    let aborted = shutdown_or_abort(
+        supervisor.into_handles(),
+        Duration::from_millis(CLEANUP_TIMEOUT_MS),
+    )
+    .await;
+    log::info!(
+        "Cancelled tasks exited after {:?} ({aborted} force-aborted)",
+        started.elapsed()
+    );
+    while let Ok(TaskEvent::Exited { task_id, reason }) = events.try_recv() {
+        log::info!("task {task_id} exited: {reason:?}");
+    }
+
+    let weak = Arc::downgrade(&client);
+    drop(client);
+    wait_for_cleanup(drop_rx, &weak).await?;
+    log::info!("Cleanup confirmed after cancellation");
     Ok(())
 }
 
@@ -105,10 +293,165 @@ mod tests {
 
     #[tokio::test(flavor = "current_thread")]
     async fn logout_completes_when_tasks_use_weak() {
-        let (drop_rx, weak) = install_supervised_client();
-        wait_for_cleanup(drop_rx)
+        let (drop_rx, weak, _handles) = install_supervised_client();
+        wait_for_cleanup(drop_rx, &weak)
             .await
             .expect("cleanup should finish when tasks only hold Weak refs");
         assert_eq!(weak.strong_count(), 0);
     }
+
+    struct MyState {
+        drop_tx: Mutex<Option<oneshot::Sender<()>>>,
+    }
+
+    impl Drop for MyState {
+        fn drop(&mut self) {
+            if let Some(tx) = self.drop_tx.lock().unwrap().take() {
+                let _ = tx.send(());
+            }
+        }
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn supervisor_tasks_exit_when_state_drops() {
+        let (drop_tx, drop_rx) = oneshot::channel();
+        let state = Arc::new(MyState {
+            drop_tx: Mutex::new(Some(drop_tx)),
+        });
+
+        let ticks = Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        let mut supervisor = Supervisor::new(Arc::downgrade(&state), Duration::from_millis(10));
+        for task_id in 0..3 {
+            let ticks = Arc::clone(&ticks);
+            supervisor.spawn(task_id, move |_state: &MyState| {
+                ticks.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+            });
+        }
+
+        tokio::time::sleep(Duration::from_millis(25)).await;
+        assert!(ticks.load(std::sync::atomic::Ordering::SeqCst) >= 3);
+
+        let weak = Arc::downgrade(&state);
+        drop(state);
+        wait_for_cleanup(drop_rx, &weak)
+            .await
+            .expect("drop signal should fire once the last strong ref is gone");
+
+        for handle in supervisor.into_handles() {
+            tokio::time::timeout(Duration::from_millis(CLEANUP_TIMEOUT_MS), handle)
+                .await
+                .expect("supervised task should exit after the drop")
+                .unwrap();
+        }
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn cleanup_error_reports_remaining_strong_count() {
+        let (client, drop_rx, _supervisor) = bootstrap_supervised_client(&CancellationToken::new());
+        let weak = Arc::downgrade(&client);
+        let err = wait_for_cleanup(drop_rx, &weak)
+            .await
+            .expect_err("cleanup cannot finish while the caller still owns the client");
+        assert_eq!(err.cause, CleanupFailure::TimedOut);
+        assert_eq!(err.remaining_strong, 1);
+        drop(client);
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn cleanup_error_distinguishes_dropped_sender() {
+        let (client, drop_rx) = ClientInner::new(5);
+        // Disarm the drop signal so the sender goes away without sending
+        drop(client.drop_tx.lock().unwrap().take());
+        let weak = Arc::downgrade(&client);
+        let err = wait_for_cleanup(drop_rx, &weak)
+            .await
+            .expect_err("a dropped sender can never confirm cleanup");
+        assert_eq!(err.cause, CleanupFailure::SenderDropped);
+        assert_eq!(err.remaining_strong, 1);
+        assert!(err.to_string().contains("without signaling"));
+        drop(client);
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn shutdown_or_abort_kills_leaky_tasks() {
+        let (client, drop_rx) = ClientInner::new(9);
+        let mut handles = Vec::new();
+        for _ in 0..2 {
+            // Deliberately leaky: the task owns a strong Arc forever
+            let leaked = Arc::clone(&client);
+            handles.push(tokio::spawn(async move {
+                loop {
+                    tokio::time::sleep(Duration::from_millis(10)).await;
+                    let _ = leaked.id;
+                }
+            }));
+        }
+        let supervisor = spawn_background_tasks(&client, CancellationToken::new());
+        handles.extend(supervisor.into_handles());
+        let weak = Arc::downgrade(&client);
+        drop(client);
+
+        let aborted = shutdown_or_abort(handles, Duration::from_millis(150)).await;
+        // The two leaky tasks never exit; the Weak ones may squeeze out once they are gone
+        assert!(
+            aborted >= 2,
+            "expected the leaky tasks to be aborted, got {aborted}"
+        );
+        assert_eq!(weak.strong_count(), 0);
+        wait_for_cleanup(drop_rx, &weak)
+            .await
+            .expect("aborting the leaky tasks should release the last Arc");
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn cancellation_exits_before_next_period() {
+        let shutdown = CancellationToken::new();
+        let (client, drop_rx, supervisor) = bootstrap_supervised_client(&shutdown);
+        // Let every task finish its first tick and park in the 80ms sleep
+        tokio::time::sleep(Duration::from_millis(5)).await;
+
+        let started = std::time::Instant::now();
+        shutdown.cancel();
+        let aborted =
+            shutdown_or_abort(supervisor.into_handles(), Duration::from_millis(200)).await;
+        let elapsed = started.elapsed();
+        assert_eq!(aborted, 0);
+        // Without the cancellation race, tasks would sit out the rest of their period
+        assert!(
+            elapsed < Duration::from_millis(TASK_PERIOD_MS),
+            "cancelled tasks took {elapsed:?} to exit, a full {TASK_PERIOD_MS}ms period"
+        );
+
+        let weak = Arc::downgrade(&client);
+        drop(client);
+        wait_for_cleanup(drop_rx, &weak)
+            .await
+            .expect("drop signal should still fire after cancellation");
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn exit_events_report_client_dropped() {
+        let (client, drop_rx, supervisor) = bootstrap_supervised_client(&CancellationToken::new());
+        let mut events = supervisor.subscribe();
+        let weak = Arc::downgrade(&client);
+        drop(client);
+        wait_for_cleanup(drop_rx, &weak).await.unwrap();
+
+        let aborted =
+            shutdown_or_abort(supervisor.into_handles(), Duration::from_millis(200)).await;
+        assert_eq!(aborted, 0);
+
+        let mut exited = Vec::new();
+        while let Ok(event) = events.try_recv() {
+            exited.push(event);
+        }
+        exited.sort_by_key(|TaskEvent::Exited { task_id, .. }| *task_id);
+        let expected: Vec<_> = (0..3)
+            .map(|task_id| TaskEvent::Exited {
+                task_id,
+                reason: ExitReason::ClientDropped,
+            })
+            .collect();
+        assert_eq!(exited, expected);
+    }
 }
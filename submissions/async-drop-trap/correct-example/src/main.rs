@@ -4,89 +4,565 @@
 //! Instead of relying on Drop for async cleanup, provide an explicit async shutdown method.
 //! This allows proper resource cleanup while maintaining Rust's safety guarantees.
 
+use futures::future::join_all;
 use std::fs::File;
+use std::future::Future;
 use std::io::Write as IoWrite;
-use std::path::PathBuf;
+use std::marker::PhantomData;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::Arc;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 use tokio::sync::{watch, Notify};
-use tokio::task::JoinHandle;
+use tokio::task::{AbortHandle, JoinHandle};
+use tokio_util::sync::CancellationToken;
+
+/// A resource that needs async cleanup before it can be dropped
+///
+/// This is the "AsyncDrop" the language doesn't have yet: implementors do their
+/// real cleanup in `shutdown()` and keep `Drop` only as a safety net that warns
+/// and aborts when `shutdown()` was skipped.
+///
+/// Like the proposed `AsyncDrop`, `shutdown()` borrows the resource instead of
+/// consuming it, so implementors must reject a second call themselves.
+trait AsyncShutdown {
+    /// What the resource reports once its cleanup has finished
+    type Output;
+
+    /// Runs the resource's async cleanup to completion
+    async fn shutdown(&mut self) -> Self::Output;
+}
+
+/// ✅ Shuts down every resource concurrently and waits for all of them
+async fn shutdown_all<R: AsyncShutdown>(mut resources: Vec<R>) -> Vec<R::Output> {
+    join_all(resources.iter_mut().map(AsyncShutdown::shutdown)).await
+}
+
+/// Why a shutdown request did not produce a [`ShutdownReport`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ShutdownError {
+    /// `shutdown()` or `shutdown_with_timeout()` was already called
+    AlreadyShutDown,
+}
+
+impl std::fmt::Display for ShutdownError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ShutdownError::AlreadyShutDown => write!(f, "Worker was already shut down"),
+        }
+    }
+}
+
+impl std::error::Error for ShutdownError {}
+
+/// Why [`WorkerSupervisor::depends_on`] rejected a dependency
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum DependencyError {
+    /// The path does not belong to a worker owned by the supervisor
+    UnknownWorker(PathBuf),
+    /// The dependency would make some worker wait on itself
+    Cycle,
+}
+
+impl std::fmt::Display for DependencyError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DependencyError::UnknownWorker(path) => write!(f, "No worker writes to {:?}", path),
+            DependencyError::Cycle => write!(f, "Dependency would create a cycle"),
+        }
+    }
+}
+
+impl std::error::Error for DependencyError {}
+
+/// What a worker's task did before it exited
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+struct ShutdownReport {
+    /// Items handed to the sink before the task stopped
+    items_processed: usize,
+    /// Whether the sink's cleanup (removing the temp file) succeeded
+    file_removed: bool,
+    /// `true` if the work ran out on its own, `false` if it was signalled
+    completed_naturally: bool,
+    /// `true` if cleanup exceeded `Config::shutdown_timeout` and the task was aborted
+    timed_out: bool,
+    /// When the task finished its cleanup; `None` if it never got that far
+    cleanup_finished_at: Option<Instant>,
+    /// `true` if cleanup exceeded `Config::cleanup_timeout` and the watchdog aborted it
+    cleanup_timed_out: bool,
+}
+
+/// Tunables for a [`BackgroundWorker`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Config {
+    /// How long `shutdown()` waits for cleanup before aborting the task
+    shutdown_timeout: Duration,
+    /// How long the watchdog lets `Sink::close` run before aborting the task
+    ///
+    /// Unlike `shutdown_timeout` this also covers cleanup started by natural
+    /// completion or a cancelled parent token, when nobody awaits `shutdown()`.
+    cleanup_timeout: Duration,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            shutdown_timeout: Duration::from_secs(5),
+            cleanup_timeout: Duration::from_secs(3),
+        }
+    }
+}
+
+/// Consumes the items a [`Work`] produces (a file, a network flush, ...)
+///
+/// Both methods are async so a sink can await I/O; a sink that never resolves
+/// is exactly what `Config::shutdown_timeout` protects against.
+trait Sink<T>: Send + 'static {
+    /// Consumes a single item
+    fn consume(&mut self, item: T) -> impl Future<Output = ()> + Send;
+
+    /// Releases the sink; always called once the work loop exits
+    ///
+    /// Returns whether cleanup succeeded.
+    fn close(self) -> impl Future<Output = bool> + Send;
+}
+
+/// The work a [`BackgroundWorker`] drives: a producer plus the sink that consumes it
+///
+/// The worker owns the graceful-shutdown machinery; implementors only decide
+/// what an item is and where it goes.
+trait Work: Send + 'static {
+    type Item: std::fmt::Debug + Send;
+    type Sink: Sink<Self::Item>;
+    /// What the worker's owner can still look up once the work has moved
+    /// into the task, e.g. the file a [`TempFileWork`] writes to
+    type Info: Send + Sync + 'static;
+
+    /// Captures [`Work::Info`]; called once, before the task is spawned
+    fn info(&self) -> Self::Info;
+
+    /// Opens the sink; called from inside the spawned task
+    fn open_sink(&mut self) -> Self::Sink;
+
+    /// Produces the next item, or `None` once the work is done
+    fn next_item(&mut self) -> Option<Self::Item>;
+}
+
+/// Distinguishes temp files created by this process; see [`unique_temp_path`]
+static NEXT_TEMP_ID: AtomicUsize = AtomicUsize::new(0);
+
+/// Appends `-<pid>-<n>` to the file stem of `path`
+///
+/// `/tmp/worker.log` becomes e.g. `/tmp/worker-4242-0.log`, so workers given
+/// the same path, in this process or a parallel test binary, never share a file.
+fn unique_temp_path(path: &Path) -> PathBuf {
+    let id = NEXT_TEMP_ID.fetch_add(1, Ordering::Relaxed);
+    let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+    let mut name = format!("{}-{}-{}", stem, std::process::id(), id);
+    if let Some(extension) = path.extension() {
+        name.push('.');
+        name.push_str(&extension.to_string_lossy());
+    }
+    path.with_file_name(name)
+}
+
+/// Where a [`TempFileWork`] writes, and how to spawn another worker like it
+#[derive(Debug, Clone)]
+struct TempFileInfo {
+    /// The file actually written to
+    path: PathBuf,
+    /// Recipe for `restart()`
+    builder: WorkerBuilder,
+}
+
+/// Default work: writes "Processing item N" lines into a temporary file
+struct TempFileWork {
+    info: TempFileInfo,
+    next: usize,
+    total: usize,
+}
+
+impl TempFileWork {
+    /// Work writing to exactly `path`; `builder` spawns its replacement on restart
+    fn new(path: PathBuf, builder: WorkerBuilder) -> Self {
+        Self {
+            info: TempFileInfo { path, builder },
+            next: 0,
+            total: 10,
+        }
+    }
+}
+
+/// Temporary file that is removed again when the sink is closed
+struct TempFileSink {
+    path: PathBuf,
+    file: File,
+}
+
+impl Sink<usize> for TempFileSink {
+    async fn consume(&mut self, item: usize) {
+        writeln!(self.file, "Processing item {}", item).expect("Failed to write to file");
+        self.file.flush().expect("Failed to flush");
+    }
+
+    async fn close(self) -> bool {
+        println!("[Worker] Flushing and closing file...");
+        drop(self.file);
+
+        // Clean up the temporary file
+        if std::fs::remove_file(&self.path).is_ok() {
+            println!("[Worker] ✓ Cleaned up temporary file: {:?}", self.path);
+            true
+        } else {
+            eprintln!("[Worker] ✗ Failed to clean up temporary file");
+            false
+        }
+    }
+}
+
+impl Work for TempFileWork {
+    type Item = usize;
+    type Sink = TempFileSink;
+    type Info = TempFileInfo;
+
+    fn info(&self) -> TempFileInfo {
+        self.info.clone()
+    }
+
+    fn open_sink(&mut self) -> TempFileSink {
+        let file = File::create(&self.info.path).expect("Failed to create temp file");
+        TempFileSink {
+            path: self.info.path.clone(),
+            file,
+        }
+    }
+
+    fn next_item(&mut self) -> Option<usize> {
+        if self.next >= self.total {
+            return None;
+        }
+        self.next += 1;
+        Some(self.next - 1)
+    }
+}
+
+/// Lifecycle of a [`BackgroundWorker`] as seen by its owner
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum WorkerState {
+    Running,
+    /// A shutdown has been requested; any further request is rejected
+    ShutDown,
+}
 
 /// A background worker with proper async cleanup
-struct BackgroundWorker {
+struct BackgroundWorker<W: Work = TempFileWork> {
+    /// Whether shutdown has already been requested
+    state: WorkerState,
+    /// Shutdown tunables; [`Config::default`] unless set via `with_config`
+    ///
+    /// A watch channel so the cleanup watchdog sees changes made after spawning.
+    config: watch::Sender<Config>,
     /// The async task handle (Option allows taking in shutdown)
     task_handle: Option<JoinHandle<()>>,
-    /// Sender to signal shutdown
-    shutdown_tx: watch::Sender<bool>,
-    /// Path to temporary log file
-    temp_file: PathBuf,
+    /// Cancelled to signal shutdown; may be a child of a caller's token
+    shutdown_token: CancellationToken,
     /// Notified when cleanup is complete
     shutdown_complete: Arc<Notify>,
+    /// Progress so far; the task publishes the final report just before it notifies
+    report_rx: watch::Receiver<ShutdownReport>,
+    /// Captured from the work before it moved into the task
+    info: W::Info,
+    /// Items consumed so far, bumped by the task after each one
+    items_processed: Arc<AtomicUsize>,
+    /// `true` while the task should hold off on new work
+    pause_tx: watch::Sender<bool>,
+    /// Aborts the task if cleanup outlives `Config::cleanup_timeout`
+    watchdog: JoinHandle<()>,
+    _work: PhantomData<fn() -> W>,
+}
+
+/// Everything needed to spawn a [`TempFileWork`] worker, kept so an
+/// equivalent worker can be spawned again after the first one shut down
+#[derive(Debug, Clone)]
+struct WorkerBuilder {
+    /// Template path; every spawned worker gets its own unique file from it
+    temp_file: PathBuf,
+    /// Token each spawned worker gets a child of; `None` for standalone workers
+    parent_token: Option<CancellationToken>,
+    config: Config,
+}
+
+impl WorkerBuilder {
+    fn new(temp_file: impl AsRef<Path>) -> Self {
+        Self {
+            temp_file: temp_file.as_ref().to_path_buf(),
+            parent_token: None,
+            config: Config::default(),
+        }
+    }
+
+    /// Ties every spawned worker to `token` through a child token
+    fn parent_token(mut self, token: CancellationToken) -> Self {
+        self.parent_token = Some(token);
+        self
+    }
+
+    /// Replaces the default [`Config`] of every spawned worker
+    fn config(mut self, config: Config) -> Self {
+        self.config = config;
+        self
+    }
+
+    /// Spawns a fresh worker with its own temp file
+    ///
+    /// A builder whose parent token is already cancelled spawns a worker that
+    /// stops and cleans up right away.
+    fn spawn(&self) -> BackgroundWorker {
+        let shutdown_token = match &self.parent_token {
+            Some(parent) => parent.child_token(),
+            None => CancellationToken::new(),
+        };
+        let work = TempFileWork::new(unique_temp_path(&self.temp_file), self.clone());
+        BackgroundWorker::with_work(work, shutdown_token).with_config(self.config)
+    }
 }
 
 impl BackgroundWorker {
+    /// Spawns a worker that writes into a temp file derived from `temp_file`
+    /// and removes it on shutdown
+    ///
+    /// The actual file gets a unique suffix (see [`unique_temp_path`]); use
+    /// [`BackgroundWorker::path`] to find it.
+    fn new(temp_file: impl AsRef<Path>) -> Self {
+        WorkerBuilder::new(temp_file).spawn()
+    }
+
+    /// Like [`BackgroundWorker::new`], but stops when `shutdown_token` is cancelled
+    ///
+    /// Pass `parent.child_token()` to tie the worker into a shutdown tree:
+    /// cancelling the parent cleans up every worker below it.
+    fn with_token(temp_file: impl AsRef<Path>, shutdown_token: CancellationToken) -> Self {
+        WorkerBuilder::new(temp_file)
+            .parent_token(shutdown_token)
+            .spawn()
+    }
+
+    /// The temp file this worker actually writes to
+    fn path(&self) -> &Path {
+        &self.info.path
+    }
+
+    /// The recipe this worker was spawned from
+    fn builder(&self) -> &WorkerBuilder {
+        &self.info.builder
+    }
+
+    /// Shuts this worker down and returns an equivalent, freshly running one
+    ///
+    /// The old temp file is gone before the new worker creates its own, even
+    /// if the old cleanup timed out. Restarting a worker that was already
+    /// shut down just spawns the replacement.
+    async fn restart(mut self) -> Self {
+        // The replacement keeps any config set on this worker after it was built
+        let builder = self.builder().clone().config(*self.config.borrow());
+        let old_file = self.path().to_path_buf();
+
+        println!("[Restart] Restarting worker for {:?}...", old_file);
+        match self.shutdown().await {
+            Ok(report) => println!("[Restart] Old worker stopped: {:?}", report),
+            Err(e) => println!("[Restart] Old worker not running: {}", e),
+        }
+        if old_file.exists() && std::fs::remove_file(&old_file).is_err() {
+            eprintln!("[Restart] ✗ Failed to remove stale file {:?}", old_file);
+        }
+
+        builder.spawn()
+    }
+}
+
+impl<W: Work> BackgroundWorker<W> {
     /// Spawns a new background worker with graceful shutdown capability
-    fn new(temp_file: PathBuf) -> Self {
-        let (shutdown_tx, mut shutdown_rx) = watch::channel(false);
+    fn with_work(mut work: W, shutdown_token: CancellationToken) -> Self {
+        let info = work.info();
+        let cancelled = shutdown_token.clone();
         let shutdown_complete = Arc::new(Notify::new());
         let shutdown_complete_clone = shutdown_complete.clone();
-        let file_path = temp_file.clone();
+        let (report_tx, report_rx) = watch::channel(ShutdownReport::default());
+        let (pause_tx, mut pause_rx) = watch::channel(false);
+        let items_processed = Arc::new(AtomicUsize::new(0));
+        let items_processed_clone = items_processed.clone();
+        let (config, config_rx) = watch::channel(Config::default());
+        let cleanup_started = Arc::new(Notify::new());
+        let cleanup_started_clone = cleanup_started.clone();
+        let watchdog_report_tx = report_tx.clone();
+        let watchdog_shutdown_complete = shutdown_complete.clone();
 
         let task_handle = tokio::spawn(async move {
             println!("[Worker] Starting background task...");
 
-            // Create and write to temporary file
-            let mut file = File::create(&file_path)
-                .expect("Failed to create temp file");
+            let mut sink = work.open_sink();
+            let mut report = ShutdownReport::default();
 
             // Main work loop with shutdown monitoring
-            let mut item_count = 0;
             loop {
                 tokio::select! {
                     // Check for shutdown signal
-                    _ = shutdown_rx.changed() => {
-                        if *shutdown_rx.borrow() {
-                            println!("[Worker] Shutdown signal received, starting cleanup...");
+                    _ = cancelled.cancelled() => {
+                        println!("[Worker] Shutdown signal received, starting cleanup...");
+                        break;
+                    }
+                    // Pause/resume requests; a dropped sender means the owner is gone
+                    changed = pause_rx.changed() => {
+                        if changed.is_err() {
                             break;
                         }
+                        if *pause_rx.borrow() {
+                            println!("[Worker] Paused");
+                        } else {
+                            println!("[Worker] Resumed");
+                        }
                     }
-                    // Do work
-                    _ = tokio::time::sleep(Duration::from_millis(100)) => {
-                        if item_count >= 10 {
+                    // Do work, unless paused
+                    _ = tokio::time::sleep(Duration::from_millis(100)), if !*pause_rx.borrow() => {
+                        let Some(item) = work.next_item() else {
                             println!("[Worker] Work completed naturally");
+                            report.completed_naturally = true;
                             break;
-                        }
-                        writeln!(file, "Processing item {}", item_count)
-                            .expect("Failed to write to file");
-                        file.flush().expect("Failed to flush");
-                        println!("[Worker] Processed item {}", item_count);
-                        item_count += 1;
+                        };
+                        println!("[Worker] Processed item {:?}", item);
+                        sink.consume(item).await;
+                        report.items_processed += 1;
+                        items_processed_clone.fetch_add(1, Ordering::Relaxed);
+                        report_tx.send_replace(report);
                     }
                 }
             }
 
-            // ✅ CRITICAL CLEANUP CODE - always executed
-            println!("[Worker] Flushing and closing file...");
-            drop(file);
-
-            // Clean up the temporary file
-            if std::fs::remove_file(&file_path).is_ok() {
-                println!("[Worker] ✓ Cleaned up temporary file: {:?}", file_path);
-            } else {
-                eprintln!("[Worker] ✗ Failed to clean up temporary file");
-            }
+            // ✅ CRITICAL CLEANUP CODE - always executed (or aborted by the watchdog)
+            cleanup_started_clone.notify_one();
+            report.file_removed = sink.close().await;
+            report.cleanup_finished_at = Some(Instant::now());
 
-            // Notify that cleanup is complete
+            // Publish the report, then notify that cleanup is complete
+            report_tx.send_replace(report);
             shutdown_complete_clone.notify_one();
             println!("[Worker] Task shutdown complete");
         });
 
+        let watchdog = tokio::spawn(Self::watch_cleanup(
+            task_handle.abort_handle(),
+            cleanup_started,
+            config_rx,
+            watchdog_report_tx,
+            watchdog_shutdown_complete,
+        ));
+
         Self {
+            state: WorkerState::Running,
+            config,
             task_handle: Some(task_handle),
-            shutdown_tx,
-            temp_file,
+            shutdown_token,
             shutdown_complete,
+            report_rx,
+            info,
+            items_processed,
+            pause_tx,
+            watchdog,
+            _work: PhantomData,
+        }
+    }
+
+    /// Watchdog: once cleanup starts, aborts the task if it runs past `Config::cleanup_timeout`
+    ///
+    /// On abort it publishes the last report with `cleanup_timed_out: true` and
+    /// signals completion itself, so `shutdown()` returns instead of waiting
+    /// on a cleanup that will never finish.
+    async fn watch_cleanup(
+        task: AbortHandle,
+        cleanup_started: Arc<Notify>,
+        config: watch::Receiver<Config>,
+        report_tx: watch::Sender<ShutdownReport>,
+        shutdown_complete: Arc<Notify>,
+    ) {
+        cleanup_started.notified().await;
+        let cleanup_timeout = config.borrow().cleanup_timeout;
+
+        let mut report_rx = report_tx.subscribe();
+        let finished = report_rx.wait_for(|report| report.cleanup_finished_at.is_some());
+        if tokio::time::timeout(cleanup_timeout, finished)
+            .await
+            .is_ok()
+        {
+            return;
         }
+
+        eprintln!(
+            "[Watchdog] ✗ Cleanup exceeded {:?}, aborting task",
+            cleanup_timeout
+        );
+        task.abort();
+        report_tx.send_modify(|report| {
+            report.file_removed = false;
+            report.cleanup_timed_out = true;
+        });
+        shutdown_complete.notify_one();
+    }
+
+    /// Whether the task is still alive and no shutdown has been requested
+    ///
+    /// Turns `false` once `shutdown()` is called, the task finishes its work
+    /// on its own, or a parent token stops it.
+    fn is_running(&self) -> bool {
+        self.state == WorkerState::Running
+            && self
+                .task_handle
+                .as_ref()
+                .is_some_and(|handle| !handle.is_finished())
+    }
+
+    /// Items the task has consumed so far
+    ///
+    /// Cheap to poll; a supervisor can restart a worker whose count stops
+    /// moving while it is not paused.
+    fn items_processed(&self) -> usize {
+        self.items_processed.load(Ordering::Relaxed)
+    }
+
+    /// Stops the task from doing new work without tearing it down
+    ///
+    /// A paused worker still reacts to shutdown and runs its full cleanup.
+    fn pause(&self) {
+        self.pause_tx.send_replace(true);
+    }
+
+    /// Lets a paused worker pick up work again
+    fn resume(&self) {
+        self.pause_tx.send_replace(false);
+    }
+
+    /// Replaces the default [`Config`]
+    ///
+    /// The config is kept for workers spawned by `restart()` as well.
+    fn with_config(self, config: Config) -> Self {
+        self.config.send_replace(config);
+        self
+    }
+
+    /// Last published progress, flagged as timed out
+    fn timed_out_report(&self) -> ShutdownReport {
+        ShutdownReport {
+            timed_out: true,
+            ..*self.report_rx.borrow()
+        }
+    }
+
+    /// Moves the worker into [`WorkerState::ShutDown`], rejecting a second request
+    fn begin_shutdown(&mut self) -> Result<(), ShutdownError> {
+        if self.state == WorkerState::ShutDown {
+            return Err(ShutdownError::AlreadyShutDown);
+        }
+        self.state = WorkerState::ShutDown;
+        Ok(())
     }
 
     /// ✅ SOLUTION: Explicit async shutdown method
@@ -95,33 +571,58 @@ impl BackgroundWorker {
     /// 1. Sends a shutdown signal to the task
     /// 2. Waits for the task to complete its cleanup
     /// 3. Joins the task handle to ensure it has finished
+    /// 4. Returns the report the task published during cleanup
     ///
-    /// This pattern ensures all async cleanup code runs to completion.
-    async fn shutdown(mut self) {
+    /// This pattern ensures all async cleanup code runs to completion. If it
+    /// takes longer than `Config::shutdown_timeout`, the task is aborted and
+    /// the report has `timed_out: true`.
+    /// Calling it again returns [`ShutdownError::AlreadyShutDown`].
+    async fn shutdown(&mut self) -> Result<ShutdownReport, ShutdownError> {
+        self.begin_shutdown()?;
         println!("[Shutdown] Initiating graceful shutdown...");
 
         // Step 1: Signal the task to shutdown
-        if self.shutdown_tx.send(true).is_err() {
-            eprintln!("[Shutdown] Warning: task already finished");
-        }
+        self.shutdown_token.cancel();
 
-        // Step 2: Wait for cleanup to complete
+        // Step 2: Wait for cleanup to complete, but never longer than configured
         println!("[Shutdown] Waiting for cleanup to complete...");
-        self.shutdown_complete.notified().await;
+        let timeout = self.config.borrow().shutdown_timeout;
+        if tokio::time::timeout(timeout, self.shutdown_complete.notified())
+            .await
+            .is_err()
+        {
+            eprintln!("[Shutdown] ✗ Cleanup exceeded {:?}, aborting task", timeout);
+            if let Some(handle) = self.task_handle.take() {
+                handle.abort();
+            }
+            return Ok(self.timed_out_report());
+        }
 
         // Step 3: Join the task to ensure it has exited
         if let Some(handle) = self.task_handle.take() {
             match handle.await {
                 Ok(()) => println!("[Shutdown] ✓ Task joined successfully"),
+                Err(e) if e.is_cancelled() => {
+                    eprintln!("[Shutdown] ✗ Task aborted by the watchdog")
+                }
                 Err(e) => eprintln!("[Shutdown] ✗ Task panicked: {}", e),
             }
         }
+
+        Ok(*self.report_rx.borrow())
     }
 
     /// Alternative: async method that can be called explicitly
     /// This allows for timeout handling and error recovery
-    async fn shutdown_with_timeout(mut self, timeout: Duration) -> Result<(), &'static str> {
-        self.shutdown_tx.send(true).ok();
+    ///
+    /// Like `shutdown()`, a timeout is not an error: the task is aborted and
+    /// the report has `timed_out: true`.
+    async fn shutdown_with_timeout(
+        &mut self,
+        timeout: Duration,
+    ) -> Result<ShutdownReport, ShutdownError> {
+        self.begin_shutdown()?;
+        self.shutdown_token.cancel();
 
         let handle = self.task_handle.take();
 
@@ -130,21 +631,212 @@ impl BackgroundWorker {
                 if let Some(h) = handle {
                     h.await.ok();
                 }
-                Ok(())
+                Ok(*self.report_rx.borrow())
             }
             _ = tokio::time::sleep(timeout) => {
                 eprintln!("[Shutdown] Timeout reached, aborting task");
                 if let Some(h) = handle {
                     h.abort();
                 }
-                Err("Shutdown timed out")
+                Ok(self.timed_out_report())
             }
         }
     }
 }
 
+impl<W: Work> AsyncShutdown for BackgroundWorker<W> {
+    type Output = Result<ShutdownReport, ShutdownError>;
+
+    async fn shutdown(&mut self) -> Result<ShutdownReport, ShutdownError> {
+        // Inherent methods take precedence, so this is not a recursive call
+        BackgroundWorker::shutdown(self).await
+    }
+}
+
+/// Read-only view of a running worker, handed to the closure in [`with_worker`]
+#[derive(Clone)]
+struct WorkerHandle {
+    report_rx: watch::Receiver<ShutdownReport>,
+    path: PathBuf,
+}
+
+impl WorkerHandle {
+    /// Progress the worker has published so far
+    fn progress(&self) -> ShutdownReport {
+        *self.report_rx.borrow()
+    }
+
+    /// The temp file the worker writes to
+    fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+/// ✅ RAII for async: runs `f` against a fresh worker, then always shuts it down
+///
+/// The worker is shut down after `f` resolves, whether it finished its job,
+/// returned early, or returned an `Err`, so callers can't forget
+/// `.shutdown().await`. `f`'s output is passed through untouched. A panic in `f`
+/// still falls back to the `Drop` safety net.
+async fn with_worker<F, Fut, T>(temp_file: impl AsRef<Path>, f: F) -> T
+where
+    F: FnOnce(WorkerHandle) -> Fut,
+    Fut: Future<Output = T>,
+{
+    let mut worker = BackgroundWorker::new(temp_file);
+    let handle = WorkerHandle {
+        report_rx: worker.report_rx.clone(),
+        path: worker.path().to_path_buf(),
+    };
+
+    let output = f(handle).await;
+    worker.shutdown().await.ok();
+    output
+}
+
+/// Owns many workers and shuts them all down with one call
+///
+/// This models logout tearing down every client at once: each worker holds a
+/// child of the supervisor's token, and workers without dependencies clean up
+/// concurrently instead of one after another. Where order matters, a worker
+/// can declare that others must shut down before it (see `depends_on`).
+#[derive(Default)]
+struct WorkerSupervisor {
+    workers: Vec<BackgroundWorker>,
+    /// `dependencies[i]` holds the indices of workers that shut down before worker `i`
+    dependencies: Vec<Vec<usize>>,
+    shutdown_token: CancellationToken,
+}
+
+impl WorkerSupervisor {
+    fn new() -> Self {
+        Self::default()
+    }
+
+    /// Spawns a worker writing into `temp_file` and takes ownership of it
+    ///
+    /// Returns the worker's resolved temp file path, which also identifies it
+    /// in `depends_on`.
+    fn spawn(&mut self, temp_file: impl AsRef<Path>) -> PathBuf {
+        let token = self.shutdown_token.child_token();
+        let worker = BackgroundWorker::with_token(temp_file, token);
+        let path = worker.path().to_path_buf();
+        self.workers.push(worker);
+        self.dependencies.push(Vec::new());
+        path
+    }
+
+    /// Index of the worker writing to `path`
+    fn index_of(&self, path: &Path) -> Result<usize, DependencyError> {
+        self.workers
+            .iter()
+            .position(|worker| worker.path() == path)
+            .ok_or_else(|| DependencyError::UnknownWorker(path.to_path_buf()))
+    }
+
+    /// Whether `from` waits on `to`, directly or through other workers
+    fn waits_on(&self, from: usize, to: usize) -> bool {
+        let mut pending = vec![from];
+        let mut seen = vec![false; self.workers.len()];
+        while let Some(index) = pending.pop() {
+            if index == to {
+                return true;
+            }
+            if !std::mem::replace(&mut seen[index], true) {
+                pending.extend(&self.dependencies[index]);
+            }
+        }
+        false
+    }
+
+    /// Makes `shutdown_all` finish shutting down `dependency` before it starts on `worker`
+    ///
+    /// Both are paths returned by `spawn`. A dependency that would close a
+    /// cycle is rejected here, so `shutdown_all` always finds an order.
+    fn depends_on(&mut self, worker: &Path, dependency: &Path) -> Result<(), DependencyError> {
+        let worker = self.index_of(worker)?;
+        let dependency = self.index_of(dependency)?;
+        if self.waits_on(dependency, worker) {
+            return Err(DependencyError::Cycle);
+        }
+        if !self.dependencies[worker].contains(&dependency) {
+            self.dependencies[worker].push(dependency);
+        }
+        Ok(())
+    }
+
+    /// Groups worker indices into stages: every worker comes after its dependencies
+    fn shutdown_stages(&self) -> Vec<Vec<usize>> {
+        fn stage_of(
+            index: usize,
+            dependencies: &[Vec<usize>],
+            stages: &mut [Option<usize>],
+        ) -> usize {
+            if let Some(stage) = stages[index] {
+                return stage;
+            }
+            let stage = dependencies[index]
+                .iter()
+                .map(|&dependency| stage_of(dependency, dependencies, stages) + 1)
+                .max()
+                .unwrap_or(0);
+            stages[index] = Some(stage);
+            stage
+        }
+
+        let mut stage_by_worker = vec![None; self.workers.len()];
+        let mut stages: Vec<Vec<usize>> = Vec::new();
+        for index in 0..self.workers.len() {
+            let stage = stage_of(index, &self.dependencies, &mut stage_by_worker);
+            if stages.len() <= stage {
+                stages.resize_with(stage + 1, Vec::new);
+            }
+            stages[stage].push(index);
+        }
+        stages
+    }
+
+    /// Shuts every worker down, dependencies first, waiting up to `timeout` for each cleanup
+    ///
+    /// Workers in the same stage shut down concurrently; a stage starts once
+    /// the previous one has finished. Workers that exceed the timeout are
+    /// aborted; their report carries the progress made so far with
+    /// `timed_out: true` and `file_removed: false`. Reports are returned in
+    /// spawn order.
+    async fn shutdown_all(mut self, timeout: Duration) -> Vec<ShutdownReport> {
+        println!(
+            "[Supervisor] Shutting down {} workers...",
+            self.workers.len()
+        );
+
+        let mut reports = vec![ShutdownReport::default(); self.workers.len()];
+        for stage in self.shutdown_stages() {
+            let mut stage_workers: Vec<(usize, &mut BackgroundWorker)> = self
+                .workers
+                .iter_mut()
+                .enumerate()
+                .filter(|(index, _)| stage.contains(index))
+                .collect();
+            let stage_reports = join_all(stage_workers.iter_mut().map(|(_, worker)| async move {
+                match worker.shutdown_with_timeout(timeout).await {
+                    Ok(report) => report,
+                    // Only the supervisor shuts its workers down, so this can't happen
+                    Err(ShutdownError::AlreadyShutDown) => *worker.report_rx.borrow(),
+                }
+            }))
+            .await;
+            for ((index, _), report) in stage_workers.iter().zip(stage_reports) {
+                reports[*index] = report;
+            }
+        }
+
+        self.shutdown_token.cancel();
+        reports
+    }
+}
+
 // ✅ Drop as a safety net, not the primary cleanup mechanism
-impl Drop for BackgroundWorker {
+impl<W: Work> Drop for BackgroundWorker<W> {
     fn drop(&mut self) {
         // Check if the task handle was taken (meaning shutdown was called)
         if let Some(handle) = &self.task_handle {
@@ -154,7 +846,7 @@ impl Drop for BackgroundWorker {
                 eprintln!("⚠️  Always call .shutdown().await before dropping!");
 
                 // Send shutdown signal as last resort
-                self.shutdown_tx.send(true).ok();
+                self.shutdown_token.cancel();
 
                 // Abort the task (not ideal, but better than hanging)
                 handle.abort();
@@ -162,6 +854,9 @@ impl Drop for BackgroundWorker {
         } else {
             println!("[Drop] Worker already shutdown cleanly");
         }
+
+        // Nothing left to watch once the owner is gone
+        self.watchdog.abort();
     }
 }
 
@@ -173,27 +868,38 @@ async fn main() {
     {
         println!("--- Example 1: Graceful Shutdown ---");
         let temp_file = PathBuf::from("/tmp/async-drop-correct-1.log");
-        let worker = BackgroundWorker::new(temp_file);
+        let mut worker = BackgroundWorker::new(temp_file).with_config(Config {
+            shutdown_timeout: Duration::from_secs(2),
+            ..Config::default()
+        });
+        println!("Worker writes to {:?}", worker.path());
 
         // Let it run for a short time
         tokio::time::sleep(Duration::from_millis(300)).await;
+        println!(
+            "Health check: running={}, items_processed={}",
+            worker.is_running(),
+            worker.items_processed()
+        );
 
         // ✅ Explicitly call shutdown before dropping
         println!("\nInitiating shutdown...");
-        worker.shutdown().await;
-        println!("✓ Worker shutdown complete\n");
+        match worker.shutdown().await {
+            Ok(report) => println!("✓ Worker shutdown complete: {:?}\n", report),
+            Err(e) => eprintln!("✗ {}\n", e),
+        }
     }
 
     // Example 2: Shutdown with timeout
     {
         println!("--- Example 2: Shutdown with Timeout ---");
         let temp_file = PathBuf::from("/tmp/async-drop-correct-2.log");
-        let worker = BackgroundWorker::new(temp_file);
+        let mut worker = BackgroundWorker::new(temp_file);
 
         tokio::time::sleep(Duration::from_millis(200)).await;
 
         match worker.shutdown_with_timeout(Duration::from_secs(1)).await {
-            Ok(()) => println!("✓ Worker shutdown within timeout\n"),
+            Ok(report) => println!("✓ Worker shutdown within timeout: {:?}\n", report),
             Err(e) => eprintln!("✗ {}\n", e),
         }
     }
@@ -211,28 +917,127 @@ async fn main() {
         println!("(See warning above - this demonstrates the safety net)\n");
     }
 
+    // Example 4: Shutting down many resources through the shared trait
+    {
+        println!("--- Example 4: Concurrent Shutdown via AsyncShutdown ---");
+        let workers: Vec<_> = (0..3)
+            .map(|i| {
+                BackgroundWorker::new(PathBuf::from(format!(
+                    "/tmp/async-drop-correct-4-{}.log",
+                    i
+                )))
+            })
+            .collect();
+
+        tokio::time::sleep(Duration::from_millis(200)).await;
+
+        let reports = shutdown_all(workers).await;
+        println!("✓ {} workers shutdown concurrently\n", reports.len());
+    }
+
+    // Example 5: One supervisor, one shutdown call
+    {
+        println!("--- Example 5: WorkerSupervisor ---");
+        let mut supervisor = WorkerSupervisor::new();
+        let paths: Vec<PathBuf> = (0..3)
+            .map(|i| supervisor.spawn(format!("/tmp/async-drop-correct-5-{}.log", i)))
+            .collect();
+        // The first worker only shuts down once the second one has finished
+        if let Err(e) = supervisor.depends_on(&paths[0], &paths[1]) {
+            eprintln!("✗ {}", e);
+        }
+
+        tokio::time::sleep(Duration::from_millis(200)).await;
+
+        let reports = supervisor.shutdown_all(Duration::from_secs(1)).await;
+        println!("✓ Supervisor reports: {:?}\n", reports);
+    }
+
+    // Example 6: Scoped worker - shutdown is guaranteed
+    {
+        println!("--- Example 6: Scoped Worker via with_worker ---");
+        let temp_file = PathBuf::from("/tmp/async-drop-correct-6.log");
+        let items = with_worker(temp_file, |handle| async move {
+            println!("Scoped worker writes to {:?}", handle.path());
+            tokio::time::sleep(Duration::from_millis(200)).await;
+            handle.progress().items_processed
+        })
+        .await;
+        println!(
+            "✓ Scoped worker processed {} items and was shut down\n",
+            items
+        );
+    }
+
+    // Example 7: Pausing a worker without tearing it down
+    {
+        println!("--- Example 7: Pause and Resume ---");
+        let temp_file = PathBuf::from("/tmp/async-drop-correct-7.log");
+        let mut worker = BackgroundWorker::new(temp_file);
+
+        tokio::time::sleep(Duration::from_millis(150)).await;
+        worker.pause();
+        tokio::time::sleep(Duration::from_millis(300)).await;
+        worker.resume();
+        tokio::time::sleep(Duration::from_millis(150)).await;
+
+        match worker.shutdown().await {
+            Ok(report) => println!("✓ Paused worker shutdown cleanly: {:?}\n", report),
+            Err(e) => eprintln!("✗ {}\n", e),
+        }
+    }
+
+    // Example 8: Restarting a worker from its builder
+    {
+        println!("--- Example 8: Restart ---");
+        let builder = WorkerBuilder::new("/tmp/async-drop-correct-8.log").config(Config {
+            shutdown_timeout: Duration::from_secs(2),
+            ..Config::default()
+        });
+        let worker = builder.spawn();
+        let old_file = worker.path().to_path_buf();
+
+        tokio::time::sleep(Duration::from_millis(200)).await;
+        let mut worker = worker.restart().await;
+        println!(
+            "Restarted: {:?} removed={}, now writing {:?}",
+            old_file,
+            !old_file.exists(),
+            worker.path()
+        );
+
+        tokio::time::sleep(Duration::from_millis(200)).await;
+        match worker.shutdown().await {
+            Ok(report) => println!("✓ Restarted worker shutdown cleanly: {:?}\n", report),
+            Err(e) => eprintln!("✗ {}\n", e),
+        }
+    }
+
     tokio::time::sleep(Duration::from_millis(100)).await;
 
     println!("=== Summary ===");
     println!("✓ Explicit async shutdown ensures cleanup code runs");
     println!("✓ Timeout handling prevents hanging on shutdown");
     println!("✓ Drop serves as a safety net with clear warnings");
+    println!("✓ AsyncShutdown lets many resource types share the pattern");
     println!("✓ No resource leaks or data corruption");
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+    use std::sync::Mutex;
 
     #[tokio::test]
     async fn test_graceful_shutdown() {
-        let temp_file = PathBuf::from("/tmp/test-async-drop-correct.log");
-        let worker = BackgroundWorker::new(temp_file.clone());
+        let mut worker = BackgroundWorker::new("/tmp/test-async-drop-correct.log");
+        let temp_file = worker.path().to_path_buf();
 
         tokio::time::sleep(Duration::from_millis(200)).await;
 
         // Explicit shutdown should complete successfully
-        worker.shutdown().await;
+        worker.shutdown().await.unwrap();
 
         // File should have been cleaned up
         assert!(!temp_file.exists(), "Temp file should be removed");
@@ -241,7 +1046,7 @@ mod tests {
     #[tokio::test]
     async fn test_shutdown_with_timeout() {
         let temp_file = PathBuf::from("/tmp/test-async-drop-timeout.log");
-        let worker = BackgroundWorker::new(temp_file);
+        let mut worker = BackgroundWorker::new(temp_file);
 
         tokio::time::sleep(Duration::from_millis(100)).await;
 
@@ -251,15 +1056,586 @@ mod tests {
 
     #[tokio::test]
     async fn test_natural_completion() {
-        let temp_file = PathBuf::from("/tmp/test-async-drop-natural.log");
-        let worker = BackgroundWorker::new(temp_file.clone());
+        let mut worker = BackgroundWorker::new("/tmp/test-async-drop-natural.log");
+        let temp_file = worker.path().to_path_buf();
 
         // Let the task complete naturally
         tokio::time::sleep(Duration::from_millis(1200)).await;
 
         // Shutdown should still work even if task finished
-        worker.shutdown().await;
+        worker.shutdown().await.unwrap();
 
         assert!(!temp_file.exists(), "Temp file should be cleaned up");
     }
+
+    #[tokio::test]
+    async fn test_shutdown_all_cleans_up_every_worker() {
+        let workers: Vec<_> = (0..3)
+            .map(|i| BackgroundWorker::new(format!("/tmp/test-async-drop-shutdown-all-{}.log", i)))
+            .collect();
+        let temp_files: Vec<PathBuf> = workers.iter().map(|w| w.path().to_path_buf()).collect();
+
+        tokio::time::sleep(Duration::from_millis(200)).await;
+
+        shutdown_all(workers).await;
+
+        for temp_file in &temp_files {
+            assert!(!temp_file.exists(), "{:?} should be removed", temp_file);
+        }
+    }
+
+    /// Endless producer whose sink records every item it receives
+    struct RecordingWork {
+        produced: Arc<AtomicUsize>,
+        consumed: Arc<Mutex<Vec<usize>>>,
+        closed: Arc<AtomicBool>,
+    }
+
+    struct RecordingSink {
+        consumed: Arc<Mutex<Vec<usize>>>,
+        closed: Arc<AtomicBool>,
+    }
+
+    impl Sink<usize> for RecordingSink {
+        async fn consume(&mut self, item: usize) {
+            self.consumed.lock().unwrap().push(item);
+        }
+
+        async fn close(self) -> bool {
+            self.closed.store(true, Ordering::SeqCst);
+            true
+        }
+    }
+
+    impl Work for RecordingWork {
+        type Item = usize;
+        type Sink = RecordingSink;
+        type Info = ();
+
+        fn info(&self) {}
+
+        fn open_sink(&mut self) -> RecordingSink {
+            RecordingSink {
+                consumed: self.consumed.clone(),
+                closed: self.closed.clone(),
+            }
+        }
+
+        fn next_item(&mut self) -> Option<usize> {
+            Some(self.produced.fetch_add(1, Ordering::SeqCst))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_generic_work_loses_no_items_on_shutdown() {
+        let produced = Arc::new(AtomicUsize::new(0));
+        let consumed = Arc::new(Mutex::new(Vec::new()));
+        let closed = Arc::new(AtomicBool::new(false));
+        let mut worker = BackgroundWorker::with_work(
+            RecordingWork {
+                produced: produced.clone(),
+                consumed: consumed.clone(),
+                closed: closed.clone(),
+            },
+            CancellationToken::new(),
+        );
+
+        tokio::time::sleep(Duration::from_millis(350)).await;
+        worker.shutdown().await.unwrap();
+
+        let consumed = consumed.lock().unwrap().clone();
+        assert!(!consumed.is_empty(), "Some items should be processed");
+        assert_eq!(
+            consumed.len(),
+            produced.load(Ordering::SeqCst),
+            "Every produced item must reach the sink"
+        );
+        assert_eq!(consumed, (0..consumed.len()).collect::<Vec<_>>());
+        assert!(closed.load(Ordering::SeqCst), "Sink should be closed");
+    }
+
+    #[tokio::test]
+    async fn test_report_after_early_shutdown() {
+        let temp_file = PathBuf::from("/tmp/test-async-drop-report-early.log");
+        let mut worker = BackgroundWorker::new(temp_file);
+
+        tokio::time::sleep(Duration::from_millis(350)).await;
+
+        let report = worker.shutdown().await.unwrap();
+        assert!(report.items_processed > 0);
+        assert!(
+            report.items_processed < 10,
+            "Shutdown should stop work early"
+        );
+        assert!(report.file_removed);
+        assert!(!report.completed_naturally);
+    }
+
+    #[tokio::test]
+    async fn test_report_after_natural_completion() {
+        let temp_file = PathBuf::from("/tmp/test-async-drop-report-natural.log");
+        let mut worker = BackgroundWorker::new(temp_file);
+
+        tokio::time::sleep(Duration::from_millis(1300)).await;
+
+        let report = worker.shutdown().await.unwrap();
+        assert_eq!(
+            report,
+            ShutdownReport {
+                items_processed: 10,
+                file_removed: true,
+                completed_naturally: true,
+                timed_out: false,
+                cleanup_finished_at: report.cleanup_finished_at,
+                cleanup_timed_out: false,
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn test_supervisor_shuts_down_all_workers() {
+        let mut supervisor = WorkerSupervisor::new();
+        let temp_files: Vec<PathBuf> = (0..5)
+            .map(|i| supervisor.spawn(format!("/tmp/test-async-drop-supervisor-{}.log", i)))
+            .collect();
+
+        tokio::time::sleep(Duration::from_millis(250)).await;
+
+        let reports = supervisor.shutdown_all(Duration::from_secs(2)).await;
+
+        assert_eq!(reports.len(), 5);
+        assert!(reports.iter().all(|report| report.file_removed));
+        for temp_file in &temp_files {
+            assert!(!temp_file.exists(), "{:?} should be removed", temp_file);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_parent_token_cancels_worker_without_shutdown() {
+        let parent = CancellationToken::new();
+        let worker = BackgroundWorker::with_token(
+            "/tmp/test-async-drop-parent-token.log",
+            parent.child_token(),
+        );
+        let temp_file = worker.path().to_path_buf();
+
+        tokio::time::sleep(Duration::from_millis(250)).await;
+        assert!(temp_file.exists(), "Worker should be writing its temp file");
+
+        // Cancelling the parent alone must drive the worker through cleanup
+        parent.cancel();
+        tokio::time::timeout(Duration::from_secs(1), worker.shutdown_complete.notified())
+            .await
+            .expect("Cleanup should finish after the parent is cancelled");
+
+        assert!(!temp_file.exists(), "Temp file should be removed");
+        assert!(!worker.report_rx.borrow().completed_naturally);
+    }
+
+    #[tokio::test]
+    async fn test_with_worker_cleans_up_when_closure_errors() {
+        let observed = Arc::new(Mutex::new(None));
+        let observed_in_closure = observed.clone();
+
+        let result: Result<(), String> = with_worker(
+            "/tmp/test-async-drop-with-worker.log",
+            |handle| async move {
+                tokio::time::sleep(Duration::from_millis(250)).await;
+                assert!(handle.path().exists(), "Worker should be running");
+                *observed_in_closure.lock().unwrap() = Some(handle.path().to_path_buf());
+
+                if handle.progress().items_processed > 0 {
+                    return Err("bailing out mid-way".to_string());
+                }
+                Ok(())
+            },
+        )
+        .await;
+
+        assert_eq!(result, Err("bailing out mid-way".to_string()));
+        let temp_file = observed.lock().unwrap().take().unwrap();
+        assert!(!temp_file.exists(), "Temp file should still be cleaned up");
+    }
+
+    #[tokio::test]
+    async fn test_double_shutdown_is_rejected() {
+        let mut worker = BackgroundWorker::new("/tmp/test-async-drop-double-shutdown.log");
+        let temp_file = worker.path().to_path_buf();
+
+        tokio::time::sleep(Duration::from_millis(150)).await;
+
+        let first = worker.shutdown_with_timeout(Duration::from_secs(1)).await;
+        assert!(first.is_ok(), "First shutdown should succeed");
+        assert!(!temp_file.exists());
+
+        let second = worker.shutdown().await;
+        assert_eq!(second, Err(ShutdownError::AlreadyShutDown));
+    }
+
+    /// Work whose sink never finishes consuming its first item
+    struct WedgedWork;
+    struct WedgedSink;
+
+    impl Sink<()> for WedgedSink {
+        async fn consume(&mut self, _item: ()) {
+            tokio::time::sleep(Duration::from_secs(3600)).await;
+        }
+
+        async fn close(self) -> bool {
+            true
+        }
+    }
+
+    impl Work for WedgedWork {
+        type Item = ();
+        type Sink = WedgedSink;
+        type Info = ();
+
+        fn info(&self) {}
+
+        fn open_sink(&mut self) -> WedgedSink {
+            WedgedSink
+        }
+
+        fn next_item(&mut self) -> Option<()> {
+            Some(())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_shutdown_respects_configured_timeout() {
+        let shutdown_timeout = Duration::from_millis(200);
+        let mut worker = BackgroundWorker::with_work(WedgedWork, CancellationToken::new())
+            .with_config(Config {
+                shutdown_timeout,
+                ..Config::default()
+            });
+
+        // Give the task time to get stuck inside `consume`
+        tokio::time::sleep(Duration::from_millis(150)).await;
+
+        let started = std::time::Instant::now();
+        let report = worker.shutdown().await.unwrap();
+        let elapsed = started.elapsed();
+
+        assert!(report.timed_out, "Wedged task should time out");
+        assert!(!report.file_removed, "Cleanup never ran");
+        assert!(
+            elapsed < shutdown_timeout + Duration::from_millis(300),
+            "Shutdown took {:?}, expected about {:?}",
+            elapsed,
+            shutdown_timeout
+        );
+    }
+
+    #[tokio::test]
+    async fn test_shutdown_with_timeout_reports_timeout_like_shutdown() {
+        let mut worker = BackgroundWorker::with_work(WedgedWork, CancellationToken::new());
+        tokio::time::sleep(Duration::from_millis(150)).await;
+
+        let report = worker
+            .shutdown_with_timeout(Duration::from_millis(200))
+            .await
+            .expect("A timeout is reported, not returned as an error");
+        assert!(report.timed_out, "Wedged task should time out");
+        assert!(!report.file_removed, "Cleanup never ran");
+    }
+
+    #[test]
+    fn test_default_config() {
+        assert_eq!(Config::default().shutdown_timeout, Duration::from_secs(5));
+        assert_eq!(Config::default().cleanup_timeout, Duration::from_secs(3));
+    }
+
+    #[tokio::test]
+    async fn test_pause_stops_work_until_resumed() {
+        let mut worker = BackgroundWorker::new("/tmp/test-async-drop-pause.log");
+        let temp_file = worker.path().to_path_buf();
+        let items = || worker.report_rx.borrow().items_processed;
+
+        tokio::time::sleep(Duration::from_millis(150)).await;
+        worker.pause();
+        // Let the pause take effect before sampling
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        let paused_at = items();
+
+        tokio::time::sleep(Duration::from_millis(350)).await;
+        assert_eq!(items(), paused_at, "No items while paused");
+        assert!(temp_file.exists(), "Pausing must not clean up");
+
+        worker.resume();
+        tokio::time::sleep(Duration::from_millis(250)).await;
+        assert!(items() > paused_at, "Work should continue after resume");
+
+        let report = worker.shutdown().await.unwrap();
+        assert!(report.file_removed);
+        assert!(!temp_file.exists());
+    }
+
+    #[tokio::test]
+    async fn test_shutdown_while_paused_runs_cleanup() {
+        let mut worker = BackgroundWorker::new("/tmp/test-async-drop-pause-shutdown.log");
+        let temp_file = worker.path().to_path_buf();
+
+        tokio::time::sleep(Duration::from_millis(150)).await;
+        worker.pause();
+        tokio::time::sleep(Duration::from_millis(100)).await;
+
+        let report = worker.shutdown().await.unwrap();
+        assert!(report.file_removed);
+        assert!(!report.timed_out);
+        assert!(!temp_file.exists());
+    }
+
+    #[test]
+    fn test_unique_temp_path_keeps_directory_and_extension() {
+        let path = unique_temp_path(Path::new("/tmp/worker.log"));
+        let name = path.file_name().unwrap().to_string_lossy().into_owned();
+
+        assert_eq!(path.parent(), Some(Path::new("/tmp")));
+        assert!(name.starts_with(&format!("worker-{}-", std::process::id())));
+        assert!(name.ends_with(".log"));
+    }
+
+    #[tokio::test]
+    async fn test_workers_with_same_path_do_not_collide() {
+        let requested = "/tmp/test-async-drop-same-path.log";
+        let mut first = BackgroundWorker::new(requested);
+        let mut second = BackgroundWorker::new(requested);
+
+        assert_ne!(first.path(), second.path());
+        assert_ne!(first.path(), Path::new(requested));
+
+        tokio::time::sleep(Duration::from_millis(250)).await;
+        assert!(first.path().exists() && second.path().exists());
+
+        let first_report = first.shutdown().await.unwrap();
+        let second_report = second.shutdown().await.unwrap();
+
+        assert!(first_report.file_removed && second_report.file_removed);
+        assert!(
+            !first.path().exists(),
+            "{:?} should be removed",
+            first.path()
+        );
+        assert!(
+            !second.path().exists(),
+            "{:?} should be removed",
+            second.path()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_health_check_tracks_progress_and_liveness() {
+        let mut worker = BackgroundWorker::new("/tmp/test-async-drop-health.log");
+        assert!(worker.is_running());
+
+        tokio::time::sleep(Duration::from_millis(250)).await;
+        let early = worker.items_processed();
+        assert!(early > 0, "Some items should be processed");
+
+        tokio::time::sleep(Duration::from_millis(250)).await;
+        let later = worker.items_processed();
+        assert!(later > early, "Progress should keep growing");
+        assert!(worker.is_running());
+
+        let report = worker.shutdown().await.unwrap();
+        assert!(!worker.is_running(), "Shut down worker is not running");
+        assert_eq!(worker.items_processed(), report.items_processed);
+    }
+
+    #[tokio::test]
+    async fn test_is_running_false_after_natural_completion() {
+        let mut worker = BackgroundWorker::new("/tmp/test-async-drop-health-natural.log");
+
+        tokio::time::sleep(Duration::from_millis(1300)).await;
+
+        assert!(!worker.is_running(), "Finished task is not running");
+        assert_eq!(worker.items_processed(), 10);
+        worker.shutdown().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_restart_runs_fresh_worker_in_new_file() {
+        let shutdown_timeout = Duration::from_secs(2);
+        let worker =
+            BackgroundWorker::new("/tmp/test-async-drop-restart.log").with_config(Config {
+                shutdown_timeout,
+                ..Config::default()
+            });
+        let old_file = worker.path().to_path_buf();
+
+        tokio::time::sleep(Duration::from_millis(250)).await;
+        assert!(worker.items_processed() > 0);
+
+        let mut worker = worker.restart().await;
+        let new_file = worker.path().to_path_buf();
+        assert!(!old_file.exists(), "Old temp file should be cleaned up");
+        assert_ne!(new_file, old_file, "Restarted worker gets a fresh file");
+        assert_eq!(worker.config.borrow().shutdown_timeout, shutdown_timeout);
+        assert!(worker.is_running());
+
+        tokio::time::sleep(Duration::from_millis(250)).await;
+        let contents = std::fs::read_to_string(&new_file).unwrap();
+        assert!(
+            contents.starts_with("Processing item 0\n"),
+            "New worker should start over in its own file"
+        );
+        assert_eq!(contents.lines().count(), worker.items_processed());
+
+        let report = worker.shutdown().await.unwrap();
+        assert!(report.items_processed > 0);
+        assert!(report.file_removed);
+        assert!(!new_file.exists());
+    }
+
+    #[tokio::test]
+    async fn test_restart_after_shutdown_spawns_replacement() {
+        let mut worker = BackgroundWorker::new("/tmp/test-async-drop-restart-stopped.log");
+        tokio::time::sleep(Duration::from_millis(150)).await;
+        worker.shutdown().await.unwrap();
+
+        let mut worker = worker.restart().await;
+        assert!(worker.is_running());
+
+        tokio::time::sleep(Duration::from_millis(150)).await;
+        assert!(worker.path().exists());
+        assert!(worker.shutdown().await.unwrap().file_removed);
+    }
+
+    #[tokio::test]
+    async fn test_temp_file_work_via_with_work_exposes_path() {
+        let path = unique_temp_path(Path::new("/tmp/test-async-drop-with-work.log"));
+        let work = TempFileWork::new(path.clone(), WorkerBuilder::new(&path));
+        let worker = BackgroundWorker::with_work(work, CancellationToken::new());
+        assert_eq!(worker.path(), path);
+
+        tokio::time::sleep(Duration::from_millis(150)).await;
+        let mut worker = worker.restart().await;
+        assert!(!path.exists(), "Old temp file should be cleaned up");
+        assert_ne!(worker.path(), path);
+
+        tokio::time::sleep(Duration::from_millis(150)).await;
+        assert!(worker.shutdown().await.unwrap().file_removed);
+    }
+
+    #[tokio::test]
+    async fn test_supervisor_shuts_down_dependencies_first() {
+        let mut supervisor = WorkerSupervisor::new();
+        let a = supervisor.spawn("/tmp/test-async-drop-depends-a.log");
+        let b = supervisor.spawn("/tmp/test-async-drop-depends-b.log");
+        let c = supervisor.spawn("/tmp/test-async-drop-depends-c.log");
+        supervisor.depends_on(&a, &b).unwrap();
+        supervisor.depends_on(&b, &c).unwrap();
+
+        tokio::time::sleep(Duration::from_millis(250)).await;
+
+        let reports = supervisor.shutdown_all(Duration::from_secs(2)).await;
+        let finished_at: Vec<Instant> = reports
+            .iter()
+            .map(|report| report.cleanup_finished_at.expect("Every cleanup ran"))
+            .collect();
+
+        assert!(reports.iter().all(|report| report.file_removed));
+        assert!(finished_at[2] < finished_at[1], "C must finish before B");
+        assert!(finished_at[1] < finished_at[0], "B must finish before A");
+    }
+
+    #[tokio::test]
+    async fn test_supervisor_rejects_dependency_cycles() {
+        let mut supervisor = WorkerSupervisor::new();
+        let a = supervisor.spawn("/tmp/test-async-drop-cycle-a.log");
+        let b = supervisor.spawn("/tmp/test-async-drop-cycle-b.log");
+        let c = supervisor.spawn("/tmp/test-async-drop-cycle-c.log");
+
+        supervisor.depends_on(&a, &b).unwrap();
+        supervisor.depends_on(&b, &c).unwrap();
+        assert_eq!(supervisor.depends_on(&c, &a), Err(DependencyError::Cycle));
+        assert_eq!(supervisor.depends_on(&a, &a), Err(DependencyError::Cycle));
+
+        let unknown = PathBuf::from("/tmp/not-a-worker.log");
+        assert_eq!(
+            supervisor.depends_on(&a, &unknown),
+            Err(DependencyError::UnknownWorker(unknown))
+        );
+
+        let reports = supervisor.shutdown_all(Duration::from_secs(2)).await;
+        assert_eq!(reports.len(), 3);
+        assert!(reports.iter().all(|report| report.file_removed));
+    }
+
+    /// Work whose sink hangs while closing, like `remove_file` on a dead network share
+    struct HangingCloseWork;
+    struct HangingCloseSink;
+
+    impl Sink<()> for HangingCloseSink {
+        async fn consume(&mut self, _item: ()) {}
+
+        async fn close(self) -> bool {
+            tokio::time::sleep(Duration::from_secs(3600)).await;
+            true
+        }
+    }
+
+    impl Work for HangingCloseWork {
+        type Item = ();
+        type Sink = HangingCloseSink;
+        type Info = ();
+
+        fn info(&self) {}
+
+        fn open_sink(&mut self) -> HangingCloseSink {
+            HangingCloseSink
+        }
+
+        fn next_item(&mut self) -> Option<()> {
+            Some(())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_watchdog_aborts_hanging_cleanup() {
+        let cleanup_timeout = Duration::from_millis(200);
+        let mut worker = BackgroundWorker::with_work(HangingCloseWork, CancellationToken::new())
+            .with_config(Config {
+                cleanup_timeout,
+                ..Config::default()
+            });
+
+        tokio::time::sleep(Duration::from_millis(250)).await;
+
+        let started = Instant::now();
+        let report = worker.shutdown().await.unwrap();
+        let elapsed = started.elapsed();
+
+        assert!(report.cleanup_timed_out, "Watchdog should fire");
+        assert!(!report.timed_out, "shutdown_timeout was never reached");
+        assert!(!report.file_removed, "Cleanup never finished");
+        assert!(report.items_processed > 0);
+        assert!(
+            elapsed < cleanup_timeout + Duration::from_millis(300),
+            "Shutdown took {:?}, expected about {:?}",
+            elapsed,
+            cleanup_timeout
+        );
+    }
+
+    #[tokio::test]
+    async fn test_watchdog_covers_cleanup_without_shutdown() {
+        let parent = CancellationToken::new();
+        let worker = BackgroundWorker::with_work(HangingCloseWork, parent.child_token())
+            .with_config(Config {
+                cleanup_timeout: Duration::from_millis(150),
+                ..Config::default()
+            });
+
+        tokio::time::sleep(Duration::from_millis(150)).await;
+        parent.cancel();
+
+        tokio::time::timeout(Duration::from_secs(1), worker.shutdown_complete.notified())
+            .await
+            .expect("Watchdog should signal completion");
+        assert!(worker.report_rx.borrow().cleanup_timed_out);
+        assert!(!worker.is_running());
+    }
 }
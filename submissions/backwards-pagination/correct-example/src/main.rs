@@ -15,14 +15,18 @@
 //!
 //! You'll see that the found index remains correct despite concurrent modifications.
 
+use env_logger::Env;
+use std::fmt;
 use std::sync::Arc;
 use tokio::sync::{mpsc, RwLock};
 use tokio::time::{sleep, Duration};
+use tokio_util::sync::CancellationToken;
 
 /// A simplified timeline item representing a message
 #[derive(Debug, Clone)]
 struct TimelineItem {
     event_id: String,
+    #[allow(dead_code)]
     content: String,
 }
 
@@ -32,17 +36,36 @@ struct BackwardsPaginateRequest {
     target_event_id: String,
     starting_index: usize,
     current_tl_len: usize,  // Snapshot for validation
+    index_hint: Option<usize>,  // Approximate index of the target, if known
 }
 
 /// Result of finding a target event
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 struct TargetEventFound {
     target_event_id: String,
     index: usize,  // OK: Adjusted index that remains correct
 }
 
+/// What the handler sends back for a request
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum SearchOutcome {
+    Found(TargetEventFound),
+    /// The handler was cancelled while this search was still pending
+    Cancelled { target_event_id: String },
+}
+
+impl fmt::Display for SearchOutcome {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SearchOutcome::Found(found) => write!(f, "index {} for '{}'", found.index, found.target_event_id),
+            SearchOutcome::Cancelled { target_event_id } => write!(f, "cancellation of '{}'", target_event_id),
+        }
+    }
+}
+
 /// Represents different types of timeline modifications
 #[derive(Debug)]
+#[allow(dead_code)] // Insert/Remove are only produced by the tests
 enum TimelineDiff {
     PushBack { item: TimelineItem },
     PushFront { item: TimelineItem },
@@ -70,6 +93,11 @@ impl Timeline {
         self.items.read().await.get(index).cloned()
     }
 
+    /// Find the current index of an event by scanning the whole timeline
+    async fn find_index(&self, event_id: &str) -> Option<usize> {
+        self.items.read().await.iter().position(|item| item.event_id == event_id)
+    }
+
     /// Apply a timeline modification
     async fn apply_diff(&self, diff: TimelineDiff) {
         let mut items = self.items.write().await;
@@ -94,163 +122,624 @@ impl Timeline {
     }
 }
 
-/// OK: CORRECT: Search handler with snapshot validation and index adjustment
+/// Number of diffs processed since a request after which a found index is
+/// re-anchored by scanning the timeline instead of trusting the adjustments
 ///
-/// This function demonstrates three key patterns:
+/// The adjustments only see the diff stream. The timeline itself is shared
+/// (`Arc<RwLock<..>>`), so a writer that edits it directly shifts the target
+/// without the handler knowing; a long wait gives that the most room.
+const REANCHOR_AFTER_DIFFS: u64 = 8;
+
+/// Scan `items[..end]` from the back for `target_event_id`
 ///
-/// 1. **Snapshot Validation**: Checks if timeline changed since request
-/// 2. **Index Adjustment**: Tracks found index as timeline is modified
-/// 3. **Biased Selection**: Prioritizes requests over timeline updates
-async fn timeline_search_handler(
+/// Returns the index, if found, and the number of items compared.
+fn search_backwards(items: &[TimelineItem], target_event_id: &str, end: usize) -> (Option<usize>, usize) {
+    let mut comparisons = 0;
+    let found = items[..end.min(items.len())].iter().rposition(|item| {
+        comparisons += 1;
+        item.event_id == target_event_id
+    });
+    (found, comparisons)
+}
+
+/// Search `items[..end]` outward from `hint`: hint, hint-1, hint+1, hint-2, ...
+///
+/// The lower side is tried first at each distance, matching the backwards
+/// direction of a plain scan. In a long timeline with a good hint this
+/// touches a handful of items instead of everything after the target.
+fn search_around_hint(
+    items: &[TimelineItem],
+    target_event_id: &str,
+    hint: usize,
+    end: usize,
+) -> (Option<usize>, usize) {
+    let end = end.min(items.len());
+    if end == 0 {
+        return (None, 0);
+    }
+    let hint = hint.min(end - 1);
+
+    let mut comparisons = 0;
+    for distance in 0..end {
+        let below = hint.checked_sub(distance);
+        let above = Some(hint + distance).filter(|&i| distance > 0 && i < end);
+        if below.is_none() && above.is_none() {
+            break;
+        }
+        for index in [below, above].into_iter().flatten() {
+            comparisons += 1;
+            if items[index].event_id == target_event_id {
+                return (Some(index), comparisons);
+            }
+        }
+    }
+    (None, comparisons)
+}
+
+/// What the handler does when a result can't be delivered
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[allow(dead_code)] // Log/Drop are opt-ins the demo doesn't use; the tests do
+enum DeliveryPolicy {
+    /// Wait for the reader to make room (backpressure). A closed channel
+    /// will never have room, so there this acts like `Stop`
+    Wait,
+    /// Print a warning, drop the result and keep running
+    Log,
+    /// Drop the result silently and keep running
+    Drop,
+    /// Stop the handler; nobody is going to read further results
+    Stop,
+}
+
+/// Channel sizes and delivery policies for the search handler
+#[derive(Debug, Clone)]
+struct HandlerConfig {
+    request_capacity: usize,
+    diff_capacity: usize,
+    result_capacity: usize,
+    /// Applied when the result channel is full
+    when_full: DeliveryPolicy,
+    /// Applied when the result receiver was dropped
+    when_closed: DeliveryPolicy,
+}
+
+impl Default for HandlerConfig {
+    fn default() -> Self {
+        Self {
+            request_capacity: 10,
+            diff_capacity: 100,
+            result_capacity: 10,
+            when_full: DeliveryPolicy::Wait,
+            when_closed: DeliveryPolicy::Stop,
+        }
+    }
+}
+
+impl HandlerConfig {
+    fn request_channel(&self) -> (mpsc::Sender<BackwardsPaginateRequest>, mpsc::Receiver<BackwardsPaginateRequest>) {
+        mpsc::channel(self.request_capacity)
+    }
+
+    fn diff_channel(&self) -> (mpsc::Sender<TimelineDiff>, mpsc::Receiver<TimelineDiff>) {
+        mpsc::channel(self.diff_capacity)
+    }
+
+    fn result_channel(&self) -> (mpsc::Sender<SearchOutcome>, mpsc::Receiver<SearchOutcome>) {
+        mpsc::channel(self.result_capacity)
+    }
+}
+
+/// Structured events emitted by the search handler
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum HandlerEvent {
+    RequestReceived { target_event_id: String, starting_index: usize },
+    SnapshotValid { len: usize },
+    SnapshotInvalidated { snapshot_len: usize, current_len: usize },
+    Searched { comparisons: usize },
+    FoundInTimeline { target_event_id: String, index: usize },
+    WaitingForDiffs { target_event_id: String },
+    IndexAdjusted { cause: &'static str, index: usize },
+    TargetRemoved,
+    FoundInDiff { target_event_id: String, index: usize },
+    Reanchored { diffs_since_request: u64, from: usize, to: usize },
+    Reporting { index: usize },
+    ResultUndelivered { reason: &'static str, outcome: SearchOutcome },
+    Stopping { reason: &'static str },
+    Cancelled { pending: Option<String> },
+}
+
+impl HandlerEvent {
+    fn level(&self) -> log::Level {
+        match self {
+            HandlerEvent::RequestReceived { .. }
+            | HandlerEvent::FoundInTimeline { .. }
+            | HandlerEvent::FoundInDiff { .. }
+            | HandlerEvent::Reporting { .. }
+            | HandlerEvent::Cancelled { .. } => log::Level::Info,
+            HandlerEvent::SnapshotInvalidated { .. }
+            | HandlerEvent::TargetRemoved
+            | HandlerEvent::ResultUndelivered { .. }
+            | HandlerEvent::Stopping { .. } => log::Level::Warn,
+            _ => log::Level::Debug,
+        }
+    }
+}
+
+impl fmt::Display for HandlerEvent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            HandlerEvent::RequestReceived { target_event_id, starting_index } => {
+                write!(f, "Received request for '{}' from index {}", target_event_id, starting_index)
+            }
+            HandlerEvent::SnapshotValid { len } => {
+                write!(f, "Timeline unchanged (len={}), index valid", len)
+            }
+            HandlerEvent::SnapshotInvalidated { snapshot_len, current_len } => {
+                write!(f, "Timeline changed (was {}, now {}), using safe fallback", snapshot_len, current_len)
+            }
+            HandlerEvent::Searched { comparisons } => write!(f, "Compared {} items", comparisons),
+            HandlerEvent::FoundInTimeline { target_event_id, index } => {
+                write!(f, "Found '{}' at index {}", target_event_id, index)
+            }
+            HandlerEvent::WaitingForDiffs { target_event_id } => {
+                write!(f, "'{}' not found yet, will check incoming updates", target_event_id)
+            }
+            HandlerEvent::IndexAdjusted { cause, index } => {
+                write!(f, "Item {}, adjusted found index to {}", cause, index)
+            }
+            HandlerEvent::TargetRemoved => write!(f, "Target was removed"),
+            HandlerEvent::FoundInDiff { target_event_id, index } => {
+                write!(f, "Found '{}' in diff at index {}", target_event_id, index)
+            }
+            HandlerEvent::Reanchored { diffs_since_request, from, to } => {
+                write!(f, "{} diffs since request, re-anchored found index {} -> {}", diffs_since_request, from, to)
+            }
+            HandlerEvent::Reporting { index } => write!(f, "Reporting final adjusted index {}", index),
+            HandlerEvent::ResultUndelivered { reason, outcome } => {
+                write!(f, "Result channel {}, dropped {}", reason, outcome)
+            }
+            HandlerEvent::Stopping { reason } => write!(f, "Result channel {}, shutting down", reason),
+            HandlerEvent::Cancelled { pending: Some(target_event_id) } => {
+                write!(f, "Cancelled while searching for '{}'", target_event_id)
+            }
+            HandlerEvent::Cancelled { pending: None } => write!(f, "Cancelled with no search pending"),
+        }
+    }
+}
+
+/// Where the search handler sends its `HandlerEvent`s
+trait LogSink: Send + Sync {
+    fn emit(&self, event: &HandlerEvent);
+}
+
+/// Default sink: forwards every event to the `log` facade
+struct LogFacade;
+
+impl LogSink for LogFacade {
+    fn emit(&self, event: &HandlerEvent) {
+        log::log!(target: "timeline_search", event.level(), "{}", event);
+    }
+}
+
+/// One input picked by `SearchHandler::next_input`
+enum Input {
+    Shutdown,
+    Request(BackwardsPaginateRequest),
+    Diff(TimelineDiff),
+    /// Both input channels are closed and drained
+    Closed,
+}
+
+/// What a single `SearchHandler::step` did
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Step {
+    /// Handled a request; `found_index` is set if the target was already in the timeline
+    Request {
+        target_event_id: String,
+        found_index: Option<usize>,
+    },
+    /// Applied a diff; `tracked_index` is the found index still being tracked
+    /// afterwards, `reported` the index sent on the result channel
+    Diff {
+        tracked_index: Option<usize>,
+        reported: Option<usize>,
+    },
+    /// Both input channels are closed and drained
+    Closed,
+    /// A `DeliveryPolicy::Stop` fired or the handler was cancelled; it
+    /// processes nothing more
+    Stopped,
+    /// The shutdown token was cancelled; any pending search got
+    /// `SearchOutcome::Cancelled`
+    Cancelled,
+}
+
+/// State of the search handler, driven one `step` at a time by `run`
+struct SearchHandler {
     timeline: Timeline,
-    mut request_rx: mpsc::Receiver<BackwardsPaginateRequest>,
-    mut diff_rx: mpsc::Receiver<TimelineDiff>,
-    result_tx: mpsc::Sender<TargetEventFound>,
-) {
+    request_rx: mpsc::Receiver<BackwardsPaginateRequest>,
+    diff_rx: mpsc::Receiver<TimelineDiff>,
+    result_tx: mpsc::Sender<SearchOutcome>,
+    config: HandlerConfig,
+    shutdown: CancellationToken,
+    stopped: bool,
+    sink: Box<dyn LogSink>,
+
     // Current search state
-    let mut target_event_id: Option<String> = None;
+    target_event_id: Option<String>,
 
     // If found, store (index, event_id)
     // OK: This index will be incrementally adjusted as timeline changes
-    let mut found_target_event_id: Option<(usize, String)> = None;
+    found_target_event_id: Option<(usize, String)>,
+
+    // Generation counter: bumped once per applied diff
+    generation: u64,
+    request_generation: u64,
+}
+
+impl SearchHandler {
+    fn with_config(
+        timeline: Timeline,
+        request_rx: mpsc::Receiver<BackwardsPaginateRequest>,
+        diff_rx: mpsc::Receiver<TimelineDiff>,
+        result_tx: mpsc::Sender<SearchOutcome>,
+        config: HandlerConfig,
+    ) -> Self {
+        Self {
+            timeline,
+            request_rx,
+            diff_rx,
+            result_tx,
+            config,
+            shutdown: CancellationToken::new(),
+            stopped: false,
+            sink: Box::new(LogFacade),
+            target_event_id: None,
+            found_target_event_id: None,
+            generation: 0,
+            request_generation: 0,
+        }
+    }
+
+    /// Stop the handler when `shutdown` is cancelled
+    fn with_shutdown(mut self, shutdown: CancellationToken) -> Self {
+        self.shutdown = shutdown;
+        self
+    }
+
+    /// Run until both input channels close, a delivery policy stops it, or
+    /// the shutdown token is cancelled
+    async fn run(mut self) {
+        while !matches!(self.step().await, Step::Closed | Step::Stopped) {}
+    }
+
+    /// Wait for the next input and handle it
+    async fn step(&mut self) -> Step {
+        if self.stopped {
+            return Step::Stopped;
+        }
+        let input = self.next_input().await;
+        self.handle_input(input).await
+    }
+
+    /// Wait for the next input without handling it
+    ///
+    /// Only cancel-safe futures are raced here, so dropping this future
+    /// before it completes loses nothing.
+    async fn next_input(&mut self) -> Input {
+        // The shutdown branch never becomes disabled, so `else` can't
+        // live next to it; detect closed inputs in a select of their own
+        let (request_rx, diff_rx) = (&mut self.request_rx, &mut self.diff_rx);
+        let message = async {
+            tokio::select! {
+                // OK: BIASED: Process requests BEFORE timeline updates
+                // This reduces the window where timeline can change
+                biased;
+
+                Some(request) = request_rx.recv() => Input::Request(request),
+                Some(diff) = diff_rx.recv() => Input::Diff(diff),
+                else => Input::Closed,
+            }
+        };
 
-    loop {
         tokio::select! {
-            // OK: BIASED: Process requests BEFORE timeline updates
-            // This reduces the window where timeline can change
             biased;
 
-            // Handle new backwards pagination requests
-            Some(request) = request_rx.recv() => {
-                println!("  [Handler] Received request for '{}' from index {}",
-                    request.target_event_id, request.starting_index);
-
-                let items = timeline.items.read().await;
-                let current_tl_len = items.len();
-
-                // OK: VALIDATE: Check if timeline changed since request
-                let starting_index = if request.current_tl_len == current_tl_len {
-                    println!("  [Handler] ✓ Timeline unchanged (len={}), index valid", current_tl_len);
-                    request.starting_index
-                } else {
-                    println!("  [Handler] WARNING: Timeline changed (was {}, now {}), using safe fallback",
-                        request.current_tl_len, current_tl_len);
-                    // Timeline changed, cannot trust starting_index
-                    // Use safe default: search from end
-                    current_tl_len
-                };
+            // Shutdown goes first so a busy diff stream can't delay it
+            _ = self.shutdown.cancelled() => Input::Shutdown,
+            input = message => input,
+        }
+    }
 
-                // Search backwards from validated index
-                let found_index = items
-                    .iter()
-                    .enumerate()
-                    .take(starting_index)
-                    .rev()
-                    .find(|(_, item)| item.event_id == request.target_event_id)
-                    .map(|(i, _)| i);
-
-                drop(items);  // Release lock
-
-                if let Some(index) = found_index {
-                    // OK: Found in existing timeline!
-                    println!("  [Handler] ✓ Found '{}' at index {}", request.target_event_id, index);
-
-                    target_event_id = None;
-                    found_target_event_id = None;
-
-                    result_tx.send(TargetEventFound {
-                        target_event_id: request.target_event_id,
-                        index,
-                    }).await.ok();
-                } else {
-                    // Not found, start searching in incoming diffs
-                    println!("  [Handler] Not found yet, will check incoming updates...");
-                    target_event_id = Some(request.target_event_id);
-                    found_target_event_id = None;
+    async fn handle_input(&mut self, input: Input) -> Step {
+        match input {
+            Input::Shutdown => {
+                self.cancel().await;
+                Step::Cancelled
+            }
+            Input::Request(request) => {
+                let target_event_id = request.target_event_id.clone();
+                let found_index = self.handle_request(request).await;
+                Step::Request { target_event_id, found_index }
+            }
+            Input::Diff(diff) => {
+                let reported = self.handle_diff(diff).await;
+                Step::Diff {
+                    tracked_index: self.found_target_event_id.as_ref().map(|(index, _)| *index),
+                    reported,
                 }
             }
+            Input::Closed => Step::Closed,
+        }
+    }
 
-            // Handle timeline updates
-            Some(diff) = diff_rx.recv() => {
-                // First, adjust the found index if we have one
-                if let Some((target_idx, _target_id)) = found_target_event_id.as_mut() {
-                    match &diff {
-                        TimelineDiff::PushFront { .. } => {
-                            // OK: ADJUST: Prepended item shifts index forward
-                            *target_idx += 1;
-                            println!("  [Handler] DOWN: Item prepended, adjusted found index to {}", target_idx);
-                        }
-                        TimelineDiff::Insert { index, .. } => {
-                            // OK: ADJUST: Insertion before target shifts it forward
-                            if *index <= *target_idx {
-                                *target_idx += 1;
-                                println!("  [Handler] INSERT: Item inserted at {}, adjusted found index to {}",
-                                    index, target_idx);
-                            }
-                        }
-                        TimelineDiff::Remove { index } => {
-                            // OK: ADJUST: Removal before target shifts it backward
-                            if *index < *target_idx {
-                                *target_idx = target_idx.saturating_sub(1);
-                                println!("  [Handler] REMOVE: Item removed at {}, adjusted found index to {}",
-                                    index, target_idx);
-                            } else if *index == *target_idx {
-                                // Target itself was removed!
-                                println!("  [Handler] WARNING: Target was removed!");
-                                found_target_event_id = None;
-                                target_event_id = None;
-                            }
-                        }
-                        TimelineDiff::PushBack { .. } => {
-                            // Appending to end doesn't affect indices
-                        }
+    /// Handle a new backwards pagination request
+    ///
+    /// Returns the index if the target was already in the timeline.
+    async fn handle_request(&mut self, request: BackwardsPaginateRequest) -> Option<usize> {
+        self.sink.emit(&HandlerEvent::RequestReceived {
+            target_event_id: request.target_event_id.clone(),
+            starting_index: request.starting_index,
+        });
+        self.request_generation = self.generation;
+
+        let items = self.timeline.items.read().await;
+        let current_tl_len = items.len();
+
+        // OK: VALIDATE: Check if timeline changed since request
+        let starting_index = if request.current_tl_len == current_tl_len {
+            self.sink.emit(&HandlerEvent::SnapshotValid { len: current_tl_len });
+            request.starting_index
+        } else {
+            self.sink.emit(&HandlerEvent::SnapshotInvalidated {
+                snapshot_len: request.current_tl_len,
+                current_len: current_tl_len,
+            });
+            // Timeline changed, cannot trust starting_index
+            // Use safe default: search from end
+            current_tl_len
+        };
+
+        // Search from validated index: around the hint if we have one,
+        // otherwise backwards. A stale hint (timeline changed) only costs
+        // extra comparisons, it can't produce a wrong index.
+        let (found_index, comparisons) = match request.index_hint {
+            Some(hint) => search_around_hint(&items, &request.target_event_id, hint, starting_index),
+            None => search_backwards(&items, &request.target_event_id, starting_index),
+        };
+        self.sink.emit(&HandlerEvent::Searched { comparisons });
+
+        drop(items);  // Release lock
+
+        if let Some(index) = found_index {
+            // OK: Found in existing timeline!
+            self.sink.emit(&HandlerEvent::FoundInTimeline {
+                target_event_id: request.target_event_id.clone(),
+                index,
+            });
+
+            self.target_event_id = None;
+            self.found_target_event_id = None;
+
+            self.deliver(SearchOutcome::Found(TargetEventFound {
+                target_event_id: request.target_event_id,
+                index,
+            })).await;
+        } else {
+            // Not found, start searching in incoming diffs
+            self.sink.emit(&HandlerEvent::WaitingForDiffs {
+                target_event_id: request.target_event_id.clone(),
+            });
+            self.target_event_id = Some(request.target_event_id);
+            self.found_target_event_id = None;
+        }
+
+        found_index
+    }
+
+    /// Give up on any pending search and stop
+    ///
+    /// A search still waiting for its target, or found but not yet
+    /// reported, is answered with `SearchOutcome::Cancelled` so the caller
+    /// isn't left waiting for a result that will never come.
+    async fn cancel(&mut self) {
+        let pending = self.found_target_event_id.take().map(|(_, id)| id)
+            .or_else(|| self.target_event_id.take());
+        self.target_event_id = None;
+        self.sink.emit(&HandlerEvent::Cancelled { pending: pending.clone() });
+
+        if let Some(target_event_id) = pending {
+            self.deliver(SearchOutcome::Cancelled { target_event_id }).await;
+        }
+        self.stopped = true;
+    }
+
+    /// Send a result, applying the configured policy if the channel is full
+    /// or closed. Returns whether the result was delivered.
+    ///
+    /// Under `DeliveryPolicy::Wait` a full channel is waited on until the
+    /// reader makes room, unless the shutdown token fires first.
+    async fn deliver(&mut self, result: SearchOutcome) -> bool {
+        let mut error = match self.result_tx.try_send(result) {
+            Ok(()) => return true,
+            Err(error) => error,
+        };
+
+        if matches!(error, mpsc::error::TrySendError::Full(_)) && self.config.when_full == DeliveryPolicy::Wait {
+            // reserve() keeps the result in hand if shutdown wins the race
+            let shutdown = self.shutdown.clone();
+            tokio::select! {
+                biased;
+
+                permit = self.result_tx.reserve() => match permit {
+                    Ok(permit) => {
+                        permit.send(error.into_inner());
+                        return true;
                     }
+                    Err(_) => error = mpsc::error::TrySendError::Closed(error.into_inner()),
+                },
+                _ = shutdown.cancelled() => {}
+            }
+        }
 
-                    // If we still have a found target, report it
-                    if let Some((final_index, final_id)) = found_target_event_id.take() {
-                        println!("  [Handler] >> Reporting final adjusted index: {}", final_index);
-                        result_tx.send(TargetEventFound {
-                            target_event_id: final_id,
-                            index: final_index,
-                        }).await.ok();
-                        target_event_id = None;
+        match error {
+            mpsc::error::TrySendError::Full(outcome) => match self.config.when_full {
+                DeliveryPolicy::Drop => {}
+                // Wait only gets here if the handler is shutting down
+                DeliveryPolicy::Log | DeliveryPolicy::Wait => {
+                    self.sink.emit(&HandlerEvent::ResultUndelivered { reason: "full", outcome });
+                }
+                DeliveryPolicy::Stop => self.stop("full"),
+            },
+            mpsc::error::TrySendError::Closed(outcome) => match self.config.when_closed {
+                DeliveryPolicy::Drop => {}
+                DeliveryPolicy::Log => {
+                    self.sink.emit(&HandlerEvent::ResultUndelivered { reason: "closed", outcome });
+                }
+                // A closed channel never has room again, so waiting means stopping
+                DeliveryPolicy::Stop | DeliveryPolicy::Wait => self.stop("closed"),
+            },
+        }
+        false
+    }
+
+    fn stop(&mut self, reason: &'static str) {
+        self.sink.emit(&HandlerEvent::Stopping { reason });
+        self.stopped = true;
+    }
+
+    /// Handle a timeline update
+    ///
+    /// Returns the index sent on the result channel, if this diff finished
+    /// a search.
+    async fn handle_diff(&mut self, diff: TimelineDiff) -> Option<usize> {
+        // First, adjust the found index if we have one
+        if let Some((target_idx, _target_id)) = self.found_target_event_id.as_mut() {
+            match &diff {
+                TimelineDiff::PushFront { .. } => {
+                    // OK: ADJUST: Prepended item shifts index forward
+                    *target_idx += 1;
+                    self.sink.emit(&HandlerEvent::IndexAdjusted { cause: "prepended", index: *target_idx });
+                }
+                TimelineDiff::Insert { index, .. } => {
+                    // OK: ADJUST: Insertion before target shifts it forward
+                    if *index <= *target_idx {
+                        *target_idx += 1;
+                        self.sink.emit(&HandlerEvent::IndexAdjusted { cause: "inserted", index: *target_idx });
                     }
-                } else if let Some(ref target_id) = target_event_id {
-                    // Still searching - check if this diff contains our target
-                    let is_target = match &diff {
-                        TimelineDiff::PushFront { item } |
-                        TimelineDiff::PushBack { item } |
-                        TimelineDiff::Insert { item, .. } => {
-                            item.event_id == *target_id
-                        }
-                        _ => false,
-                    };
-
-                    if is_target {
-                        // Found the target in this diff!
-                        let index = match &diff {
-                            TimelineDiff::PushFront { .. } => 0,
-                            TimelineDiff::PushBack { .. } => timeline.get_length().await - 1,
-                            TimelineDiff::Insert { index, .. } => *index,
-                            _ => unreachable!(),
-                        };
-
-                        println!("  [Handler] ✓ Found '{}' in diff at index {}", target_id, index);
-
-                        // Mark as found, will be reported after this batch
-                        found_target_event_id = Some((index, target_id.clone()));
+                }
+                TimelineDiff::Remove { index } => {
+                    // OK: ADJUST: Removal before target shifts it backward
+                    if *index < *target_idx {
+                        *target_idx = target_idx.saturating_sub(1);
+                        self.sink.emit(&HandlerEvent::IndexAdjusted { cause: "removed", index: *target_idx });
+                    } else if *index == *target_idx {
+                        // Target itself was removed!
+                        self.sink.emit(&HandlerEvent::TargetRemoved);
+                        self.found_target_event_id = None;
+                        self.target_event_id = None;
                     }
                 }
+                TimelineDiff::PushBack { .. } => {
+                    // Appending to end doesn't affect indices
+                }
+            }
+        } else if let Some(ref target_id) = self.target_event_id {
+            // Still searching - check if this diff contains our target
+            let is_target = match &diff {
+                TimelineDiff::PushFront { item } |
+                TimelineDiff::PushBack { item } |
+                TimelineDiff::Insert { item, .. } => {
+                    item.event_id == *target_id
+                }
+                _ => false,
+            };
+
+            if is_target {
+                // Found the target in this diff!
+                let index = match &diff {
+                    TimelineDiff::PushFront { .. } => 0,
+                    // Not applied yet: the item lands at the current length
+                    TimelineDiff::PushBack { .. } => self.timeline.get_length().await,
+                    TimelineDiff::Insert { index, .. } => *index,
+                    _ => unreachable!(),
+                };
 
-                // Apply the diff to timeline
-                timeline.apply_diff(diff).await;
+                self.sink.emit(&HandlerEvent::FoundInDiff {
+                    target_event_id: target_id.clone(),
+                    index,
+                });
+
+                // Mark as found, will be reported after this batch
+                self.found_target_event_id = Some((index, target_id.clone()));
             }
+        }
 
-            else => break,
+        // Apply the diff to timeline
+        self.timeline.apply_diff(diff).await;
+        self.generation += 1;
+
+        // OK: RE-ANCHOR: After many diffs since the request, don't trust
+        // that every change arrived as a diff; look the target up directly
+        let diffs_since_request = self.generation - self.request_generation;
+        if let Some((found_idx, found_id)) = self.found_target_event_id.as_mut() {
+            if diffs_since_request > REANCHOR_AFTER_DIFFS {
+                if let Some(actual) = self.timeline.find_index(found_id).await {
+                    if actual != *found_idx {
+                        self.sink.emit(&HandlerEvent::Reanchored {
+                            diffs_since_request,
+                            from: *found_idx,
+                            to: actual,
+                        });
+                        *found_idx = actual;
+                    }
+                }
+            }
+        }
+
+        // Report once the queued batch is drained, so the index already
+        // reflects every diff that was waiting behind the match. Past the
+        // re-anchor limit the index was just checked against the timeline
+        // itself, so a steady diff stream can't hold the report back
+        let mut reported = None;
+        if self.diff_rx.is_empty() || diffs_since_request > REANCHOR_AFTER_DIFFS {
+            if let Some((final_index, final_id)) = self.found_target_event_id.take() {
+                self.sink.emit(&HandlerEvent::Reporting { index: final_index });
+                let delivered = self.deliver(SearchOutcome::Found(TargetEventFound {
+                    target_event_id: final_id,
+                    index: final_index,
+                })).await;
+                self.target_event_id = None;
+                reported = delivered.then_some(final_index);
+            }
         }
+
+        reported
     }
 }
 
+/// OK: CORRECT: Search handler with snapshot validation and index adjustment
+///
+/// This function demonstrates four key patterns:
+///
+/// 1. **Snapshot Validation**: Checks if timeline changed since request
+/// 2. **Index Adjustment**: Tracks found index as timeline is modified
+/// 3. **Biased Selection**: Prioritizes requests over timeline updates
+/// 4. **Re-anchoring**: After a long burst of diffs, re-scans for the target
+async fn timeline_search_handler(
+    timeline: Timeline,
+    request_rx: mpsc::Receiver<BackwardsPaginateRequest>,
+    diff_rx: mpsc::Receiver<TimelineDiff>,
+    result_tx: mpsc::Sender<SearchOutcome>,
+    config: HandlerConfig,
+    shutdown: CancellationToken,
+) {
+    SearchHandler::with_config(timeline, request_rx, diff_rx, result_tx, config)
+        .with_shutdown(shutdown)
+        .run()
+        .await;
+}
+
 /// Simulate concurrent timeline modifications
 async fn simulate_concurrent_updates(
     timeline: Timeline,
@@ -314,6 +803,8 @@ async fn verify_result(timeline: &Timeline, found_index: usize, expected_event_i
 
 #[tokio::main]
 async fn main() {
+    env_logger::Builder::from_env(Env::default().default_filter_or("info")).init();
+
     println!("=== Backwards Pagination with Snapshot Validation (CORRECT) ===\n");
 
     let timeline = Timeline::new();
@@ -331,14 +822,17 @@ async fn main() {
     }
 
     // Create channels
-    let (request_tx, request_rx) = mpsc::channel(10);
-    let (diff_tx, diff_rx) = mpsc::channel(100);
-    let (result_tx, mut result_rx) = mpsc::channel(10);
+    let config = HandlerConfig::default();
+    let (request_tx, request_rx) = config.request_channel();
+    let (diff_tx, diff_rx) = config.diff_channel();
+    let (result_tx, mut result_rx) = config.result_channel();
 
     // Start the search handler
+    let shutdown = CancellationToken::new();
     let handler_timeline = Timeline { items: timeline.items.clone() };
-    tokio::spawn(async move {
-        timeline_search_handler(handler_timeline, request_rx, diff_rx, result_tx).await;
+    let handler_shutdown = shutdown.clone();
+    let handler = tokio::spawn(async move {
+        timeline_search_handler(handler_timeline, request_rx, diff_rx, result_tx, config, handler_shutdown).await;
     });
 
     // Start concurrent modifications
@@ -361,11 +855,13 @@ async fn main() {
         target_event_id: target_event_id.clone(),
         starting_index,
         current_tl_len,
+        // The reply preview remembers roughly where the original was
+        index_hint: Some(6),
     }).await.ok();
 
     // Wait for result
-    if let Some(result) = result_rx.recv().await {
-        println!("\n>> Search returned index: {}", result.index);
+    if let Some(SearchOutcome::Found(result)) = result_rx.recv().await {
+        println!("\n>> Search returned index {} for '{}'", result.index, result.target_event_id);
 
         // Wait for concurrent modifications to complete
         sleep(Duration::from_millis(600)).await;
@@ -382,17 +878,55 @@ async fn main() {
         println!("\nERROR: Search failed to find the event");
     }
 
+    // Coordinated shutdown: the handler stops even though its channels are open
+    shutdown.cancel();
+    handler.await.ok();
+
     println!("\n=== Key Techniques Demonstrated ===");
     println!("1. OK: Snapshot validation detects timeline changes");
     println!("2. OK: Incremental index adjustment tracks modifications");
     println!("3. OK: Biased selection reduces race windows");
-    println!("4. OK: Found index always points to correct message\n");
+    println!("4. OK: Long diff bursts re-anchor the index by re-scanning");
+    println!("5. OK: Found index always points to correct message\n");
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    use futures_util::FutureExt;
+
+    // Test-only entry points: the binary only ever goes through `run`
+    impl SearchHandler {
+        /// Send handler events to `sink` instead of the `log` facade
+        fn with_sink(mut self, sink: impl LogSink + 'static) -> Self {
+            self.sink = Box::new(sink);
+            self
+        }
+
+        /// Handle exactly one pending input without waiting for one
+        ///
+        /// Goes through the same `next_input`/`handle_input` pair as `run`, so
+        /// tests can drive the handler step by step instead of racing tasks.
+        /// Returns `None` if nothing was pending.
+        async fn process_once(&mut self) -> Option<Step> {
+            if self.stopped {
+                return Some(Step::Stopped);
+            }
+            // Unconstrained: a test looping over this never yields, so tokio's
+            // coop budget would eventually make `recv` look empty
+            let input = tokio::task::unconstrained(self.next_input()).now_or_never()?;
+            Some(self.handle_input(input).await)
+        }
+    }
+
+    fn found(outcome: SearchOutcome) -> TargetEventFound {
+        match outcome {
+            SearchOutcome::Found(found) => found,
+            other => panic!("expected a found target, got {:?}", other),
+        }
+    }
+
     #[tokio::test]
     async fn test_snapshot_validation_detects_changes() {
         let timeline = Timeline::new();
@@ -500,15 +1034,8 @@ mod tests {
             }
         }
 
-        let (request_tx, request_rx) = mpsc::channel(10);
-        let (diff_tx, diff_rx) = mpsc::channel(100);
-        let (result_tx, mut result_rx) = mpsc::channel(10);
-
-        // Start handler
-        let handler_timeline = Timeline { items: timeline.items.clone() };
-        tokio::spawn(async move {
-            timeline_search_handler(handler_timeline, request_rx, diff_rx, result_tx).await;
-        });
+        let Fixture { mut handler, request_tx, diff_tx: _diff_tx, mut result_rx } =
+            fixture(&timeline, HandlerConfig::default());
 
         // Take snapshot BEFORE modification
         let snapshot_len = timeline.get_length().await;
@@ -518,18 +1045,510 @@ mod tests {
             target_event_id: "event_3".to_string(),
             starting_index: 8,
             current_tl_len: snapshot_len,
+            index_hint: None,
         }).await.ok();
 
-        // Give handler time to process
-        sleep(Duration::from_millis(50)).await;
+        // Step the handler instead of waiting for a spawned task
+        assert_eq!(handler.process_once().await, Some(Step::Request {
+            target_event_id: "event_3".to_string(),
+            found_index: Some(3),
+        }));
+        assert_eq!(handler.process_once().await, None);
 
         // Get result - should find at index 3
-        if let Some(result) = result_rx.recv().await {
-            // Verify the index is correct (still 3, no modifications yet)
-            let item = timeline.get_item(result.index).await;
-            assert!(item.is_some());
-            assert_eq!(item.unwrap().event_id, "event_3");
-            assert_eq!(result.index, 3);
+        let result = found(result_rx.try_recv().expect("result is sent before the step returns"));
+        // Verify the index is correct (still 3, no modifications yet)
+        let item = timeline.get_item(result.index).await;
+        assert!(item.is_some());
+        assert_eq!(item.unwrap().event_id, "event_3");
+        assert_eq!(result.index, 3);
+    }
+
+    #[tokio::test]
+    async fn test_prepend_burst_before_match_reports_exact_index() {
+        let timeline = timeline_of(5).await;
+        let Fixture { handler, request_tx, diff_tx, mut result_rx } =
+            fixture(&timeline, HandlerConfig::default());
+        let sink = CapturingSink::default();
+        let mut handler = handler.with_sink(sink.clone());
+
+        // Queue the request and the whole burst before the handler runs;
+        // requests are still taken first
+        request_tx.send(BackwardsPaginateRequest {
+            target_event_id: "late_event".to_string(),
+            starting_index: 5,
+            current_tl_len: 5,
+            index_hint: None,
+        }).await.unwrap();
+
+        // Short enough that every diff, including the one behind the match,
+        // lands before the re-anchor limit: only the adjustments are in play
+        let burst = REANCHOR_AFTER_DIFFS as usize / 2;
+        for i in 0..burst {
+            diff_tx.send(TimelineDiff::PushFront { item: item(&format!("old_{}", i)) }).await.unwrap();
+        }
+        diff_tx.send(TimelineDiff::PushBack { item: item("late_event") }).await.unwrap();
+        // One more diff queued behind the match; the report must include it
+        diff_tx.send(TimelineDiff::PushFront { item: item("old_last") }).await.unwrap();
+        drop(request_tx);
+        drop(diff_tx);
+
+        while handler.process_once().await != Some(Step::Closed) {}
+        let result = found(result_rx.try_recv().expect("target should be reported"));
+
+        // 5 initial + burst + old_last in front of it
+        assert_eq!(result.index, 5 + burst + 1);
+        let item = timeline.get_item(result.index).await.unwrap();
+        assert_eq!(item.event_id, "late_event");
+
+        // The per-diff adjustments were exact on their own
+        let events = sink.0.lock().unwrap();
+        assert!(events.contains(&HandlerEvent::FoundInDiff {
+            target_event_id: "late_event".to_string(),
+            index: 5 + burst,
+        }));
+        assert!(!events.iter().any(|e| matches!(e, HandlerEvent::Reanchored { .. })));
+    }
+
+    #[tokio::test]
+    async fn test_burst_past_reanchor_limit_reports_without_draining() {
+        let timeline = timeline_of(5).await;
+        let Fixture { handler, request_tx, diff_tx, mut result_rx } =
+            fixture(&timeline, HandlerConfig::default());
+        let sink = CapturingSink::default();
+        let mut handler = handler.with_sink(sink.clone());
+        let limit = REANCHOR_AFTER_DIFFS as usize;
+
+        // The match comes first, then a stream of prepends that outlasts the limit
+        request_tx.send(request_for("target", 5)).await.unwrap();
+        diff_tx.send(TimelineDiff::PushBack { item: item("target") }).await.unwrap();
+        for i in 0..2 * limit {
+            diff_tx.send(TimelineDiff::PushFront { item: item(&format!("old_{}", i)) }).await.unwrap();
+        }
+        handler.process_once().await;
+        assert_eq!(handler.process_once().await, Some(Step::Diff { tracked_index: Some(5), reported: None }));
+
+        // Another holder of the shared timeline prepends without sending diffs
+        {
+            let mut items = timeline.items.write().await;
+            items.insert(0, item("direct_0"));
+            items.insert(0, item("direct_1"));
+        }
+
+        // Up to the limit the report waits for the queue to drain
+        for prepended in 1..limit {
+            assert_eq!(handler.process_once().await, Some(Step::Diff {
+                tracked_index: Some(5 + prepended),
+                reported: None,
+            }));
         }
+
+        // The first diff past it re-anchors and reports, with diffs still queued
+        let expected = 5 + limit + 2;
+        assert_eq!(handler.process_once().await, Some(Step::Diff {
+            tracked_index: None,
+            reported: Some(expected),
+        }));
+        assert!(!handler.diff_rx.is_empty());
+        assert_eq!(found(result_rx.try_recv().unwrap()).index, expected);
+        assert_eq!(timeline.get_item(expected).await.unwrap().event_id, "target");
+        assert!(sink.0.lock().unwrap().contains(&HandlerEvent::Reanchored {
+            diffs_since_request: REANCHOR_AFTER_DIFFS + 1,
+            from: 5 + limit,
+            to: expected,
+        }));
+    }
+
+    #[tokio::test]
+    async fn test_push_back_match_reports_its_own_index() {
+        for initial in [0, 3] {
+            let timeline = timeline_of(initial).await;
+            let Fixture { mut handler, request_tx, diff_tx, mut result_rx } =
+                fixture(&timeline, HandlerConfig::default());
+
+            request_tx.send(request_for("target", initial)).await.unwrap();
+            handler.process_once().await;
+            diff_tx.send(TimelineDiff::PushBack { item: item("target") }).await.unwrap();
+            handler.process_once().await;
+
+            // Well under REANCHOR_AFTER_DIFFS, so no re-scan can hide a wrong index
+            let result = found(result_rx.try_recv().expect("target should be reported"));
+            assert_eq!(result.index, initial);
+            assert_eq!(timeline.get_item(result.index).await.unwrap().event_id, "target");
+        }
+    }
+
+    #[tokio::test]
+    async fn test_out_of_band_edits_are_reanchored_after_long_wait() {
+        let timeline = timeline_of(5).await;
+        let Fixture { handler, request_tx, diff_tx, mut result_rx } =
+            fixture(&timeline, HandlerConfig::default());
+        let sink = CapturingSink::default();
+        let mut handler = handler.with_sink(sink.clone());
+
+        request_tx.send(request_for("target", 5)).await.unwrap();
+        handler.process_once().await;
+
+        // Match recorded at 5, with a diff still queued so it isn't reported yet
+        diff_tx.send(TimelineDiff::PushBack { item: item("target") }).await.unwrap();
+        diff_tx.send(TimelineDiff::PushBack { item: item("filler_0") }).await.unwrap();
+        assert_eq!(handler.process_once().await, Some(Step::Diff { tracked_index: Some(5), reported: None }));
+
+        // Another holder of the shared timeline prepends without sending diffs
+        {
+            let mut items = timeline.items.write().await;
+            items.insert(0, item("direct_0"));
+            items.insert(0, item("direct_1"));
+        }
+
+        for i in 1..=REANCHOR_AFTER_DIFFS {
+            diff_tx.send(TimelineDiff::PushBack { item: item(&format!("filler_{}", i)) }).await.unwrap();
+        }
+        drop(diff_tx);
+        drop(request_tx);
+        while handler.process_once().await != Some(Step::Closed) {}
+
+        let result = found(result_rx.try_recv().expect("target should be reported"));
+        assert_eq!(result.index, 7);
+        assert_eq!(timeline.get_item(result.index).await.unwrap().event_id, "target");
+        assert!(sink.0.lock().unwrap().iter().any(|e| {
+            matches!(e, HandlerEvent::Reanchored { from: 5, to: 7, .. })
+        }));
+    }
+
+    #[tokio::test]
+    async fn test_process_once_tracks_prepends_step_by_step() {
+        let timeline = Timeline::new();
+        let Fixture { mut handler, request_tx, diff_tx, mut result_rx } =
+            fixture(&timeline, HandlerConfig::default());
+
+        request_tx.send(request_for("target", 0)).await.unwrap();
+        diff_tx.send(TimelineDiff::PushFront { item: item("target") }).await.unwrap();
+        for i in 0..3 {
+            diff_tx.send(TimelineDiff::PushFront { item: item(&format!("old_{}", i)) }).await.unwrap();
+        }
+
+        assert_eq!(handler.process_once().await, Some(Step::Request {
+            target_event_id: "target".to_string(),
+            found_index: None,
+        }));
+        assert_eq!(handler.process_once().await, Some(Step::Diff { tracked_index: Some(0), reported: None }));
+
+        // Each prepend moves the tracked index by exactly one
+        assert_eq!(handler.process_once().await, Some(Step::Diff { tracked_index: Some(1), reported: None }));
+        assert_eq!(handler.process_once().await, Some(Step::Diff { tracked_index: Some(2), reported: None }));
+        assert!(result_rx.try_recv().is_err());
+
+        // The last queued diff drains the batch, so the index is reported
+        assert_eq!(handler.process_once().await, Some(Step::Diff { tracked_index: None, reported: Some(3) }));
+        assert_eq!(found(result_rx.try_recv().unwrap()).index, 3);
+        assert_eq!(timeline.get_item(3).await.unwrap().event_id, "target");
+        assert_eq!(handler.process_once().await, None);
+
+        drop(request_tx);
+        drop(diff_tx);
+        assert_eq!(handler.process_once().await, Some(Step::Closed));
+    }
+
+    async fn timeline_of(len: usize) -> Timeline {
+        let timeline = Timeline::new();
+        let mut items = timeline.items.write().await;
+        for i in 0..len {
+            items.push(TimelineItem {
+                event_id: format!("event_{}", i),
+                content: format!("Message {}", i),
+            });
+        }
+        drop(items);
+        timeline
+    }
+
+    fn request_for(target_event_id: &str, len: usize) -> BackwardsPaginateRequest {
+        BackwardsPaginateRequest {
+            target_event_id: target_event_id.to_string(),
+            starting_index: len,
+            current_tl_len: len,
+            index_hint: None,
+        }
+    }
+
+    /// A handler over a clone of `timeline`, plus the channel ends a test drives it with
+    struct Fixture {
+        handler: SearchHandler,
+        request_tx: mpsc::Sender<BackwardsPaginateRequest>,
+        diff_tx: mpsc::Sender<TimelineDiff>,
+        result_rx: mpsc::Receiver<SearchOutcome>,
+    }
+
+    fn fixture(timeline: &Timeline, config: HandlerConfig) -> Fixture {
+        let (request_tx, request_rx) = config.request_channel();
+        let (diff_tx, diff_rx) = config.diff_channel();
+        let (result_tx, result_rx) = config.result_channel();
+        let handler = SearchHandler::with_config(
+            Timeline { items: timeline.items.clone() }, request_rx, diff_rx, result_tx, config,
+        );
+        Fixture { handler, request_tx, diff_tx, result_rx }
+    }
+
+    fn item(event_id: &str) -> TimelineItem {
+        TimelineItem {
+            event_id: event_id.to_string(),
+            content: event_id.to_string(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_dropped_result_receiver_stops_handler() {
+        let timeline = timeline_of(5).await;
+        let config = HandlerConfig::default();
+        let (request_tx, request_rx) = config.request_channel();
+        let (_diff_tx, diff_rx) = config.diff_channel();
+        let (result_tx, result_rx) = config.result_channel();
+        drop(result_rx);
+
+        let handler = tokio::spawn(timeline_search_handler(
+            Timeline { items: timeline.items.clone() }, request_rx, diff_rx, result_tx, config,
+            CancellationToken::new(),
+        ));
+        request_tx.send(request_for("event_2", 5)).await.unwrap();
+
+        // Input channels are still open, so only the Stop policy ends the task
+        tokio::time::timeout(Duration::from_secs(1), handler)
+            .await
+            .expect("handler should stop once its result receiver is gone")
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_full_and_closed_result_channel_follow_policy() {
+        let timeline = timeline_of(5).await;
+        let config = HandlerConfig {
+            result_capacity: 1,
+            when_full: DeliveryPolicy::Drop,
+            when_closed: DeliveryPolicy::Log,
+            ..HandlerConfig::default()
+        };
+        let Fixture { mut handler, request_tx, diff_tx: _diff_tx, mut result_rx } = fixture(&timeline, config);
+
+        for target in ["event_1", "event_2"] {
+            request_tx.send(request_for(target, 5)).await.unwrap();
+            handler.process_once().await;
+        }
+
+        // Only the first result fit; the second was dropped, not awaited
+        assert_eq!(found(result_rx.try_recv().unwrap()).target_event_id, "event_1");
+        assert!(result_rx.try_recv().is_err());
+        assert_eq!(handler.process_once().await, None);
+
+        // A closed receiver is only logged under this config
+        drop(result_rx);
+        request_tx.send(request_for("event_3", 5)).await.unwrap();
+        handler.process_once().await;
+        assert_eq!(handler.process_once().await, None);
+    }
+
+    #[tokio::test]
+    async fn test_full_result_channel_waits_for_reader_by_default() {
+        let timeline = timeline_of(5).await;
+        let config = HandlerConfig { result_capacity: 1, ..HandlerConfig::default() };
+        assert_eq!(config.when_full, DeliveryPolicy::Wait);
+        let Fixture { mut handler, request_tx, diff_tx: _diff_tx, mut result_rx } = fixture(&timeline, config);
+
+        request_tx.send(request_for("event_1", 5)).await.unwrap();
+        request_tx.send(request_for("event_2", 5)).await.unwrap();
+        handler.process_once().await;
+
+        // The second result only fits once the reader takes the first
+        let (step, first) = tokio::join!(handler.process_once(), result_rx.recv());
+        assert_eq!(step, Some(Step::Request {
+            target_event_id: "event_2".to_string(),
+            found_index: Some(2),
+        }));
+        assert_eq!(found(first.unwrap()).target_event_id, "event_1");
+        assert_eq!(found(result_rx.try_recv().unwrap()).target_event_id, "event_2");
+    }
+
+    #[tokio::test]
+    async fn test_waiting_delivery_gives_up_on_shutdown() {
+        let timeline = timeline_of(5).await;
+        let config = HandlerConfig { result_capacity: 1, ..HandlerConfig::default() };
+        let Fixture { handler, request_tx, diff_tx: _diff_tx, mut result_rx } = fixture(&timeline, config);
+        let shutdown = CancellationToken::new();
+        let sink = CapturingSink::default();
+        let mut handler = handler.with_shutdown(shutdown.clone()).with_sink(sink.clone());
+
+        request_tx.send(request_for("event_1", 5)).await.unwrap();
+        request_tx.send(request_for("event_2", 5)).await.unwrap();
+        handler.process_once().await;
+
+        // Nobody reads, so only the shutdown can end the wait
+        let (step, ()) = tokio::join!(handler.process_once(), async { shutdown.cancel() });
+        assert!(matches!(step, Some(Step::Request { .. })));
+        assert!(sink.0.lock().unwrap().iter().any(|e| matches!(
+            e,
+            HandlerEvent::ResultUndelivered { reason: "full", .. }
+        )));
+        assert_eq!(found(result_rx.try_recv().unwrap()).target_event_id, "event_1");
+        assert!(result_rx.try_recv().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_index_hint_needs_far_fewer_comparisons() {
+        let timeline = timeline_of(10_000).await;
+        let items = timeline.items.read().await;
+
+        let (backwards, full_scan) = search_backwards(&items, "event_2000", 10_000);
+        let (around_hint, hinted) = search_around_hint(&items, "event_2000", 2_010, 10_000);
+
+        assert_eq!(backwards, Some(2000));
+        assert_eq!(around_hint, Some(2000));
+        assert_eq!(full_scan, 8_000);
+        // Distance 10 from the hint: the hint itself plus two per step
+        assert_eq!(hinted, 20);
+        assert!(hinted * 100 < full_scan);
+    }
+
+    #[tokio::test]
+    async fn test_index_hint_search_widens_and_respects_bounds() {
+        let timeline = timeline_of(10).await;
+        let items = timeline.items.read().await;
+
+        // Hint below the target: found by widening upward
+        assert_eq!(search_around_hint(&items, "event_7", 4, 10), (Some(7), 7));
+        // Hint past the end is clamped
+        assert_eq!(search_around_hint(&items, "event_9", 500, 10), (Some(9), 1));
+        // Items at or past `end` are never compared
+        assert_eq!(search_around_hint(&items, "event_8", 2, 8), (None, 8));
+        assert_eq!(search_around_hint(&items, "event_0", 0, 0), (None, 0));
+    }
+
+    #[tokio::test]
+    async fn test_stale_hint_after_timeline_change_still_finds_target() {
+        let timeline = timeline_of(100).await;
+        let Fixture { mut handler, request_tx, diff_tx: _diff_tx, mut result_rx } =
+            fixture(&timeline, HandlerConfig::default());
+
+        // Snapshot says 90 items, so the hint was taken before 10 more arrived
+        request_tx.send(BackwardsPaginateRequest {
+            target_event_id: "event_42".to_string(),
+            starting_index: 90,
+            current_tl_len: 90,
+            index_hint: Some(30),
+        }).await.unwrap();
+        handler.process_once().await;
+
+        assert_eq!(found(result_rx.try_recv().unwrap()).index, 42);
+    }
+
+    /// Sink that records every event for later assertions
+    #[derive(Clone, Default)]
+    struct CapturingSink(Arc<std::sync::Mutex<Vec<HandlerEvent>>>);
+
+    impl LogSink for CapturingSink {
+        fn emit(&self, event: &HandlerEvent) {
+            self.0.lock().unwrap().push(event.clone());
+        }
+    }
+
+    #[tokio::test]
+    async fn test_changed_timeline_emits_snapshot_invalidated() {
+        let timeline = timeline_of(12).await;
+        let Fixture { handler, request_tx, diff_tx: _diff_tx, mut result_rx } =
+            fixture(&timeline, HandlerConfig::default());
+        let sink = CapturingSink::default();
+        let mut handler = handler.with_sink(sink.clone());
+
+        // Snapshot taken when the timeline still had 10 items
+        request_tx.send(request_for("event_4", 10)).await.unwrap();
+        handler.process_once().await;
+        assert_eq!(found(result_rx.try_recv().unwrap()).index, 4);
+
+        let events = sink.0.lock().unwrap();
+        assert!(events.contains(&HandlerEvent::SnapshotInvalidated {
+            snapshot_len: 10,
+            current_len: 12,
+        }));
+        assert!(!events.iter().any(|e| matches!(e, HandlerEvent::SnapshotValid { .. })));
+        assert_eq!(events.last(), Some(&HandlerEvent::FoundInTimeline {
+            target_event_id: "event_4".to_string(),
+            index: 4,
+        }));
+    }
+
+    #[tokio::test]
+    async fn test_cancel_stops_pending_search_promptly() {
+        let timeline = timeline_of(5).await;
+        let config = HandlerConfig::default();
+        let (request_tx, request_rx) = config.request_channel();
+        let (diff_tx, diff_rx) = config.diff_channel();
+        let (result_tx, mut result_rx) = config.result_channel();
+        let shutdown = CancellationToken::new();
+
+        let handler = tokio::spawn(timeline_search_handler(
+            Timeline { items: timeline.items.clone() }, request_rx, diff_rx, result_tx, config,
+            shutdown.clone(),
+        ));
+
+        // Never matched: the handler keeps waiting for diffs that contain it
+        request_tx.send(request_for("never_arrives", 5)).await.unwrap();
+        for i in 0..3 {
+            diff_tx.send(TimelineDiff::PushBack { item: item(&format!("unrelated_{}", i)) }).await.unwrap();
+        }
+        tokio::task::yield_now().await;
+
+        shutdown.cancel();
+        tokio::time::timeout(Duration::from_secs(1), handler)
+            .await
+            .expect("handler should stop promptly once cancelled")
+            .unwrap();
+
+        assert_eq!(result_rx.recv().await, Some(SearchOutcome::Cancelled {
+            target_event_id: "never_arrives".to_string(),
+        }));
+        // The handler is gone even though both input senders are still alive
+        assert!(result_rx.recv().await.is_none());
+        drop((request_tx, diff_tx));
+    }
+
+    #[tokio::test]
+    async fn test_handler_exits_once_inputs_close() {
+        let timeline = timeline_of(5).await;
+        let config = HandlerConfig::default();
+        let (request_tx, request_rx) = config.request_channel();
+        let (diff_tx, diff_rx) = config.diff_channel();
+        let (result_tx, _result_rx) = config.result_channel();
+
+        let handler = tokio::spawn(timeline_search_handler(
+            Timeline { items: timeline.items.clone() }, request_rx, diff_rx, result_tx, config,
+            CancellationToken::new(),
+        ));
+        drop((request_tx, diff_tx));
+
+        // Nobody cancels the token, so only `else => break` can end the loop
+        tokio::time::timeout(Duration::from_secs(1), handler)
+            .await
+            .expect("handler should stop once both input channels close")
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_process_once_reports_cancellation() {
+        let timeline = timeline_of(5).await;
+        let Fixture { handler, request_tx, diff_tx: _diff_tx, mut result_rx } =
+            fixture(&timeline, HandlerConfig::default());
+        let shutdown = CancellationToken::new();
+        let sink = CapturingSink::default();
+        let mut handler = handler.with_shutdown(shutdown.clone()).with_sink(sink.clone());
+
+        // Found immediately, so nothing is pending when the token fires
+        request_tx.send(request_for("event_1", 5)).await.unwrap();
+        handler.process_once().await;
+        assert_eq!(found(result_rx.try_recv().unwrap()).index, 1);
+
+        shutdown.cancel();
+        assert_eq!(handler.process_once().await, Some(Step::Cancelled));
+        assert_eq!(handler.process_once().await, Some(Step::Stopped));
+        assert!(result_rx.try_recv().is_err());
+        assert!(sink.0.lock().unwrap().contains(&HandlerEvent::Cancelled { pending: None }));
     }
 }
@@ -292,3 +292,108 @@ fn main() {
 
     println!("✓ All solutions work correctly and safely!");
 }
+
+// ============================================================================
+// Tests & benchmark: trait object vs sort_by vs FFI qsort
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Small xorshift generator so the benchmark needs no extra dependencies.
+    fn random_i32s(len: usize, mut seed: u64) -> Vec<i32> {
+        (0..len)
+            .map(|_| {
+                seed ^= seed << 13;
+                seed ^= seed >> 7;
+                seed ^= seed << 17;
+                seed as i32
+            })
+            .collect()
+    }
+
+    fn sort_trait_object(data: &mut [i32]) {
+        rust_sort(data, &AscendingComparator);
+    }
+
+    // Deliberately a closure comparator: this is the path being measured.
+    #[allow(clippy::unnecessary_sort_by)]
+    fn sort_slice_sort_by(data: &mut [i32]) {
+        data.sort_by(|a, b| a.cmp(b));
+    }
+
+    fn sort_ffi_qsort(data: &mut [i32]) {
+        unsafe {
+            qsort(
+                data.as_mut_ptr() as *mut c_void,
+                data.len(),
+                std::mem::size_of::<i32>(),
+                compare_ascending,
+            );
+        }
+    }
+
+    type SortFn = fn(&mut [i32]);
+
+    const STRATEGIES: [(&str, SortFn); 3] = [
+        ("trait object (rust_sort)", sort_trait_object),
+        ("slice::sort_by", sort_slice_sort_by),
+        ("FFI qsort", sort_ffi_qsort),
+    ];
+
+    /// Sorts a copy of `input` with every strategy and asserts they agree.
+    fn sort_with_all(input: &[i32]) -> Vec<i32> {
+        let mut expected = input.to_vec();
+        expected.sort_unstable();
+
+        for (name, sort) in STRATEGIES {
+            let mut data = input.to_vec();
+            sort(&mut data);
+            assert_eq!(data, expected, "{} produced a different ordering", name);
+        }
+        expected
+    }
+
+    #[test]
+    fn all_strategies_produce_identical_output() {
+        for (len, seed) in [(0, 1), (1, 2), (1_000, 3), (100_000, 4)] {
+            let input = random_i32s(len, seed);
+            let sorted = sort_with_all(&input);
+            assert!(sorted.windows(2).all(|w| w[0] <= w[1]));
+        }
+    }
+
+    /// Timed comparison. Run with:
+    /// `cargo test --release --features bench -- --nocapture bench_`
+    #[cfg(feature = "bench")]
+    #[test]
+    fn bench_sort_strategies() {
+        use std::time::{Duration, Instant};
+
+        const ITERATIONS: u32 = 20;
+
+        for len in [1_000, 100_000] {
+            let input = random_i32s(len, 0x5eed);
+            sort_with_all(&input);
+
+            println!("\n{} random i32 ({} iterations):", len, ITERATIONS);
+            for (name, sort) in STRATEGIES {
+                let mut total = Duration::ZERO;
+                for _ in 0..ITERATIONS {
+                    let mut data = input.clone();
+                    let start = Instant::now();
+                    sort(&mut data);
+                    total += start.elapsed();
+                    std::hint::black_box(&data);
+                }
+                let per_iter = total / ITERATIONS;
+                let throughput = len as f64 / per_iter.as_secs_f64() / 1_000_000.0;
+                println!(
+                    "  {:<26} {:>10.2?}/iter  {:>8.2} Melem/s",
+                    name, per_iter, throughput
+                );
+            }
+        }
+    }
+}
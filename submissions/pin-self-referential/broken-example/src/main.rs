@@ -37,49 +37,81 @@ fn main() {
     println!("  cargo +nightly miri run");
 }
 
-/// Example 1: Simple self-referential struct
-/// PROBLEM: Pointer becomes dangling when struct is moved
-fn example1_self_referential_move() {
-    struct SelfReferential {
-        data: String,
-        ptr_to_data: *const u8,
-    }
+/// Self-referential struct used by Example 1
+struct SelfReferential {
+    data: String,
+    ptr_to_data: *const u8,
+}
 
-    impl SelfReferential {
-        fn new(text: &str) -> Self {
-            let data = String::from(text);
-            let ptr_to_data = data.as_ptr();
+impl SelfReferential {
+    fn new(text: &str) -> Self {
+        let data = String::from(text);
+        let ptr_to_data = data.as_ptr();
 
-            Self {
-                data,
-                ptr_to_data, // PROBLEM: Points to data's heap buffer
-            }
+        Self {
+            data,
+            ptr_to_data, // PROBLEM: Points to data's heap buffer
         }
+    }
 
-        fn get_data(&self) -> &str {
-            unsafe {
-                // UNDEFINED BEHAVIOR: ptr_to_data may be dangling
-                let slice = std::slice::from_raw_parts(
-                    self.ptr_to_data,
-                    self.data.len()
-                );
-                std::str::from_utf8_unchecked(slice)
-            }
+    fn get_data(&self) -> &str {
+        unsafe {
+            // UNDEFINED BEHAVIOR: ptr_to_data may be dangling
+            let slice = std::slice::from_raw_parts(
+                self.ptr_to_data,
+                self.data.len()
+            );
+            std::str::from_utf8_unchecked(slice)
         }
     }
 
+    /// Debug-mode invariant: panics if `ptr_to_data` no longer points into `data`
+    ///
+    /// Turns the silent UB of a stale pointer into a loud failure before it is
+    /// read. Compiled out in release builds, like any `debug_assert!`.
+    fn assert_ptr_valid(&self) {
+        debug_assert!(
+            self.ptr_to_data == self.data.as_ptr(),
+            "SelfReferential invariant broken: ptr_to_data ({:p}) no longer points at data ({:p})",
+            self.ptr_to_data,
+            self.data.as_ptr()
+        );
+    }
+}
+
+/// Example 1: Simple self-referential struct
+/// PROBLEM: Pointer becomes dangling when struct is moved
+fn example1_self_referential_move() {
     // This works (by luck)
     let s1 = SelfReferential::new("hello");
+    s1.assert_ptr_valid();
     println!("  s1.data = {:?}", s1.get_data());
 
     // Move the struct - UB starts here
-    let s2 = s1;
+    let mut s2 = s1;
+    // Passes here: the move copied the String's heap pointer, not the heap buffer
+    s2.assert_ptr_valid();
     println!("  s2.data = {:?} (might work, might be garbage)", s2.get_data());
 
     // Note: Depending on memory layout, this might:
     // 1. Work correctly (by luck)
     // 2. Print garbage
     // 3. Segfault
+
+    // Reassigning data frees the buffer ptr_to_data points at
+    s2.data = String::from("reassigned");
+    if cfg!(debug_assertions) {
+        let caught = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            s2.assert_ptr_valid();
+        }));
+        println!(
+            "  assert_ptr_valid after reassignment: {}",
+            if caught.is_err() { "caught the dangling pointer" } else { "passed" }
+        );
+    } else {
+        println!("  assert_ptr_valid is compiled out in release builds");
+    }
+    // Calling s2.get_data() now would read freed memory
 }
 
 /// Example 2: Self-referential in Vec
@@ -148,3 +180,25 @@ fn example3_lifetime_attempt() {
     println!("  This example does not compile due to lifetime errors");
     println!("  Rust's borrow checker prevents self-referential structs with lifetimes");
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ptr_guard_holds_after_move() {
+        let s1 = SelfReferential::new("hello");
+        let s2 = s1;
+        s2.assert_ptr_valid();
+        assert_eq!(s2.get_data(), "hello");
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "SelfReferential invariant broken")]
+    fn test_ptr_guard_catches_reassignment() {
+        let mut s = SelfReferential::new("hello");
+        s.data = String::from("new string value that is much longer");
+        s.assert_ptr_valid();
+    }
+}
@@ -1,6 +1,8 @@
 // Alternative designs that avoid self-reference
 // These are often simpler and safer than using Pin
 
+use std::ptr::NonNull;
+
 pub fn demonstrate_alternatives() {
     println!("  [1] Using indices instead of pointers:");
     demo_index_based();
@@ -12,6 +14,10 @@ pub fn demonstrate_alternatives() {
 
     println!("  [3] Lazy computation:");
     demo_lazy_computation();
+    println!();
+
+    println!("  [4] Immutable Box<str>:");
+    demo_boxed_str();
 }
 
 /// Strategy 1: Use indices instead of pointers
@@ -150,6 +156,68 @@ fn demo_lazy_computation() {
     println!("    Cons: Slight indirection overhead");
 }
 
+/// Text that keeps a pointer into its own heap buffer
+///
+/// The allocation is owned through a raw pointer from `Box::into_raw`, not a
+/// `Box` field. A `Box` asserts unique ownership every time the struct is
+/// moved, which (under Stacked Borrows) invalidates any other pointer into
+/// its buffer; a raw pointer asserts nothing, so `first_word` stays usable.
+struct FrozenText {
+    // From Box::into_raw; freed in Drop
+    data: NonNull<str>,
+    // Points into data's heap buffer, never into the struct itself
+    first_word: *const str,
+}
+
+impl FrozenText {
+    fn new(text: &str) -> Self {
+        let boxed: Box<str> = text.into();
+        // SAFETY: Box::into_raw never returns null
+        let data = unsafe { NonNull::new_unchecked(Box::into_raw(boxed)) };
+        // SAFETY: data is a live allocation we own; this borrow ends here
+        let text = unsafe { data.as_ref() };
+        let first_word = text.split_whitespace().next().unwrap_or("") as *const str;
+        Self { data, first_word }
+    }
+
+    fn as_str(&self) -> &str {
+        // SAFETY: data is only freed in Drop and never written through
+        unsafe { self.data.as_ref() }
+    }
+
+    fn first_word(&self) -> &str {
+        // SAFETY: first_word was derived from data, which is never mutated,
+        // reallocated or freed while self is alive
+        unsafe { &*self.first_word }
+    }
+
+    fn len(&self) -> usize {
+        self.as_str().len()
+    }
+}
+
+impl Drop for FrozenText {
+    fn drop(&mut self) {
+        // SAFETY: data came from Box::into_raw and is freed exactly once
+        unsafe { drop(Box::from_raw(self.data.as_ptr())) }
+    }
+}
+
+/// Strategy 4: Store the data as a frozen `Box<str>` allocation
+/// A `Box<str>` can't grow, so its heap buffer never reallocates; moving the
+/// outer struct moves the pointer, not the bytes it points to
+fn demo_boxed_str() {
+    let text = FrozenText::new("Frozen text never moves");
+    println!("    First word:  {:?} (of {} bytes)", text.first_word(), text.len());
+
+    // Moving the struct copies the pointer; the heap bytes stay put
+    let text2 = text;
+    println!("    After move:  {:?}", text2.first_word());
+
+    println!("    Pros: Stored pointer is valid across moves, no Pin needed");
+    println!("    Cons: Can never grow (unlike indices), needs unsafe and a manual Drop");
+}
+
 #[cfg(test)]
 mod tests {
     #[test]
@@ -214,4 +282,25 @@ mod tests {
         let buf2 = buf;
         assert_eq!(buf2.read(), b"hello");
     }
+
+    #[test]
+    fn test_boxed_str_view_survives_move() {
+        use super::FrozenText;
+
+        let text1 = FrozenText::new("test string");
+        let heap_ptr = text1.as_str().as_ptr();
+        assert_eq!(text1.first_word(), "test");
+
+        let text2 = text1; // Move
+        assert_eq!(text2.as_str().as_ptr(), heap_ptr, "Heap buffer must not move");
+        assert_eq!(text2.first_word(), "test");
+
+        // Moving into a Vec that reallocates doesn't change the view either
+        let mut texts = vec![text2];
+        for i in 0..16 {
+            texts.push(FrozenText::new(&format!("filler {}", i)));
+        }
+        assert_eq!(texts[0].first_word(), "test");
+        assert_eq!(texts[16].first_word(), "filler");
+    }
 }
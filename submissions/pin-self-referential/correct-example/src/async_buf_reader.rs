@@ -1,12 +1,101 @@
 // Complete async buffer reader implementation using Pin and pin_project
 // This is a production-ready example of safe self-referential structure
 
+use futures::Stream;
+#[cfg(not(feature = "pin-project-lite"))]
 use pin_project::pin_project;
 use std::io;
 use std::marker::PhantomPinned;
 use std::pin::Pin;
 use std::task::{Context, Poll};
-use tokio::io::{AsyncRead, ReadBuf};
+use tokio::io::{AsyncBufRead, AsyncBufReadExt, AsyncRead, ReadBuf};
+
+/// Reads a few lines through the `AsyncBufRead` trait on a current-thread runtime
+pub fn demonstrate_async_buf_reader() {
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .build()
+        .expect("build current-thread runtime");
+
+    runtime.block_on(async {
+        let input = std::io::Cursor::new(b"first line\nsecond line\n".to_vec());
+        let mut reader = AsyncBufReader::new(input, 8);
+
+        let mut line = String::new();
+        while reader.as_mut().read_line(&mut line).await.unwrap() > 0 {
+            println!(
+                "    read_line: {:?} (still buffered: {:?})",
+                line,
+                String::from_utf8_lossy(reader.as_ref().available())
+            );
+            line.clear();
+        }
+
+        let fields = std::io::Cursor::new(b"#csv\nalpha,beta,gamma".to_vec());
+        let mut reader = AsyncBufReader::new(fields, 4);
+        // Nothing is buffered yet, so skipping the header on the inner reader is safe
+        reader.as_mut().get_pin_mut().get_mut().set_position(5);
+        println!(
+            "    get_ref: skipped header, cursor at {}",
+            reader.get_ref().position()
+        );
+        let mut field = Vec::new();
+        while reader.as_mut().read_until(b',', &mut field).await.unwrap() > 0 {
+            println!("    read_until: {:?}", String::from_utf8_lossy(&field));
+            field.clear();
+        }
+
+        let cursor = reader.into_inner();
+        println!("    into_inner: cursor at position {}", cursor.position());
+
+        let long = std::io::Cursor::new(b"a line longer than four bytes\nnext".to_vec());
+        let mut reader = AsyncBufReader::new(long, 4);
+        let line = reader.as_mut().fill_buf_at_least(30).await.unwrap();
+        println!("    fill_buf_at_least: {:?}", String::from_utf8_lossy(line));
+        println!("    capacity grew from 4 to {}", reader.capacity());
+
+        let framed = std::io::Cursor::new(b"GET /index".to_vec());
+        let mut reader = AsyncBufReader::new(framed, 2);
+        let method = reader.as_mut().peek(3).await.unwrap();
+        println!("    peek(3): {:?} (nothing consumed)", String::from_utf8_lossy(method));
+
+        reader.as_mut().reset(std::io::Cursor::new(b"POST /form".to_vec()));
+        let method = reader.as_mut().peek(4).await.unwrap();
+        println!("    reset + peek(4): {:?} (same buffer, new stream)", String::from_utf8_lossy(method));
+
+        let log = std::io::Cursor::new(b"boot\nready\nno newline at end".to_vec());
+        let lines: Vec<io::Result<String>> =
+            futures::StreamExt::collect(lines_stream(AsyncBufReader::new(log, 8))).await;
+        let lines: Vec<String> = lines.into_iter().map(Result::unwrap).collect();
+        println!("    lines_stream: {:?}", lines);
+    });
+}
+
+/// Turn a reader into a `Stream` of lines, driven by its buffering
+///
+/// Lines are yielded without their `\n` / `\r\n` terminator, and a final
+/// line without a trailing newline is still yielded. The stream ends at EOF,
+/// or right after yielding the first I/O error.
+pub fn lines_stream<R: AsyncRead>(
+    reader: Pin<Box<AsyncBufReader<R>>>,
+) -> impl Stream<Item = io::Result<String>> {
+    futures::stream::unfold(Some(reader), |state| async move {
+        let mut reader = state?;
+        let mut line = String::new();
+        match reader.as_mut().read_line(&mut line).await {
+            Ok(0) => None,
+            Ok(_) => {
+                if line.ends_with('\n') {
+                    line.pop();
+                    if line.ends_with('\r') {
+                        line.pop();
+                    }
+                }
+                Some((Ok(line), Some(reader)))
+            }
+            Err(e) => Some((Err(e), None)),
+        }
+    })
+}
 
 /// A buffered asynchronous reader with internal self-reference
 ///
@@ -15,6 +104,7 @@ use tokio::io::{AsyncRead, ReadBuf};
 /// 1. The struct is marked !Unpin with PhantomPinned
 /// 2. It must be used through Pin<&mut Self>
 /// 3. pin_project ensures safe field access
+#[cfg(not(feature = "pin-project-lite"))]
 #[pin_project]
 pub struct AsyncBufReader<R> {
     #[pin]
@@ -35,12 +125,53 @@ pub struct AsyncBufReader<R> {
     _pin: PhantomPinned,
 }
 
+// Same struct through `pin_project_lite`, a declarative macro with no
+// proc-macro dependency. It generates the same `project()` method with the
+// same field names, so every impl below is shared by both variants.
+#[cfg(feature = "pin-project-lite")]
+pin_project_lite::pin_project! {
+    /// A buffered asynchronous reader with internal self-reference
+    ///
+    /// This struct maintains a buffer and a pointer to the filled portion.
+    /// The pointer is safe because:
+    /// 1. The struct is marked !Unpin with PhantomPinned
+    /// 2. It must be used through Pin<&mut Self>
+    /// 3. pin_project_lite ensures safe field access
+    pub struct AsyncBufReader<R> {
+        #[pin]
+        inner: R,
+
+        // The buffer is pinned to prevent reallocation
+        buffer: Box<[u8]>,
+
+        // Raw pointer to filled portion of buffer
+        // SAFETY: Valid as long as the struct is pinned
+        filled_ptr: *const u8,
+        filled_len: usize,
+
+        // Current read position
+        pos: usize,
+
+        // Mark as !Unpin to prevent moving
+        _pin: PhantomPinned,
+    }
+}
+
+/// Smallest buffer `AsyncBufReader::new` will allocate
+///
+/// A zero-length buffer would hand the inner reader an empty `ReadBuf`, and a
+/// read of 0 bytes is indistinguishable from EOF, so the stream would look
+/// empty. One byte is enough to always make progress.
+pub const MIN_CAPACITY: usize = 1;
+
 impl<R> AsyncBufReader<R> {
     /// Create a new AsyncBufReader with specified buffer size
     ///
-    /// Returns Pin<Box<Self>> to ensure the struct is immediately pinned
+    /// Returns Pin<Box<Self>> to ensure the struct is immediately pinned.
+    /// `capacity` is raised to `MIN_CAPACITY` if smaller, so `new(inner, 0)`
+    /// is valid and behaves like a one-byte buffer.
     pub fn new(inner: R, capacity: usize) -> Pin<Box<Self>> {
-        let buffer = vec![0u8; capacity].into_boxed_slice();
+        let buffer = vec![0u8; capacity.max(MIN_CAPACITY)].into_boxed_slice();
         let filled_ptr = buffer.as_ptr();
 
         let reader = Self {
@@ -73,6 +204,69 @@ impl<R> AsyncBufReader<R> {
         &filled[self.pos..]
     }
 
+    /// Unwrap the reader, returning the inner reader
+    ///
+    /// Bytes already buffered but not yet consumed are discarded: the inner
+    /// reader is positioned after everything that was filled, not after what
+    /// was read. Drain `available()` first if those bytes matter.
+    ///
+    /// SAFETY: Moving out of the pinned box is sound because:
+    /// - We consume the `Pin<Box<Self>>`, so nothing can observe the struct
+    ///   (or `filled_ptr`) afterwards; the self-reference is dropped with it
+    /// - `inner` is a structurally pinned field, so moving it requires
+    ///   `R: Unpin`, which makes the move harmless for `R`
+    /// - The type has no `Drop` impl that could expect the fields in place
+    pub fn into_inner(self: Pin<Box<Self>>) -> R
+    where
+        R: Unpin,
+    {
+        let this = unsafe { Pin::into_inner_unchecked(self) };
+        this.inner
+    }
+
+    /// Get a shared reference to the inner reader
+    ///
+    /// Takes `&self` rather than `Pin<&Self>`: a `Pin<&Self>` receiver would
+    /// clash with `Pin::get_ref`, and shared access can't move anything anyway.
+    pub fn get_ref(&self) -> &R {
+        &self.inner
+    }
+
+    /// Get a pinned mutable reference to the inner reader
+    ///
+    /// Reading from or seeking the inner reader directly bypasses the buffer:
+    /// bytes already buffered stay there and will still be returned first, so
+    /// out-of-band changes while `available()` is non-empty desync the two.
+    pub fn get_pin_mut(self: Pin<&mut Self>) -> Pin<&mut R> {
+        self.project().inner
+    }
+
+    /// Swap in a new inner reader, reusing the buffer allocation
+    ///
+    /// Buffered bytes from the old reader are dropped, so nothing stale is
+    /// returned from the new one. Useful for many short streams, where
+    /// allocating a fresh buffer per stream would dominate.
+    ///
+    /// SAFETY: This stays pin-safe because:
+    /// - `Pin::set` drops the old inner reader in place and writes the new
+    ///   one into the same pinned slot, so no pinned value is moved (and no
+    ///   `R: Unpin` bound is needed)
+    /// - `buffer` keeps its allocation; `filled_ptr` is re-pointed at it
+    ///   anyway so the invariant holds without relying on that
+    /// - `&mut` access means no slice from `filled()`/`available()` is alive
+    pub fn reset(self: Pin<&mut Self>, new_inner: R) {
+        let mut this = self.project();
+        this.inner.set(new_inner);
+        *this.filled_len = 0;
+        *this.pos = 0;
+        *this.filled_ptr = this.buffer.as_ptr();
+    }
+
+    /// Current size of the internal buffer
+    pub fn capacity(&self) -> usize {
+        self.buffer.len()
+    }
+
     /// Consume bytes from the buffer
     pub fn consume(self: Pin<&mut Self>, amt: usize) {
         let this = self.project();
@@ -99,8 +293,11 @@ impl<R: AsyncRead> AsyncBufReader<R> {
             return Poll::Ready(Ok(&filled[*this.pos..]));
         }
 
-        // Need to read more data
+        // Need to read more data. Everything buffered was consumed, so mark
+        // the buffer empty first: if the read below returns Pending, a later
+        // poll must not see pos < filled_len and replay the old bytes.
         *this.pos = 0;
+        *this.filled_len = 0;
 
         // Create ReadBuf from our buffer
         let mut read_buf = ReadBuf::new(this.buffer);
@@ -124,6 +321,124 @@ impl<R: AsyncRead> AsyncBufReader<R> {
             Poll::Pending => Poll::Pending,
         }
     }
+
+    /// Read bytes into `buf` up to and including `byte`, or until EOF
+    ///
+    /// Returns the number of bytes appended (0 at EOF). A delimiter that only
+    /// shows up after one or more refills is found because every fill is
+    /// searched and copied out before it is consumed.
+    pub async fn read_until(
+        mut self: Pin<&mut Self>,
+        byte: u8,
+        buf: &mut Vec<u8>,
+    ) -> io::Result<usize> {
+        let mut total = 0;
+        loop {
+            // Copy inside poll_fn so the borrowed slice never outlives the poll
+            let (used, done) = std::future::poll_fn(|cx| {
+                let available = std::task::ready!(self.as_mut().poll_fill_buf(cx))?;
+                let step = match available.iter().position(|&b| b == byte) {
+                    Some(i) => (i + 1, true),
+                    // An empty fill means EOF
+                    None => (available.len(), available.is_empty()),
+                };
+                buf.extend_from_slice(&available[..step.0]);
+                Poll::Ready(Ok::<_, io::Error>(step))
+            })
+            .await?;
+
+            self.as_mut().consume(used);
+            total += used;
+            if done {
+                return Ok(total);
+            }
+        }
+    }
+
+    /// Fill until at least `n` unread bytes are buffered (fewer only at EOF)
+    ///
+    /// Grows the buffer when `n` exceeds its capacity, so a record longer
+    /// than the initial capacity can still be buffered whole.
+    pub async fn fill_buf_at_least(
+        mut self: Pin<&mut Self>,
+        n: usize,
+    ) -> io::Result<&[u8]> {
+        std::future::poll_fn(|cx| self.as_mut().poll_fill_buf_at_least(cx, n)).await?;
+        Ok(self.into_ref().available())
+    }
+
+    /// Look at the next `n` bytes without consuming them
+    ///
+    /// Refills (and grows) as needed; returns fewer than `n` bytes only if
+    /// the stream ends first. `pos` is left untouched.
+    pub async fn peek(self: Pin<&mut Self>, n: usize) -> io::Result<&[u8]> {
+        let buffered = self.fill_buf_at_least(n).await?;
+        Ok(&buffered[..n.min(buffered.len())])
+    }
+
+    /// Poll version of `fill_buf_at_least`
+    ///
+    /// INVARIANT: `buffer` is only compacted or reallocated here, while we
+    /// hold `Pin<&mut Self>`, and `filled_ptr` is re-pointed at
+    /// `buffer.as_ptr()` after every write to the buffer, before any path
+    /// returns. No `&[u8]` handed out earlier can still be alive: they all
+    /// borrow `self`, which this `&mut` access rules out.
+    pub fn poll_fill_buf_at_least(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        n: usize,
+    ) -> Poll<io::Result<()>> {
+        let mut this = self.project();
+        loop {
+            let unread = *this.filled_len - *this.pos;
+            if unread >= n {
+                return Poll::Ready(Ok(()));
+            }
+
+            // Move the unread tail to the front to make room
+            if *this.pos > 0 {
+                this.buffer.copy_within(*this.pos..*this.filled_len, 0);
+                *this.pos = 0;
+                *this.filled_len = unread;
+            }
+
+            if n > this.buffer.len() {
+                let mut grown = vec![0u8; n].into_boxed_slice();
+                grown[..unread].copy_from_slice(&this.buffer[..unread]);
+                // The old allocation is freed here; filled_ptr is re-pointed below
+                *this.buffer = grown;
+            }
+            *this.filled_ptr = this.buffer.as_ptr();
+
+            let mut read_buf = ReadBuf::new(&mut this.buffer[*this.filled_len..]);
+            let polled = this.inner.as_mut().poll_read(cx, &mut read_buf);
+            let read = read_buf.filled().len();
+            *this.filled_len += read;
+            *this.filled_ptr = this.buffer.as_ptr();
+
+            match polled {
+                Poll::Ready(Ok(())) if read == 0 => return Poll::Ready(Ok(())),
+                Poll::Ready(Ok(())) => {}
+                Poll::Ready(Err(e)) => return Poll::Ready(Err(e)),
+                Poll::Pending => return Poll::Pending,
+            }
+        }
+    }
+}
+
+// Implement AsyncBufRead so the reader works with AsyncBufReadExt
+// (read_line, lines, ...). The trait methods forward to the inherent ones;
+// the `AsyncBufReader::` path picks the inherent method, so this does not
+// recurse. The returned slice borrows from `self`, which the trait's
+// signature ties to the same lifetime as the pinned reference.
+impl<R: AsyncRead> AsyncBufRead for AsyncBufReader<R> {
+    fn poll_fill_buf(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<&[u8]>> {
+        AsyncBufReader::poll_fill_buf(self, cx)
+    }
+
+    fn consume(self: Pin<&mut Self>, amt: usize) {
+        AsyncBufReader::consume(self, amt)
+    }
 }
 
 // Implement AsyncRead for our buffered reader
@@ -227,6 +542,234 @@ mod tests {
         let _ = reader.as_ref().filled();
     }
 
+    #[tokio::test]
+    async fn test_read_line_through_trait() {
+        let cursor = Cursor::new(b"alpha\nbeta\n".to_vec());
+        // Smaller than a line so read_line has to refill mid-line
+        let mut reader = AsyncBufReader::new(cursor, 4);
+
+        let mut line = String::new();
+        let n = reader.as_mut().read_line(&mut line).await.unwrap();
+        assert_eq!((n, line.as_str()), (6, "alpha\n"));
+
+        line.clear();
+        reader.as_mut().read_line(&mut line).await.unwrap();
+        assert_eq!(line, "beta\n");
+
+        line.clear();
+        assert_eq!(reader.as_mut().read_line(&mut line).await.unwrap(), 0);
+    }
+
+    async fn collect_until(data: &[u8], capacity: usize) -> Vec<Vec<u8>> {
+        let mut reader = AsyncBufReader::new(Cursor::new(data.to_vec()), capacity);
+        let mut chunks = Vec::new();
+        loop {
+            let mut chunk = Vec::new();
+            if reader.as_mut().read_until(b',', &mut chunk).await.unwrap() == 0 {
+                return chunks;
+            }
+            chunks.push(chunk);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_read_until_delimiter_in_middle() {
+        let chunks = collect_until(b"abc,defgh", 16).await;
+        assert_eq!(chunks, vec![b"abc,".to_vec(), b"defgh".to_vec()]);
+    }
+
+    #[tokio::test]
+    async fn test_read_until_without_delimiter_reads_to_eof() {
+        // Spans three fills of 4 bytes with no delimiter at all
+        let chunks = collect_until(b"0123456789", 4).await;
+        assert_eq!(chunks, vec![b"0123456789".to_vec()]);
+    }
+
+    #[tokio::test]
+    async fn test_read_until_delimiter_on_buffer_boundary() {
+        // ',' is the last byte of the first fill, then the first of the third
+        let chunks = collect_until(b"abc,defg,hi", 4).await;
+        assert_eq!(
+            chunks,
+            vec![b"abc,".to_vec(), b"defg,".to_vec(), b"hi".to_vec()]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_into_inner_recovers_reader() {
+        let cursor = Cursor::new(b"0123456789".to_vec());
+        let mut reader = AsyncBufReader::new(cursor, 4);
+
+        let mut head = [0u8; 2];
+        reader.as_mut().read_exact(&mut head).await.unwrap();
+        assert_eq!(&head, b"01");
+        assert_eq!(reader.as_ref().available(), b"23");
+
+        // "23" was buffered but unread, so it is discarded with the reader
+        let mut cursor = reader.into_inner();
+        assert_eq!(cursor.position(), 4);
+        let mut rest = Vec::new();
+        cursor.read_to_end(&mut rest).await.unwrap();
+        assert_eq!(rest, b"456789");
+    }
+
+    #[tokio::test]
+    async fn test_get_ref_reports_inner_position() {
+        let cursor = Cursor::new(b"0123456789".to_vec());
+        let mut reader = AsyncBufReader::new(cursor, 4);
+        assert_eq!(reader.get_ref().position(), 0);
+
+        let mut head = [0u8; 5];
+        reader.as_mut().read_exact(&mut head).await.unwrap();
+        // Two fills of 4 bytes were needed to hand out 5
+        assert_eq!(reader.get_ref().position(), 8);
+    }
+
+    #[tokio::test]
+    async fn test_get_pin_mut_seeks_empty_buffer() {
+        let cursor = Cursor::new(b"skip:data".to_vec());
+        let mut reader = AsyncBufReader::new(cursor, 4);
+        reader.as_mut().get_pin_mut().get_mut().set_position(5);
+
+        let mut rest = String::new();
+        reader.as_mut().read_to_string(&mut rest).await.unwrap();
+        assert_eq!(rest, "data");
+    }
+
+    #[tokio::test]
+    async fn test_fill_buf_at_least_grows_for_long_line() {
+        let data = b"this line is much longer than eight bytes\nshort\n";
+        let mut reader = AsyncBufReader::new(Cursor::new(data.to_vec()), 8);
+
+        // Consume a little first so the unread tail has to be moved as well
+        let mut head = [0u8; 5];
+        reader.as_mut().read_exact(&mut head).await.unwrap();
+
+        let line_len = 42 - 5;
+        let buffered = reader.as_mut().fill_buf_at_least(line_len).await.unwrap();
+        assert_eq!(buffered, b"line is much longer than eight bytes\n");
+        assert!(reader.capacity() >= line_len);
+
+        let mut rest = String::new();
+        reader.as_mut().read_to_string(&mut rest).await.unwrap();
+        assert_eq!(rest, "line is much longer than eight bytes\nshort\n");
+    }
+
+    #[tokio::test]
+    async fn test_fill_buf_at_least_stops_at_eof() {
+        let mut reader = AsyncBufReader::new(Cursor::new(b"tiny".to_vec()), 2);
+        let buffered = reader.as_mut().fill_buf_at_least(64).await.unwrap();
+        assert_eq!(buffered, b"tiny");
+    }
+
+    #[tokio::test]
+    async fn test_peek_within_single_fill() {
+        let mut reader = AsyncBufReader::new(Cursor::new(b"HEADERbody".to_vec()), 16);
+        assert_eq!(reader.as_mut().peek(6).await.unwrap(), b"HEADER");
+        // Peeking again is idempotent and nothing was consumed
+        assert_eq!(reader.as_mut().peek(3).await.unwrap(), b"HEA");
+
+        let mut all = String::new();
+        reader.as_mut().read_to_string(&mut all).await.unwrap();
+        assert_eq!(all, "HEADERbody");
+    }
+
+    #[tokio::test]
+    async fn test_peek_across_refill() {
+        let mut reader = AsyncBufReader::new(Cursor::new(b"0123456789".to_vec()), 4);
+        let mut head = [0u8; 3];
+        reader.as_mut().read_exact(&mut head).await.unwrap();
+
+        // Only "3" is buffered; the peek must pull in the next fill
+        assert_eq!(reader.as_mut().peek(4).await.unwrap(), b"3456");
+
+        let mut next = [0u8; 4];
+        reader.as_mut().read_exact(&mut next).await.unwrap();
+        assert_eq!(&next, b"3456");
+    }
+
+    #[tokio::test]
+    async fn test_peek_past_eof_returns_remaining() {
+        let mut reader = AsyncBufReader::new(Cursor::new(b"ab".to_vec()), 4);
+        assert_eq!(reader.as_mut().peek(10).await.unwrap(), b"ab");
+    }
+
+    #[tokio::test]
+    async fn test_lines_stream_collects_all_lines() {
+        use futures::StreamExt;
+
+        let data = b"first\nsecond\r\n\nlast without newline";
+        let reader = AsyncBufReader::new(Cursor::new(data.to_vec()), 4);
+        let lines: Vec<String> = lines_stream(reader)
+            .map(|line| line.unwrap())
+            .collect()
+            .await;
+        assert_eq!(lines, ["first", "second", "", "last without newline"]);
+
+        let reader = AsyncBufReader::new(Cursor::new(b"only\n".to_vec()), 4);
+        let lines: Vec<String> = lines_stream(reader)
+            .map(|line| line.unwrap())
+            .collect()
+            .await;
+        assert_eq!(lines, ["only"]);
+    }
+
+    #[tokio::test]
+    async fn test_zero_capacity_falls_back_to_minimum() {
+        let data = b"zero capacity still reads everything";
+        let mut reader = AsyncBufReader::new(Cursor::new(data.to_vec()), 0);
+        assert_eq!(reader.capacity(), MIN_CAPACITY);
+
+        // The first fill must make progress instead of reporting a fake EOF
+        let waker = futures::task::noop_waker();
+        let mut cx = Context::from_waker(&waker);
+        match reader.as_mut().poll_fill_buf(&mut cx) {
+            Poll::Ready(Ok(bytes)) => assert_eq!(bytes, b"z"),
+            other => panic!("expected a non-empty fill, got {:?}", other.map(|r| r.map(<[u8]>::len))),
+        }
+
+        let mut all = String::new();
+        reader.as_mut().read_to_string(&mut all).await.unwrap();
+        assert_eq!(all.as_bytes(), data);
+    }
+
+    #[tokio::test]
+    async fn test_reset_reuses_buffer_without_stale_bytes() {
+        let mut reader = AsyncBufReader::new(Cursor::new(b"first stream".to_vec()), 8);
+
+        // Leave the tail of the first stream buffered, then drain to EOF
+        let mut head = [0u8; 2];
+        reader.read_exact(&mut head).await.unwrap();
+        assert_eq!(reader.as_ref().available(), b"rst st");
+        let mut rest = String::new();
+        reader.read_to_string(&mut rest).await.unwrap();
+        assert_eq!(rest, "rst stream");
+
+        let buffer_before = reader.as_ref().filled().as_ptr();
+        reader.as_mut().reset(Cursor::new(b"second".to_vec()));
+        assert!(reader.as_ref().available().is_empty());
+        assert_eq!(reader.capacity(), 8);
+
+        let mut second = String::new();
+        reader.read_to_string(&mut second).await.unwrap();
+        assert_eq!(second, "second");
+        assert_eq!(reader.as_ref().filled().as_ptr(), buffer_before);
+    }
+
+    #[tokio::test]
+    async fn test_reset_discards_unread_bytes() {
+        let mut reader = AsyncBufReader::new(Cursor::new(b"unread bytes".to_vec()), 16);
+        std::future::poll_fn(|cx| reader.as_mut().poll_fill_buf(cx).map_ok(|_| ()))
+            .await
+            .unwrap();
+        assert!(!reader.as_ref().available().is_empty());
+
+        reader.as_mut().reset(Cursor::new(b"fresh".to_vec()));
+        let mut out = String::new();
+        reader.read_to_string(&mut out).await.unwrap();
+        assert_eq!(out, "fresh");
+    }
+
     #[tokio::test]
     async fn test_multiple_reads() {
         let data = b"Line 1\nLine 2\nLine 3\n";
@@ -239,4 +782,231 @@ mod tests {
 
         assert_eq!(buf, "Line 1\nLine 2\nLine 3\n");
     }
+
+    // Runs under both `cargo test` (pin_project) and
+    // `cargo test --features pin-project-lite`; the expected transcript is
+    // the same for both, so the two variants must read identically.
+    #[tokio::test]
+    async fn test_read_transcript_same_for_both_projections() {
+        let input = b"id,name\n1,pin\n2,lite\nno trailing newline".to_vec();
+        // Tiny buffer so every operation crosses a refill
+        let mut reader = AsyncBufReader::new(Cursor::new(input), 3);
+        let mut transcript = Vec::new();
+
+        let peeked = reader.as_mut().peek(2).await.unwrap().to_vec();
+        transcript.push(format!("peek {:?}", String::from_utf8_lossy(&peeked)));
+
+        let mut field = Vec::new();
+        reader.as_mut().read_until(b',', &mut field).await.unwrap();
+        transcript.push(format!("until {:?}", String::from_utf8_lossy(&field)));
+
+        let mut line = String::new();
+        reader.as_mut().read_line(&mut line).await.unwrap();
+        transcript.push(format!("line {:?}", line));
+
+        let mut exact = [0u8; 4];
+        reader.as_mut().read_exact(&mut exact).await.unwrap();
+        transcript.push(format!("exact {:?}", String::from_utf8_lossy(&exact)));
+
+        let ahead = reader.as_mut().fill_buf_at_least(8).await.unwrap().to_vec();
+        transcript.push(format!("at_least {:?}", String::from_utf8_lossy(&ahead)));
+
+        let mut rest = String::new();
+        reader.read_to_string(&mut rest).await.unwrap();
+        transcript.push(format!("rest {:?}", rest));
+
+        assert_eq!(
+            transcript,
+            [
+                r#"peek "id""#,
+                r#"until "id,""#,
+                r#"line "name\n""#,
+                r#"exact "1,pi""#,
+                r#"at_least "n\n2,lite""#,
+                r#"rest "n\n2,lite\nno trailing newline""#,
+            ]
+        );
+        assert_eq!(reader.capacity(), 8);
+    }
+}
+
+// Sequences aimed at the raw `filled_ptr`: refills, growth, resets and
+// interleaved `Pending`s. Plain `cargo test` checks the bookkeeping; run
+// `cargo +nightly miri test miri_invariants` to also catch dangling or
+// aliasing reads through the pointer. Sizes are kept small so Miri stays fast.
+#[cfg(test)]
+mod miri_invariants {
+    use super::*;
+    use std::collections::VecDeque;
+    use tokio::io::AsyncReadExt;
+
+    /// Reader that hands out a fixed script of chunks; `None` means
+    /// "not ready yet" and returns `Pending` once
+    struct Scripted {
+        steps: VecDeque<Option<Vec<u8>>>,
+    }
+
+    impl Scripted {
+        fn new(steps: &[Option<&[u8]>]) -> Self {
+            Scripted {
+                steps: steps.iter().map(|s| s.map(<[u8]>::to_vec)).collect(),
+            }
+        }
+    }
+
+    impl AsyncRead for Scripted {
+        fn poll_read(
+            mut self: Pin<&mut Self>,
+            cx: &mut Context<'_>,
+            buf: &mut ReadBuf<'_>,
+        ) -> Poll<io::Result<()>> {
+            match self.steps.pop_front() {
+                Some(None) => {
+                    cx.waker().wake_by_ref();
+                    Poll::Pending
+                }
+                Some(Some(mut chunk)) => {
+                    let n = chunk.len().min(buf.remaining());
+                    buf.put_slice(&chunk[..n]);
+                    if n < chunk.len() {
+                        self.steps.push_front(Some(chunk.split_off(n)));
+                    }
+                    Poll::Ready(Ok(()))
+                }
+                None => Poll::Ready(Ok(())),
+            }
+        }
+    }
+
+    fn assert_invariants<R>(reader: Pin<&AsyncBufReader<R>>) {
+        assert_eq!(reader.filled_ptr, reader.buffer.as_ptr(), "filled_ptr is stale");
+        assert!(reader.pos <= reader.filled_len);
+        assert!(reader.filled_len <= reader.buffer.len());
+        // Touch every filled byte through the raw pointer so Miri checks it
+        let sum: u32 = reader.filled().iter().map(|&b| u32::from(b)).sum();
+        std::hint::black_box(sum);
+    }
+
+    fn cx() -> Context<'static> {
+        Context::from_waker(futures::task::noop_waker_ref())
+    }
+
+    #[test]
+    fn fill_consume_cycles_keep_pointer_in_sync() {
+        let mut reader = AsyncBufReader::new(
+            Scripted::new(&[Some(b"ab"), None, Some(b"cdefg"), None, None, Some(b"h")]),
+            3,
+        );
+        let mut out = Vec::new();
+
+        loop {
+            match reader.as_mut().poll_fill_buf(&mut cx()) {
+                Poll::Ready(Ok([])) => break,
+                Poll::Ready(Ok(bytes)) => {
+                    // Consume one byte at a time to hit every pos/filled_len pair
+                    out.push(bytes[0]);
+                    reader.as_mut().consume(1);
+                }
+                Poll::Ready(Err(e)) => panic!("unexpected error: {e}"),
+                Poll::Pending => {}
+            }
+            assert_invariants(reader.as_ref());
+        }
+
+        assert_eq!(out, b"abcdefgh");
+    }
+
+    #[test]
+    fn pending_refill_does_not_replay_consumed_bytes() {
+        let mut reader =
+            AsyncBufReader::new(Scripted::new(&[Some(b"old!"), None, Some(b"new!")]), 4);
+
+        let first = reader.as_mut().poll_fill_buf(&mut cx()).map_ok(<[u8]>::to_vec);
+        assert!(matches!(first, Poll::Ready(Ok(ref b)) if b == b"old!"));
+        reader.as_mut().consume(4);
+
+        // The refill parks; the consumed bytes must not come back as unread
+        assert!(reader.as_mut().poll_fill_buf(&mut cx()).is_pending());
+        assert!(reader.as_ref().available().is_empty());
+        assert_invariants(reader.as_ref());
+
+        let second = reader.as_mut().poll_fill_buf(&mut cx()).map_ok(<[u8]>::to_vec);
+        assert!(matches!(second, Poll::Ready(Ok(ref b)) if b == b"new!"));
+        assert_invariants(reader.as_ref());
+    }
+
+    #[test]
+    fn growth_repoints_to_new_allocation() {
+        let mut reader = AsyncBufReader::new(
+            Scripted::new(&[Some(b"he"), None, Some(b"llo"), None, Some(b" world")]),
+            2,
+        );
+        let before = reader.as_ref().filled().as_ptr();
+
+        let mut polled = 0;
+        while reader.as_mut().poll_fill_buf_at_least(&mut cx(), 8).is_pending() {
+            assert_invariants(reader.as_ref());
+            polled += 1;
+        }
+        assert_eq!(polled, 2);
+        assert_invariants(reader.as_ref());
+
+        // The old buffer was freed while both were alive, so they differ
+        assert_ne!(reader.as_ref().filled().as_ptr(), before);
+        assert_eq!(reader.as_ref().available(), b"hello wo");
+    }
+
+    #[test]
+    fn compaction_after_partial_consume() {
+        let mut reader = AsyncBufReader::new(Scripted::new(&[Some(b"0123456789")]), 6);
+
+        let _ = reader.as_mut().poll_fill_buf(&mut cx());
+        reader.as_mut().consume(4);
+        assert_invariants(reader.as_ref());
+
+        // "45" is moved to the front and the rest refilled in place
+        let _ = reader.as_mut().poll_fill_buf_at_least(&mut cx(), 5);
+        assert_invariants(reader.as_ref());
+        assert_eq!(reader.as_ref().available(), b"456789");
+        assert_eq!(reader.capacity(), 6);
+    }
+
+    #[tokio::test]
+    async fn peek_then_read_across_refills() {
+        let mut reader = AsyncBufReader::new(
+            Scripted::new(&[Some(b"GE"), None, Some(b"T /"), None, Some(b"path")]),
+            2,
+        );
+
+        assert_eq!(reader.as_mut().peek(3).await.unwrap(), b"GET");
+        assert_invariants(reader.as_ref());
+
+        let mut all = String::new();
+        reader.read_to_string(&mut all).await.unwrap();
+        assert_eq!(all, "GET /path");
+        assert_invariants(reader.as_ref());
+    }
+
+    #[tokio::test]
+    async fn reset_after_growth_and_pending() {
+        let mut reader =
+            AsyncBufReader::new(Scripted::new(&[Some(b"abc"), None, Some(b"defgh")]), 2);
+        reader.as_mut().fill_buf_at_least(6).await.unwrap();
+        let grown = reader.capacity();
+        assert_invariants(reader.as_ref());
+
+        // Drain the first reader to EOF, then swap in one that starts Pending
+        reader.as_mut().consume(6);
+        assert!(reader.as_mut().poll_fill_buf(&mut cx()).is_ready());
+        reader.as_mut().reset(Scripted::new(&[None, Some(b"xy")]));
+        assert_invariants(reader.as_ref());
+        assert!(reader.as_mut().poll_fill_buf(&mut cx()).is_pending());
+        assert!(reader.as_ref().available().is_empty());
+
+        let mut rest = Vec::new();
+        reader.read_to_end(&mut rest).await.unwrap();
+        assert_eq!(rest, b"xy");
+        assert_eq!(reader.capacity(), grown);
+        assert_invariants(reader.as_ref());
+    }
 }
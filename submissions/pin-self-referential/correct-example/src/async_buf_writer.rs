@@ -0,0 +1,312 @@
+// Async buffered writer, the write-side counterpart of AsyncBufReader
+// Same Pin pattern: a pinned Box<[u8]> with a raw pointer into it
+
+use pin_project::pin_project;
+use std::io;
+use std::marker::PhantomPinned;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use tokio::io::{AsyncWrite, AsyncWriteExt};
+
+/// Writes many small chunks and shows they reach the inner writer coalesced
+pub fn demonstrate_async_buf_writer() {
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .build()
+        .expect("build current-thread runtime");
+
+    runtime.block_on(async {
+        let mut writer = AsyncBufWriter::new(Vec::new(), 16);
+
+        for word in ["pin", "ned ", "buf", "fer"] {
+            writer.write_all(word.as_bytes()).await.unwrap();
+            println!(
+                "    write {:?}: buffered {:?}, inner has {} bytes",
+                word,
+                String::from_utf8_lossy(writer.as_ref().buffered()),
+                writer.get_ref().len()
+            );
+        }
+
+        writer.flush().await.unwrap();
+        println!(
+            "    flush: buffered {} bytes, inner has {:?}",
+            writer.as_ref().buffered().len(),
+            String::from_utf8_lossy(writer.get_ref())
+        );
+
+        writer.shutdown().await.unwrap();
+        println!("    shutdown: {} bytes written in total", writer.get_ref().len());
+    });
+}
+
+/// A buffered asynchronous writer with internal self-reference
+///
+/// Writes are copied into a fixed buffer and handed to the inner writer in
+/// one go when the buffer fills up, on `flush`, or on `shutdown`.
+/// The pointer is safe for the same reasons as in `AsyncBufReader`:
+/// 1. The struct is marked !Unpin with PhantomPinned
+/// 2. It must be used through Pin<&mut Self>
+/// 3. pin_project ensures safe field access
+#[pin_project]
+pub struct AsyncBufWriter<W> {
+    #[pin]
+    inner: W,
+
+    // The buffer is pinned to prevent reallocation
+    buffer: Box<[u8]>,
+
+    // Raw pointer to the start of the buffer
+    // SAFETY: Valid as long as the struct is pinned
+    filled_ptr: *const u8,
+    filled_len: usize,
+
+    // Bytes at the front of the buffer already accepted by the inner writer
+    written: usize,
+
+    // Mark as !Unpin to prevent moving
+    _pin: PhantomPinned,
+}
+
+impl<W> AsyncBufWriter<W> {
+    /// Create a new AsyncBufWriter with specified buffer size
+    ///
+    /// Returns Pin<Box<Self>> to ensure the struct is immediately pinned.
+    /// `capacity` is raised to `MIN_CAPACITY` like the reader's.
+    pub fn new(inner: W, capacity: usize) -> Pin<Box<Self>> {
+        let capacity = capacity.max(crate::async_buf_reader::MIN_CAPACITY);
+        let buffer = vec![0u8; capacity].into_boxed_slice();
+        let filled_ptr = buffer.as_ptr();
+
+        let writer = Self {
+            inner,
+            buffer,
+            filled_ptr,
+            filled_len: 0,
+            written: 0,
+            _pin: PhantomPinned,
+        };
+
+        Box::pin(writer)
+    }
+
+    /// Bytes accepted by `poll_write` but not yet handed to the inner writer
+    ///
+    /// SAFETY: filled_ptr points into buffer, which is never reallocated and
+    /// cannot move while the struct is pinned.
+    pub fn buffered(self: Pin<&Self>) -> &[u8] {
+        let filled = unsafe {
+            std::slice::from_raw_parts(self.filled_ptr, self.filled_len)
+        };
+        &filled[self.written..]
+    }
+
+    /// Get a shared reference to the inner writer
+    pub fn get_ref(&self) -> &W {
+        &self.inner
+    }
+}
+
+impl<W: AsyncWrite> AsyncBufWriter<W> {
+    /// Hand every buffered byte to the inner writer
+    ///
+    /// `written` advances after each partial write, so a `Pending` or an
+    /// error in the middle resumes where it left off instead of rewriting.
+    pub fn poll_flush_buf(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        let mut this = self.project();
+
+        while *this.written < *this.filled_len {
+            let pending = unsafe {
+                std::slice::from_raw_parts(*this.filled_ptr, *this.filled_len)
+            };
+            match this.inner.as_mut().poll_write(cx, &pending[*this.written..]) {
+                Poll::Ready(Ok(0)) => {
+                    return Poll::Ready(Err(io::Error::new(
+                        io::ErrorKind::WriteZero,
+                        "failed to write the buffered data",
+                    )));
+                }
+                Poll::Ready(Ok(n)) => *this.written += n,
+                Poll::Ready(Err(e)) => return Poll::Ready(Err(e)),
+                Poll::Pending => return Poll::Pending,
+            }
+        }
+
+        *this.written = 0;
+        *this.filled_len = 0;
+        Poll::Ready(Ok(()))
+    }
+}
+
+impl<W: AsyncWrite> AsyncWrite for AsyncBufWriter<W> {
+    fn poll_write(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        if self.filled_len + buf.len() > self.buffer.len() {
+            std::task::ready!(self.as_mut().poll_flush_buf(cx))?;
+        }
+
+        let this = self.project();
+
+        // Too big to buffer even when empty: skip the extra copy
+        if buf.len() >= this.buffer.len() {
+            return this.inner.poll_write(cx, buf);
+        }
+
+        this.buffer[*this.filled_len..*this.filled_len + buf.len()].copy_from_slice(buf);
+        *this.filled_len += buf.len();
+        // Same allocation as before; re-pointed to keep the invariant explicit
+        *this.filled_ptr = this.buffer.as_ptr();
+        Poll::Ready(Ok(buf.len()))
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        std::task::ready!(self.as_mut().poll_flush_buf(cx))?;
+        self.project().inner.poll_flush(cx)
+    }
+
+    fn poll_shutdown(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        std::task::ready!(self.as_mut().poll_flush_buf(cx))?;
+        self.project().inner.poll_shutdown(cx)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Inner writer that records every `poll_write` call it receives
+    #[derive(Default)]
+    struct RecordingWriter {
+        writes: Vec<Vec<u8>>,
+        flushes: usize,
+        shut_down: bool,
+    }
+
+    impl RecordingWriter {
+        fn data(&self) -> Vec<u8> {
+            self.writes.concat()
+        }
+    }
+
+    impl AsyncWrite for RecordingWriter {
+        fn poll_write(
+            mut self: Pin<&mut Self>,
+            _cx: &mut Context<'_>,
+            buf: &[u8],
+        ) -> Poll<io::Result<usize>> {
+            self.writes.push(buf.to_vec());
+            Poll::Ready(Ok(buf.len()))
+        }
+
+        fn poll_flush(mut self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+            self.flushes += 1;
+            Poll::Ready(Ok(()))
+        }
+
+        fn poll_shutdown(mut self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+            self.shut_down = true;
+            Poll::Ready(Ok(()))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_small_writes_coalesce() {
+        let mut writer = AsyncBufWriter::new(RecordingWriter::default(), 8);
+
+        for _ in 0..10 {
+            writer.write_all(b"ab").await.unwrap();
+        }
+        writer.flush().await.unwrap();
+
+        let inner = writer.get_ref();
+        assert_eq!(inner.data(), b"ab".repeat(10));
+        // 20 bytes through an 8-byte buffer: 8 + 8 + 4, not 10 separate writes
+        assert_eq!(inner.writes.len(), 3);
+        assert_eq!(inner.writes[0], b"abababab");
+    }
+
+    #[tokio::test]
+    async fn test_writes_stay_buffered_until_full() {
+        let mut writer = AsyncBufWriter::new(RecordingWriter::default(), 8);
+
+        writer.write_all(b"1234").await.unwrap();
+        writer.write_all(b"5678").await.unwrap();
+        assert!(writer.get_ref().writes.is_empty());
+        assert_eq!(writer.as_ref().buffered(), b"12345678");
+
+        // Does not fit: the full buffer goes out first
+        writer.write_all(b"9").await.unwrap();
+        assert_eq!(writer.get_ref().writes, vec![b"12345678".to_vec()]);
+        assert_eq!(writer.as_ref().buffered(), b"9");
+    }
+
+    #[tokio::test]
+    async fn test_flush_drains_buffer() {
+        let mut writer = AsyncBufWriter::new(RecordingWriter::default(), 64);
+
+        writer.write_all(b"hello").await.unwrap();
+        writer.flush().await.unwrap();
+
+        assert!(writer.as_ref().buffered().is_empty());
+        assert_eq!(writer.get_ref().data(), b"hello");
+        assert_eq!(writer.get_ref().flushes, 1);
+    }
+
+    #[tokio::test]
+    async fn test_shutdown_drains_buffer() {
+        let mut writer = AsyncBufWriter::new(RecordingWriter::default(), 64);
+
+        writer.write_all(b"goodbye").await.unwrap();
+        writer.shutdown().await.unwrap();
+
+        assert!(writer.as_ref().buffered().is_empty());
+        assert_eq!(writer.get_ref().data(), b"goodbye");
+        assert!(writer.get_ref().shut_down);
+    }
+
+    #[tokio::test]
+    async fn test_large_write_bypasses_buffer() {
+        let mut writer = AsyncBufWriter::new(RecordingWriter::default(), 4);
+
+        writer.write_all(b"ab").await.unwrap();
+        writer.write_all(b"a long payload").await.unwrap();
+
+        // Buffered bytes are flushed first so ordering is preserved
+        assert_eq!(
+            writer.get_ref().writes,
+            vec![b"ab".to_vec(), b"a long payload".to_vec()]
+        );
+        assert!(writer.as_ref().buffered().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_zero_length_inner_write_is_an_error() {
+        struct Full;
+
+        impl AsyncWrite for Full {
+            fn poll_write(
+                self: Pin<&mut Self>,
+                _cx: &mut Context<'_>,
+                _buf: &[u8],
+            ) -> Poll<io::Result<usize>> {
+                Poll::Ready(Ok(0))
+            }
+
+            fn poll_flush(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+                Poll::Ready(Ok(()))
+            }
+
+            fn poll_shutdown(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+                Poll::Ready(Ok(()))
+            }
+        }
+
+        let mut writer = AsyncBufWriter::new(Full, 8);
+        writer.write_all(b"data").await.unwrap();
+
+        let err = writer.flush().await.unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::WriteZero);
+    }
+}
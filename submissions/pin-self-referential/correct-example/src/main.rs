@@ -3,6 +3,7 @@
 
 mod pin_basics;
 mod async_buf_reader;
+mod async_buf_writer;
 mod alternative_designs;
 
 fn main() {
@@ -20,7 +21,12 @@ fn main() {
 
     // Example 3: Async buffer reader (requires tokio runtime)
     println!("Example 3: Async Buffer Reader with Pin");
-    println!("  (See async_buf_reader.rs and run tests with `cargo test`)");
+    async_buf_reader::demonstrate_async_buf_reader();
+    println!();
+
+    // Example 4: Async buffer writer (same Pin pattern on the write side)
+    println!("Example 4: Async Buffer Writer with Pin");
+    async_buf_writer::demonstrate_async_buf_writer();
     println!();
 
     println!("=== All examples completed successfully ===");
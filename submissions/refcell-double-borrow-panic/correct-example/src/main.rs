@@ -1,10 +1,17 @@
 /// RefCell Safe Usage - Simple Solutions
 ///
-/// This demonstrates three simple solutions to avoid RefCell panics:
+/// This demonstrates simple solutions to avoid RefCell panics:
 /// 1. Clone and Release - Copy data before processing
 /// 2. Do Everything in One Borrow - Avoid function calls
 /// 3. Use Cell for Simple Types - No borrowing needed
-use std::cell::{Cell, RefCell};
+/// 4. try_borrow - Turn a conflict into a recoverable error
+/// 5. Deferred Mutations - Queue writes, apply them after the borrow ends
+///
+/// Plus a debugging aid, LabeledRefCell, that names who holds a borrow.
+use std::cell::{Cell, Ref, RefCell, RefMut};
+use std::fmt;
+use std::ops::{Deref, DerefMut};
+use std::sync::atomic::{AtomicI32, Ordering};
 
 // ============================================================================
 // Solution 1: Clone and Release (Simplest!)
@@ -14,11 +21,11 @@ thread_local! {
     static CACHE: RefCell<Vec<String>> = const { RefCell::new(Vec::new()) };
 }
 
-fn add_to_cache(item: String) {
-    CACHE.with(|cache| {
-        cache.borrow_mut().push(item.clone());
-        println!("Added: {}", item);
-    });
+/// Add an item to the cache, failing instead of panicking if it's borrowed
+fn add_to_cache(item: String) -> Result<(), BorrowError> {
+    SafeCache.push(item.clone())?;
+    println!("Added: {}", item);
+    Ok(())
 }
 
 /// Process items - CORRECT VERSION
@@ -36,7 +43,7 @@ fn process_items_correct() {
         println!("Processing: {}", item);
 
         if item.contains("special") {
-            add_to_cache(format!("derived-{}", item));  // ✅ Safe!
+            add_to_cache(format!("derived-{}", item)).expect("borrow released above");  // ✅ Safe!
         }
     }
 }
@@ -73,25 +80,103 @@ fn update_and_log_correct() {
 // Solution 3: Use Cell for Simple Types (Best for Scalars!)
 // ============================================================================
 
+/// Integer types a SimpleCounter can count with
+trait CounterValue: Copy + PartialOrd + fmt::Display + std::ops::Add<Output = Self> {
+    const ONE: Self;
+    /// Above this, update_and_log says the counter is getting big
+    const BIG: Self;
+
+    fn checked_add(self, rhs: Self) -> Option<Self>;
+    fn saturating_add(self, rhs: Self) -> Self;
+}
+
+macro_rules! impl_counter_value {
+    ($($t:ty),*) => {$(
+        impl CounterValue for $t {
+            const ONE: Self = 1;
+            const BIG: Self = 5;
+
+            fn checked_add(self, rhs: Self) -> Option<Self> {
+                <$t>::checked_add(self, rhs)
+            }
+
+            fn saturating_add(self, rhs: Self) -> Self {
+                <$t>::saturating_add(self, rhs)
+            }
+        }
+    )*};
+}
+
+impl_counter_value!(i8, i16, i32, i64, u8, u16, u32, u64, usize);
+
+/// Called with the new value after every change to a SimpleCounter
+type Observer<T> = Box<dyn Fn(T)>;
+
 /// Counter using Cell - No borrowing needed!
-struct SimpleCounter {
-    value: Cell<i32>,
+///
+/// Observers may call `increment`/`get` on the same counter while being
+/// notified: the value lives in a Cell, and the observer list is only ever
+/// borrowed shared during notification, so nested notifications just nest.
+/// (Calling `observe` from inside an observer would still panic.)
+struct SimpleCounter<T = i32> {
+    value: Cell<T>,
+    observers: RefCell<Vec<Observer<T>>>,
 }
 
 impl SimpleCounter {
     fn new() -> Self {
+        Self::starting_at(0)
+    }
+}
+
+impl<T: CounterValue> SimpleCounter<T> {
+    /// Counter of any CounterValue type, starting at `value`
+    fn starting_at(value: T) -> Self {
         Self {
-            value: Cell::new(0),
+            value: Cell::new(value),
+            observers: RefCell::new(Vec::new()),
+        }
+    }
+
+    /// Register `observer` to be called after every change
+    fn observe(&self, observer: impl Fn(T) + 'static) {
+        self.observers.borrow_mut().push(Box::new(observer));
+    }
+
+    /// Store `value` and tell every observer about it
+    fn set(&self, value: T) {
+        self.value.set(value);
+        for observer in self.observers.borrow().iter() {
+            observer(value);
         }
     }
 
     /// Increment - no borrow needed!
+    ///
+    /// Panics on overflow in debug builds (and wraps in release), like `+`.
+    /// Use checked_increment or saturating_increment near the type's limit.
     fn increment(&self) {
-        self.value.set(self.value.get() + 1);
+        self.set(self.value.get() + T::ONE);
+    }
+
+    /// Increment unless it would overflow; returns whether it did
+    fn checked_increment(&self) -> bool {
+        match self.value.get().checked_add(T::ONE) {
+            Some(next) => {
+                self.set(next);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Increment, staying at the type's maximum instead of overflowing
+    fn saturating_increment(&self) {
+        self.set(self.value.get().saturating_add(T::ONE));
     }
 
     /// Get value - no borrow needed!
-    fn get(&self) -> i32 {
+    fn get(&self) -> T {
         self.value.get()
     }
 
@@ -102,12 +187,430 @@ impl SimpleCounter {
         let value = self.get();  // ✅ No panic!
         println!("Counter: {}", value);
 
+        if value > T::BIG {
+            println!("Counter is getting big!");
+        }
+    }
+}
+
+/// Thread-safe counterpart of SimpleCounter - still no borrowing!
+///
+/// Same surface, but the value is an AtomicI32, so `&AtomicCounter` can be
+/// shared across threads. Relaxed ordering is enough: the counter doesn't
+/// publish any other data, each increment just has to land exactly once.
+#[derive(Debug, Default)]
+struct AtomicCounter {
+    value: AtomicI32,
+}
+
+impl AtomicCounter {
+    fn new() -> Self {
+        Self::default()
+    }
+
+    /// Increment - no lock needed!
+    fn increment(&self) {
+        self.value.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Get value - no lock needed!
+    fn get(&self) -> i32 {
+        self.value.load(Ordering::Relaxed)
+    }
+
+    /// Update and log - works from any thread
+    fn update_and_log(&self) {
+        // Use the value fetch_add returns: a separate get() could already
+        // include other threads' increments
+        let value = self.value.fetch_add(1, Ordering::Relaxed) + 1;
+        println!("Counter: {}", value);
+
         if value > 5 {
             println!("Counter is getting big!");
         }
     }
 }
 
+// ============================================================================
+// Solution 4: try_borrow Instead of Panicking
+// ============================================================================
+
+/// A borrow conflict reported as a value instead of a panic
+#[derive(Debug)]
+enum BorrowError {
+    /// Wanted to read, but someone is writing
+    Shared(std::cell::BorrowError),
+    /// Wanted to write, but someone is reading or writing
+    Exclusive(std::cell::BorrowMutError),
+}
+
+impl fmt::Display for BorrowError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BorrowError::Shared(e) => write!(f, "cache is being written: {}", e),
+            BorrowError::Exclusive(e) => write!(f, "cache is in use: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for BorrowError {}
+
+impl From<std::cell::BorrowError> for BorrowError {
+    fn from(e: std::cell::BorrowError) -> Self {
+        BorrowError::Shared(e)
+    }
+}
+
+impl From<std::cell::BorrowMutError> for BorrowError {
+    fn from(e: std::cell::BorrowMutError) -> Self {
+        BorrowError::Exclusive(e)
+    }
+}
+
+/// Handle to CACHE whose methods use try_borrow/try_borrow_mut
+///
+/// A reentrant call gets an `Err` it can recover from, instead of unwinding.
+#[derive(Clone, Copy, Debug, Default)]
+struct SafeCache;
+
+impl SafeCache {
+    /// Append an item
+    fn push(&self, item: String) -> Result<(), BorrowError> {
+        CACHE.with(|cache| {
+            cache.try_borrow_mut()?.push(item);
+            Ok(())
+        })
+    }
+
+    /// Run `f` over the items while holding a shared borrow
+    fn with_items<R>(&self, f: impl FnOnce(&[String]) -> R) -> Result<R, BorrowError> {
+        CACHE.with(|cache| Ok(f(&cache.try_borrow()?)))
+    }
+
+    /// Copy of the items, with the borrow already released
+    fn snapshot(&self) -> Result<Vec<String>, BorrowError> {
+        self.with_items(|items| items.to_vec())
+    }
+
+    /// Remove every item
+    fn clear(&self) -> Result<(), BorrowError> {
+        CACHE.with(|cache| {
+            cache.try_borrow_mut()?.clear();
+            Ok(())
+        })
+    }
+}
+
+/// What a try_process_items run did
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+struct ProcessStats {
+    /// Items read from the cache
+    processed: usize,
+    /// "derived-" items added to the cache
+    derived: usize,
+}
+
+/// Process items - RECOVERABLE VERSION
+///
+/// SOLUTION: Collect the derived items under a try_borrow, release it, then
+/// write them. A conflicting borrow comes back as `Err` instead of a panic.
+fn try_process_items() -> Result<ProcessStats, BorrowError> {
+    println!("\n[TRY] Processing items...");
+
+    let mut stats = ProcessStats::default();
+    let derived = SafeCache.with_items(|items| {
+        stats.processed = items.len();
+        items
+            .iter()
+            .inspect(|item| println!("Processing: {}", item))
+            .filter(|item| item.contains("special"))
+            .map(|item| format!("derived-{}", item))
+            .collect::<Vec<_>>()
+    })?;
+
+    // The read borrow is released, so the writes succeed
+    for item in derived {
+        add_to_cache(item)?;  // ✅ No panic!
+        stats.derived += 1;
+    }
+    Ok(stats)
+}
+
+// ============================================================================
+// Solution 5: Defer Mutations Until the Borrow Ends
+// ============================================================================
+
+/// Cache that queues writes made while it's being iterated
+///
+/// Writes requested inside `iter_with` go to a separate `pending` buffer,
+/// so they never touch the `items` borrow. They're applied once the
+/// outermost iteration releases it - no clone of the items needed.
+#[derive(Debug, Default)]
+struct DeferredCache {
+    items: RefCell<Vec<String>>,
+    pending: RefCell<Vec<String>>,
+    /// How many `iter_with` calls are currently running
+    depth: Cell<usize>,
+}
+
+impl DeferredCache {
+    fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add an item right away (don't call this while iterating)
+    fn add(&self, item: String) {
+        self.items.borrow_mut().push(item);
+    }
+
+    /// Queue an item to be added after the current iteration
+    fn defer_add(&self, item: String) {
+        self.pending.borrow_mut().push(item);
+        if self.depth.get() == 0 {
+            self.flush();
+        }
+    }
+
+    /// Call `f` on every item; `f` may call `defer_add` on this cache
+    fn iter_with(&self, mut f: impl FnMut(&str)) {
+        self.depth.set(self.depth.get() + 1);
+        for item in self.items.borrow().iter() {
+            f(item);
+        }
+        self.depth.set(self.depth.get() - 1);
+
+        // Only the outermost iteration holds the last borrow
+        if self.depth.get() == 0 {
+            self.flush();
+        }
+    }
+
+    /// Apply queued items
+    fn flush(&self) {
+        let pending = std::mem::take(&mut *self.pending.borrow_mut());
+        self.items.borrow_mut().extend(pending);
+    }
+
+    /// Copy of the current items
+    fn items(&self) -> Vec<String> {
+        self.items.borrow().clone()
+    }
+}
+
+/// A queued write for a DeferredCell
+type Command<T> = Box<dyn FnOnce(&mut T)>;
+
+/// Generic form of DeferredCache: reads are immediate, writes are commands
+///
+/// `defer` only pushes onto the command queue, so it's safe to call from
+/// inside `read` (even nested reads). Nothing changes until `flush`.
+/// Single-threaded by design: two RefCells, no Mutex.
+struct DeferredCell<T> {
+    value: RefCell<T>,
+    commands: RefCell<Vec<Command<T>>>,
+}
+
+impl<T> DeferredCell<T> {
+    fn new(value: T) -> Self {
+        Self {
+            value: RefCell::new(value),
+            commands: RefCell::new(Vec::new()),
+        }
+    }
+
+    /// Borrow the value for the duration of `f`
+    fn read<R>(&self, f: impl FnOnce(&T) -> R) -> R {
+        f(&self.value.borrow())
+    }
+
+    /// Queue a mutation for the next `flush`
+    fn defer(&self, command: impl FnOnce(&mut T) + 'static) {
+        self.commands.borrow_mut().push(Box::new(command));
+    }
+
+    /// Number of queued mutations
+    fn pending(&self) -> usize {
+        self.commands.borrow().len()
+    }
+
+    /// Apply queued mutations in order, returning how many ran
+    ///
+    /// Commands queued by other commands run in the same flush. If a `read`
+    /// is still active, nothing is applied and the queue is kept.
+    fn flush(&self) -> usize {
+        let Ok(mut value) = self.value.try_borrow_mut() else {
+            return 0;
+        };
+
+        let mut applied = 0;
+        loop {
+            let commands = std::mem::take(&mut *self.commands.borrow_mut());
+            if commands.is_empty() {
+                return applied;
+            }
+            applied += commands.len();
+            for command in commands {
+                command(&mut value);
+            }
+        }
+    }
+}
+
+/// Process items - DEFERRED VERSION
+///
+/// SOLUTION: Same loop as process_items_broken, but the write is queued
+fn process_items_deferred(cache: &DeferredCache) {
+    println!("\n[DEFERRED] Processing items...");
+
+    cache.iter_with(|item| {
+        println!("Processing: {}", item);
+
+        if item.contains("special") {
+            cache.defer_add(format!("derived-{}", item));  // ✅ Queued, no panic!
+        }
+    });
+}
+
+// ============================================================================
+// Debugging Aid: Name the Borrow Holder
+// ============================================================================
+
+/// RefCell that remembers a label for each outstanding borrow
+///
+/// A plain RefCell panic says "already borrowed" but not by whom. Here a
+/// conflicting borrow panics with the label of the holder instead. Labels
+/// are only recorded in debug builds; release builds keep RefCell's cost.
+#[derive(Debug, Default)]
+struct LabeledRefCell<T> {
+    inner: RefCell<T>,
+    holders: BorrowLabels,
+}
+
+impl<T> LabeledRefCell<T> {
+    fn new(value: T) -> Self {
+        Self {
+            inner: RefCell::new(value),
+            holders: BorrowLabels::default(),
+        }
+    }
+
+    /// Shared borrow, recorded under `label`
+    fn borrow_labeled(&self, label: &'static str) -> LabeledRef<'_, T> {
+        let guard = self.inner.try_borrow().unwrap_or_else(|_| self.conflict(label));
+        LabeledRef {
+            guard,
+            holders: &self.holders,
+            entry: self.holders.push(label),
+        }
+    }
+
+    /// Mutable borrow, recorded under `label`
+    fn borrow_mut_labeled(&self, label: &'static str) -> LabeledRefMut<'_, T> {
+        let guard = self
+            .inner
+            .try_borrow_mut()
+            .unwrap_or_else(|_| self.conflict(label));
+        LabeledRefMut {
+            guard,
+            holders: &self.holders,
+            entry: self.holders.push(label),
+        }
+    }
+
+    /// Label of the most recent borrow still alive, if known
+    fn holder(&self) -> Option<&'static str> {
+        self.holders.latest()
+    }
+
+    fn conflict(&self, label: &'static str) -> ! {
+        panic!(
+            "`{}` can't borrow: already borrowed by `{}`",
+            label,
+            self.holder().unwrap_or("<unknown>")
+        )
+    }
+}
+
+/// Labels of the live borrows, oldest first
+///
+/// Shared borrows can be dropped in any order, so each guard removes its
+/// own entry (found by id) rather than restoring whatever came before it.
+#[derive(Debug, Default)]
+struct BorrowLabels {
+    live: RefCell<Vec<(u64, &'static str)>>,
+    next_id: Cell<u64>,
+}
+
+impl BorrowLabels {
+    fn push(&self, label: &'static str) -> Option<u64> {
+        if !cfg!(debug_assertions) {
+            return None;
+        }
+        let id = self.next_id.get();
+        self.next_id.set(id + 1);
+        self.live.borrow_mut().push((id, label));
+        Some(id)
+    }
+
+    fn remove(&self, entry: Option<u64>) {
+        if let Some(id) = entry {
+            self.live.borrow_mut().retain(|&(live_id, _)| live_id != id);
+        }
+    }
+
+    fn latest(&self) -> Option<&'static str> {
+        self.live.borrow().last().map(|&(_, label)| label)
+    }
+}
+
+/// Shared borrow from a LabeledRefCell; removes its label on drop
+struct LabeledRef<'a, T> {
+    guard: Ref<'a, T>,
+    holders: &'a BorrowLabels,
+    entry: Option<u64>,
+}
+
+impl<T> Deref for LabeledRef<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.guard
+    }
+}
+
+impl<T> Drop for LabeledRef<'_, T> {
+    fn drop(&mut self) {
+        self.holders.remove(self.entry);
+    }
+}
+
+/// Mutable borrow from a LabeledRefCell; removes its label on drop
+struct LabeledRefMut<'a, T> {
+    guard: RefMut<'a, T>,
+    holders: &'a BorrowLabels,
+    entry: Option<u64>,
+}
+
+impl<T> Deref for LabeledRefMut<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.guard
+    }
+}
+
+impl<T> DerefMut for LabeledRefMut<'_, T> {
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.guard
+    }
+}
+
+impl<T> Drop for LabeledRefMut<'_, T> {
+    fn drop(&mut self) {
+        self.holders.remove(self.entry);
+    }
+}
+
 // ============================================================================
 // Main: Demonstration
 // ============================================================================
@@ -119,9 +622,9 @@ fn main() {
     println!("--- Solution 1: Clone and Release ---");
     CACHE.with(|c| c.borrow_mut().clear());
 
-    add_to_cache("apple".to_string());
-    add_to_cache("special-banana".to_string());
-    add_to_cache("cherry".to_string());
+    for item in ["apple", "special-banana", "cherry"] {
+        add_to_cache(item.to_string()).expect("nothing else borrows the cache");
+    }
 
     process_items_correct();
 
@@ -158,11 +661,99 @@ fn main() {
     println!("  - Zero overhead, no panic possible");
     println!("\n  Limitation: Only works with Copy types (i32, bool, etc)");
 
+    // Near the limit of a small type, pick the overflow behaviour explicitly
+    let small = SimpleCounter::starting_at(i8::MAX - 1);
+    println!("\ni8 counter at {}", small.get());
+    println!("checked_increment: {} (now {})", small.checked_increment(), small.get());
+    println!("checked_increment: {} (still {})", small.checked_increment(), small.get());
+    small.saturating_increment();
+    println!("saturating_increment: stays at {}", small.get());
+
+    // Observers can touch the counter they're observing
+    let counter = std::rc::Rc::new(SimpleCounter::new());
+    let weak = std::rc::Rc::downgrade(&counter);
+    counter.observe(move |value| {
+        if value % 5 != 0 {
+            if let Some(counter) = weak.upgrade() {
+                counter.increment();  // ✅ Reentrant, no panic!
+            }
+        }
+    });
+    counter.increment();
+    println!("\nObserver rounded one increment up to {}", counter.get());
+
+    // Same idea across threads: AtomicCounter instead of Cell
+    let shared = AtomicCounter::new();
+    std::thread::scope(|s| {
+        for _ in 0..4 {
+            s.spawn(|| shared.increment());
+        }
+    });
+    println!("\nAtomicCounter after 4 threads: {}", shared.get());
+    shared.update_and_log();
+
+    // Solution 4: try_borrow
+    println!("\n--- Solution 4: try_borrow Instead of Panicking ---");
+    SafeCache.clear().expect("nothing else borrows the cache");
+    add_to_cache("special-kiwi".to_string()).expect("nothing else borrows the cache");
+
+    match try_process_items() {
+        Ok(stats) => println!(
+            "Processed {} item(s), added {} derived",
+            stats.processed, stats.derived
+        ),
+        Err(e) => println!("Gave up: {}", e),
+    }
+    println!("Cache now: {:?}", SafeCache.snapshot());
+
+    println!("\nWhy it works:");
+    println!("  - try_borrow_mut() returns Err instead of panicking");
+    println!("  - The caller decides how to recover (retry, skip, report)");
+
+    // Solution 5: Deferred mutations
+    println!("\n--- Solution 5: Defer Mutations Until the Borrow Ends ---");
+    let deferred = DeferredCache::new();
+    for item in ["apple", "special-banana", "cherry"] {
+        deferred.add(item.to_string());
+    }
+
+    process_items_deferred(&deferred);
+    println!("Cache now: {:?}", deferred.items());
+
+    // The generic version works for any T, with an explicit flush
+    let counter = DeferredCell::new(0);
+    counter.read(|value| {
+        println!("Reading {} and queueing an increment", value);
+        counter.defer(|value| *value += 1);
+    });
+    println!("Queued: {}, value still {}", counter.pending(), counter.read(|value| *value));
+    counter.flush();
+    println!("After flush: {}", counter.read(|value| *value));
+
+    println!("\nWhy it works:");
+    println!("  - Writes during iteration go to a separate queue");
+    println!("  - The queue is applied after the iteration borrow ends");
+    println!("  - No clone of the whole collection needed");
+
+    // Debugging aid
+    println!("\n--- Debugging Aid: Name the Borrow Holder ---");
+    let labeled = LabeledRefCell::new(vec!["apple".to_string()]);
+    {
+        let items = labeled.borrow_labeled("render_list");
+        println!("{} item(s), borrow held by {:?}", items.len(), labeled.holder());
+        // labeled.borrow_mut_labeled("add_item") here would panic with:
+        // "`add_item` can't borrow: already borrowed by `render_list`"
+    }
+    labeled.borrow_mut_labeled("add_item").push("cherry".to_string());
+    println!("After release: {:?}", *labeled.borrow_labeled("print"));
+
     println!("\n=== Summary ===");
-    println!("Three simple solutions:");
+    println!("Five simple solutions:");
     println!("  1. Clone data → Release borrow → Process");
     println!("  2. Do everything in one borrow scope");
     println!("  3. Use Cell for simple Copy types");
+    println!("  4. try_borrow when a conflict should be an error, not a crash");
+    println!("  5. Queue writes during iteration, apply them afterwards");
 }
 
 // ============================================================================
@@ -177,8 +768,8 @@ mod tests {
     fn test_clone_and_release() {
         CACHE.with(|c| c.borrow_mut().clear());
 
-        add_to_cache("test".to_string());
-        add_to_cache("special-item".to_string());
+        add_to_cache("test".to_string()).unwrap();
+        add_to_cache("special-item".to_string()).unwrap();
 
         // This should NOT panic
         process_items_correct();
@@ -225,4 +816,287 @@ mod tests {
 
         assert_eq!(counter.get(), 20);  // 10 increments + 10 from update_and_log
     }
+
+    #[test]
+    fn test_checked_increment_stops_at_max() {
+        let counter = SimpleCounter::starting_at(0i8);
+
+        let mut succeeded = 0;
+        while counter.checked_increment() {
+            succeeded += 1;
+        }
+
+        assert_eq!(succeeded, 127);
+        assert_eq!(counter.get(), i8::MAX);
+        // Still refused, and the value is untouched
+        assert!(!counter.checked_increment());
+        assert_eq!(counter.get(), i8::MAX);
+    }
+
+    #[test]
+    fn test_saturating_increment_clamps_at_max() {
+        let counter = SimpleCounter::starting_at(i8::MAX - 2);
+
+        for _ in 0..5 {
+            counter.saturating_increment();
+        }
+
+        assert_eq!(counter.get(), i8::MAX);
+    }
+
+    #[test]
+    fn test_observer_can_reenter_counter() {
+        use std::rc::Rc;
+
+        let counter = Rc::new(SimpleCounter::new());
+        let seen = Rc::new(RefCell::new(Vec::new()));
+
+        // Keeps incrementing from inside the notification until a multiple of 5
+        let weak = Rc::downgrade(&counter);
+        counter.observe(move |value| {
+            let counter = weak.upgrade().unwrap();
+            assert_eq!(counter.get(), value);  // ✅ Reading during notification
+            if value % 5 != 0 {
+                counter.increment();  // ✅ Writing during notification
+            }
+        });
+        let log = Rc::clone(&seen);
+        counter.observe(move |value| log.borrow_mut().push(value));
+
+        counter.increment();
+
+        assert_eq!(counter.get(), 5);
+        // Every change was reported; nested notifications finish first
+        assert_eq!(*seen.borrow(), vec![5, 4, 3, 2, 1]);
+    }
+
+    #[test]
+    fn test_atomic_counter_across_threads() {
+        const THREADS: i32 = 8;
+        const PER_THREAD: i32 = 1000;
+
+        let counter = AtomicCounter::new();
+        std::thread::scope(|s| {
+            for _ in 0..THREADS {
+                s.spawn(|| {
+                    for _ in 0..PER_THREAD {
+                        counter.increment();
+                    }
+                });
+            }
+        });
+
+        // No lock, no borrow, and no increment lost
+        assert_eq!(counter.get(), THREADS * PER_THREAD);
+
+        counter.update_and_log();
+        assert_eq!(counter.get(), THREADS * PER_THREAD + 1);
+    }
+
+    #[test]
+    fn test_counter_works_for_unsigned_types() {
+        let counter = SimpleCounter::starting_at(u8::MAX - 1);
+
+        counter.increment();
+        assert_eq!(counter.get(), u8::MAX);
+        assert!(!counter.checked_increment());
+    }
+
+    #[test]
+    fn test_safe_cache_reentry_returns_err() {
+        SafeCache.clear().unwrap();
+        add_to_cache("test".to_string()).unwrap();
+
+        // Deliberately reenter: write while the read borrow is held
+        let result = SafeCache
+            .with_items(|_items| add_to_cache("new".to_string()))
+            .unwrap();
+        assert!(matches!(result, Err(BorrowError::Exclusive(_))));
+
+        // And read while a write borrow is held
+        let nested = CACHE.with(|c| {
+            let _guard = c.borrow_mut();
+            SafeCache.snapshot()
+        });
+        assert!(matches!(nested, Err(BorrowError::Shared(_))));
+
+        // Nothing was lost or corrupted by the failed attempts
+        assert_eq!(SafeCache.snapshot().unwrap(), vec!["test".to_string()]);
+    }
+
+    #[test]
+    fn test_try_process_items_recovers() {
+        SafeCache.clear().unwrap();
+        add_to_cache("plain".to_string()).unwrap();
+        add_to_cache("special-item".to_string()).unwrap();
+
+        assert_eq!(try_process_items().unwrap().derived, 1);
+
+        let items = SafeCache.snapshot().unwrap();
+        assert_eq!(items.last().map(String::as_str), Some("derived-special-item"));
+    }
+
+    #[test]
+    fn test_try_process_items_counts_derived_items() {
+        SafeCache.clear().unwrap();
+        for item in ["apple", "special-banana", "cherry", "special-kiwi", "special-fig"] {
+            add_to_cache(item.to_string()).unwrap();
+        }
+
+        let stats = try_process_items().unwrap();
+
+        assert_eq!(stats, ProcessStats { processed: 5, derived: 3 });
+        assert_eq!(
+            SafeCache.snapshot().unwrap(),
+            vec![
+                "apple",
+                "special-banana",
+                "cherry",
+                "special-kiwi",
+                "special-fig",
+                "derived-special-banana",
+                "derived-special-kiwi",
+                "derived-special-fig",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_try_process_items_reports_conflict() {
+        SafeCache.clear().unwrap();
+        add_to_cache("special-item".to_string()).unwrap();
+
+        // A writer still holds the cache: the run fails instead of panicking
+        let result = CACHE.with(|c| {
+            let _guard = c.borrow_mut();
+            try_process_items()
+        });
+
+        assert!(matches!(result, Err(BorrowError::Shared(_))));
+        assert_eq!(SafeCache.snapshot().unwrap(), vec!["special-item".to_string()]);
+    }
+
+    #[test]
+    fn test_deferred_cache_adds_after_iteration() {
+        let cache = DeferredCache::new();
+        cache.add("test".to_string());
+        cache.add("special-item".to_string());
+
+        // Mirrors process_items_broken, which panics with a plain RefCell
+        let mut seen = Vec::new();
+        cache.iter_with(|item| {
+            seen.push(item.to_string());
+            if item.contains("special") {
+                cache.defer_add(format!("derived-{}", item));
+            }
+        });
+
+        // The derived item wasn't visible during the iteration...
+        assert_eq!(seen, vec!["test", "special-item"]);
+        // ...but it's there afterwards
+        assert_eq!(cache.items(), vec!["test", "special-item", "derived-special-item"]);
+    }
+
+    #[test]
+    fn test_deferred_cache_nested_iteration() {
+        let cache = DeferredCache::new();
+        cache.add("a".to_string());
+
+        cache.iter_with(|outer| {
+            cache.iter_with(|inner| cache.defer_add(format!("{}{}", outer, inner)));
+            // The inner iteration must not flush while the outer borrow is held
+            assert_eq!(cache.items.borrow().len(), 1);
+        });
+
+        assert_eq!(cache.items(), vec!["a", "aa"]);
+    }
+
+    #[test]
+    fn test_deferred_cell_changes_only_after_flush() {
+        let cell = DeferredCell::new(vec![1, 2]);
+
+        cell.read(|outer| {
+            for &n in outer {
+                cell.read(|inner| {
+                    // Nested reads may enqueue writes...
+                    let len = inner.len();
+                    cell.defer(move |v| v.push(n * 10 + len as i32));
+                });
+            }
+            // ...and flushing during a read is a no-op, not a panic
+            assert_eq!(cell.flush(), 0);
+        });
+
+        // Nothing changed yet
+        assert_eq!(cell.pending(), 2);
+        assert_eq!(cell.read(|v| v.clone()), vec![1, 2]);
+
+        assert_eq!(cell.flush(), 2);
+        assert_eq!(cell.read(|v| v.clone()), vec![1, 2, 12, 22]);
+        assert_eq!(cell.pending(), 0);
+    }
+
+    #[test]
+    fn test_deferred_cell_commands_can_queue_commands() {
+        let cell = std::rc::Rc::new(DeferredCell::new(String::new()));
+
+        let inner = std::rc::Rc::clone(&cell);
+        cell.defer(move |s| {
+            s.push('a');
+            inner.defer(|s| s.push('b'));
+        });
+
+        assert_eq!(cell.flush(), 2);
+        assert_eq!(cell.read(|s| s.clone()), "ab");
+    }
+
+    #[test]
+    #[cfg_attr(not(debug_assertions), ignore = "labels are only recorded in debug builds")]
+    #[should_panic(expected = "already borrowed by `render_list`")]
+    fn test_labeled_borrow_names_holder() {
+        let cell = LabeledRefCell::new(vec![1, 2, 3]);
+
+        let _items = cell.borrow_labeled("render_list");
+        let _conflict = cell.borrow_mut_labeled("add_item");  // ❌ PANIC, but now we know who
+    }
+
+    #[test]
+    #[cfg_attr(not(debug_assertions), ignore = "labels are only recorded in debug builds")]
+    fn test_labeled_borrow_restores_holder() {
+        let cell = LabeledRefCell::new(0);
+        assert_eq!(cell.holder(), None);
+
+        {
+            let _outer = cell.borrow_labeled("outer");
+            {
+                let _inner = cell.borrow_labeled("inner");
+                assert_eq!(cell.holder(), Some("inner"));
+            }
+            assert_eq!(cell.holder(), Some("outer"));
+        }
+        assert_eq!(cell.holder(), None);
+
+        *cell.borrow_mut_labeled("writer") += 1;
+        assert_eq!(*cell.borrow_labeled("reader"), 1);
+    }
+
+    #[test]
+    #[cfg_attr(not(debug_assertions), ignore = "labels are only recorded in debug builds")]
+    fn test_labeled_borrows_dropped_out_of_order() {
+        let cell = LabeledRefCell::new(0);
+
+        let first = cell.borrow_labeled("first");
+        let second = cell.borrow_labeled("second");
+        assert_eq!(cell.holder(), Some("second"));
+
+        // Dropping the older guard first must not resurrect a stale label
+        drop(first);
+        assert_eq!(cell.holder(), Some("second"));
+
+        drop(second);
+        assert_eq!(cell.holder(), None);
+
+        *cell.borrow_mut_labeled("writer") += 1;
+        assert_eq!(cell.holder(), None);
+    }
 }
@@ -1,12 +1,19 @@
 // CORRECT EXAMPLE: Using enum to represent room display name states
 // Type-safe approach that prevents bugs at compile time
 
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
 use std::collections::HashMap;
 use std::fmt;
+use unicode_segmentation::UnicodeSegmentation;
 
 /// Strongly-typed enum representing all possible room display name states
 /// This matches the Matrix SDK's RoomDisplayName enum
-#[derive(Debug, Clone, PartialEq)]
+///
+/// Serialized with an explicit tag so cached room state round-trips:
+/// `{"type":"Named","value":"General"}`, or `{"type":"Empty"}` for `Empty`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", content = "value")]
 enum RoomDisplayName {
     /// Room has a proper name
     Named(String),
@@ -21,6 +28,42 @@ enum RoomDisplayName {
 }
 
 impl RoomDisplayName {
+    /// Decide which variant applies from a room's raw state, in the same
+    /// priority order as the Matrix SDK:
+    /// explicit name → `Named`, else alias → `Aliased`,
+    /// else other members → `Calculated`, else `Empty`
+    ///
+    /// Blank names and aliases count as missing.
+    fn compute(name: Option<&str>, alias: Option<&str>, members: &[String]) -> RoomDisplayName {
+        fn present(s: Option<&str>) -> Option<&str> {
+            s.map(str::trim).filter(|s| !s.is_empty())
+        }
+
+        if let Some(name) = present(name) {
+            return RoomDisplayName::Named(name.to_string());
+        }
+        if let Some(alias) = present(alias) {
+            return RoomDisplayName::Aliased(alias.to_string());
+        }
+
+        // DM-style naming from the other members
+        match members {
+            [] => RoomDisplayName::Empty,
+            [only] => RoomDisplayName::Calculated(only.clone()),
+            [first, second] => RoomDisplayName::Calculated(format!("{} and {}", first, second)),
+            [first, second, rest @ ..] => {
+                let others = if rest.len() == 1 { "other" } else { "others" };
+                RoomDisplayName::Calculated(format!(
+                    "{}, {} and {} {}",
+                    first,
+                    second,
+                    rest.len(),
+                    others
+                ))
+            }
+        }
+    }
+
     /// Convert to displayable string for UI
     fn to_display_string(&self) -> String {
         match self {
@@ -32,10 +75,88 @@ impl RoomDisplayName {
         }
     }
 
+    /// Display string clipped to at most `max_chars` user-perceived characters
+    ///
+    /// Counts grapheme clusters, so emoji and accented letters are never split.
+    /// A clipped name ends in "…", which counts toward the limit; names that
+    /// already fit (including the `Empty` placeholder) are returned unchanged.
+    fn truncated(&self, max_chars: usize) -> String {
+        let display = self.to_display_string();
+        if display.graphemes(true).nth(max_chars).is_none() {
+            return display;
+        }
+
+        let clipped: String = display
+            .graphemes(true)
+            .take(max_chars.saturating_sub(1))
+            .collect();
+        // Don't leave a dangling space before the ellipsis
+        let mut clipped = clipped.trim_end().to_string();
+        if max_chars > 0 {
+            clipped.push('…');
+        }
+        clipped
+    }
+
+    /// How trustworthy the name source is; higher wins
+    ///
+    /// An explicit name beats an alias, which beats a member-derived name,
+    /// which beats any placeholder.
+    fn priority(&self) -> u8 {
+        match self {
+            RoomDisplayName::Named(_) => 3,
+            RoomDisplayName::Aliased(_) => 2,
+            RoomDisplayName::Calculated(_) => 1,
+            RoomDisplayName::EmptyWas(_) | RoomDisplayName::Empty => 0,
+        }
+    }
+
     /// Check if this is a placeholder/empty name
     fn is_placeholder(&self) -> bool {
         matches!(self, RoomDisplayName::Empty | RoomDisplayName::EmptyWas(_))
     }
+
+    /// The text carried by the variant (empty for `Empty`)
+    fn text(&self) -> &str {
+        match self {
+            RoomDisplayName::Named(text)
+            | RoomDisplayName::Calculated(text)
+            | RoomDisplayName::Aliased(text)
+            | RoomDisplayName::EmptyWas(text) => text,
+            RoomDisplayName::Empty => "",
+        }
+    }
+
+    /// Position of the variant in declaration order, used as the last tie-breaker
+    fn variant_index(&self) -> u8 {
+        match self {
+            RoomDisplayName::Named(_) => 0,
+            RoomDisplayName::Calculated(_) => 1,
+            RoomDisplayName::Aliased(_) => 2,
+            RoomDisplayName::EmptyWas(_) => 3,
+            RoomDisplayName::Empty => 4,
+        }
+    }
+}
+
+/// Alphabetical, case-insensitive, with placeholder names sorted last
+///
+/// Ties on the folded text fall back to the exact text and then the variant,
+/// so the ordering agrees with `==`.
+impl Ord for RoomDisplayName {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.is_placeholder()
+            .cmp(&other.is_placeholder())
+            .then_with(|| self.text().to_lowercase().cmp(&other.text().to_lowercase()))
+            .then_with(|| self.text().cmp(other.text()))
+            .then_with(|| self.variant_index().cmp(&other.variant_index()))
+    }
+}
+
+impl PartialOrd for RoomDisplayName {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
 }
 
 impl fmt::Display for RoomDisplayName {
@@ -53,6 +174,19 @@ struct RoomInfo {
     /// - Some(RoomDisplayName::Empty): Room explicitly has no name
     /// - Some(RoomDisplayName::Named(s)): Room has a proper name
     room_name: Option<RoomDisplayName>,
+    /// Messages the user hasn't read yet
+    unread_count: u32,
+}
+
+/// Sorts rooms for display: named rooms alphabetically, then placeholders,
+/// then rooms whose name hasn't loaded yet. Ties keep a stable order by ID.
+fn sort_rooms(rooms: &mut [RoomInfo]) {
+    rooms.sort_by(|a, b| {
+        // `None` sorts before `Some`, so compare "is missing" first
+        (a.room_name.is_none(), &a.room_name)
+            .cmp(&(b.room_name.is_none(), &b.room_name))
+            .then_with(|| a.room_id.cmp(&b.room_id))
+    });
 }
 
 /// Simulates a rooms list manager
@@ -78,11 +212,31 @@ impl RoomsList {
                 return;
             }
 
+            // Never downgrade, e.g. a late Calculated name over an explicit Named one
+            if let Some(current) = &room.room_name
+                && new_name.priority() < current.priority()
+            {
+                println!("  [SKIP] {:?} would downgrade {:?} for {}", new_name, current, room.room_id);
+                return;
+            }
+
             println!("  [UPDATE] Setting name to: {:?}", new_name);
             room.room_name = Some(new_name);
         }
     }
 
+    /// Display name with an unread badge, e.g. "General (3)"
+    ///
+    /// Just the name when there's nothing unread. Placeholder names get the
+    /// badge too, so an unnamed room with messages still stands out.
+    fn display_with_badge(&self, room_id: &str) -> String {
+        let name = self.get_display_name(room_id);
+        match self.rooms.get(room_id).map_or(0, |room| room.unread_count) {
+            0 => name,
+            unread => format!("{} ({})", name, unread),
+        }
+    }
+
     /// Gets displayable room name for UI
     fn get_display_name(&self, room_id: &str) -> String {
         self.rooms
@@ -102,6 +256,7 @@ fn main() {
         RoomInfo {
             room_id: "!abc:matrix.org".to_string(),
             room_name: Some(RoomDisplayName::Named("General Chat".to_string())),
+            unread_count: 3,
         },
     );
 
@@ -111,6 +266,7 @@ fn main() {
         RoomInfo {
             room_id: "!xyz:matrix.org".to_string(),
             room_name: None,  // Clear: name not loaded yet
+            unread_count: 1,
         },
     );
 
@@ -120,13 +276,14 @@ fn main() {
         RoomInfo {
             room_id: "!def:matrix.org".to_string(),
             room_name: Some(RoomDisplayName::Empty),  // Clear: has no name
+            unread_count: 0,
         },
     );
 
     println!("=== Initial State ===");
-    println!("Room 1: {}", rooms.get_display_name("!abc:matrix.org"));
-    println!("Room 2: {}", rooms.get_display_name("!xyz:matrix.org"));
-    println!("Room 3: {}", rooms.get_display_name("!def:matrix.org"));
+    println!("Room 1: {}", rooms.display_with_badge("!abc:matrix.org"));
+    println!("Room 2: {}", rooms.display_with_badge("!xyz:matrix.org"));
+    println!("Room 3: {}", rooms.display_with_badge("!def:matrix.org"));
 
     println!("\n=== Trying to update with placeholder (Empty) ===");
     // This update will be skipped - preventing bugs!
@@ -140,6 +297,45 @@ fn main() {
     );
     println!("Room 2 after proper update: {}", rooms.get_display_name("!xyz:matrix.org"));
 
+    println!("\n=== Trying to downgrade with a calculated name ===");
+    rooms.update_room_name(
+        "!xyz:matrix.org".to_string(),
+        RoomDisplayName::Calculated("Alice and Bob".to_string())
+    );
+    println!("Room 2 after calculated update: {}", rooms.get_display_name("!xyz:matrix.org"));
+
+    println!("\n=== Computing names from room state ===");
+    let members = ["Alice".to_string(), "Bob".to_string()];
+    println!("Name set:    {:?}", RoomDisplayName::compute(Some("Rust"), Some("#rust:matrix.org"), &members));
+    println!("Alias only:  {:?}", RoomDisplayName::compute(None, Some("#rust:matrix.org"), &members));
+    println!("Members:     {:?}", RoomDisplayName::compute(None, None, &members));
+    println!("Nothing:     {:?}", RoomDisplayName::compute(None, None, &[]));
+
+    println!("\n=== Caching names to disk ===");
+    // Every variant survives a JSON round trip, so nothing degrades to Empty
+    let cached = [
+        RoomDisplayName::Named("General".to_string()),
+        RoomDisplayName::Calculated("Alice and Bob".to_string()),
+        RoomDisplayName::Aliased("#rust:matrix.org".to_string()),
+        RoomDisplayName::EmptyWas("Old Room".to_string()),
+        RoomDisplayName::Empty,
+    ];
+    let json = serde_json::to_string(&cached).expect("names serialize");
+    println!("Cached: {}", json);
+    let restored: Vec<RoomDisplayName> = serde_json::from_str(&json).expect("names deserialize");
+    println!("Restored {} names intact: {}", restored.len(), restored == cached);
+
+    println!("\n=== Sorted room list ===");
+    let mut sorted: Vec<RoomInfo> = rooms.rooms.values().cloned().collect();
+    sort_rooms(&mut sorted);
+    for room in &sorted {
+        println!("{}: {}", room.room_id, rooms.get_display_name(&room.room_id));
+    }
+
+    println!("\n=== Clipping long names for a narrow sidebar ===");
+    let long = RoomDisplayName::Named("Rust Async Working Group 🦀🚀".to_string());
+    println!("{} → {}", long, long.truncated(12));
+
     println!("\n=== Benefits ===");
     println!("✅ Type system enforces clear semantics");
     println!("✅ Cannot accidentally confuse empty string with None");
@@ -153,3 +349,204 @@ fn main() {
 // 2. Type safety prevents bugs that would only appear at runtime
 // 3. Pattern matching makes intent explicit and catches missing cases
 // 4. Aligning internal types with SDK types reduces conversion errors
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn members(names: &[&str]) -> Vec<String> {
+        names.iter().map(|name| name.to_string()).collect()
+    }
+
+    #[test]
+    fn test_compute_prefers_explicit_name() {
+        let name = RoomDisplayName::compute(Some("General"), Some("#general:matrix.org"), &members(&["Alice"]));
+        assert_eq!(name, RoomDisplayName::Named("General".to_string()));
+    }
+
+    #[test]
+    fn test_compute_falls_back_to_alias() {
+        let alias = Some("#general:matrix.org");
+        let expected = RoomDisplayName::Aliased("#general:matrix.org".to_string());
+
+        assert_eq!(RoomDisplayName::compute(None, alias, &members(&["Alice"])), expected);
+        // A blank name is as good as no name
+        assert_eq!(RoomDisplayName::compute(Some("  "), alias, &[]), expected);
+    }
+
+    #[test]
+    fn test_compute_calculates_from_members() {
+        let calculated = |names: &[&str]| RoomDisplayName::compute(None, None, &members(names));
+
+        assert_eq!(calculated(&["Alice"]), RoomDisplayName::Calculated("Alice".to_string()));
+        assert_eq!(calculated(&["Alice", "Bob"]), RoomDisplayName::Calculated("Alice and Bob".to_string()));
+        assert_eq!(
+            calculated(&["Alice", "Bob", "Carol"]),
+            RoomDisplayName::Calculated("Alice, Bob and 1 other".to_string())
+        );
+        assert_eq!(
+            calculated(&["Alice", "Bob", "Carol", "Dan"]),
+            RoomDisplayName::Calculated("Alice, Bob and 2 others".to_string())
+        );
+    }
+
+    #[test]
+    fn test_compute_empty_members_fallback() {
+        assert_eq!(RoomDisplayName::compute(None, None, &[]), RoomDisplayName::Empty);
+        assert_eq!(RoomDisplayName::compute(Some(""), Some(""), &[]), RoomDisplayName::Empty);
+    }
+
+    #[test]
+    fn test_sort_rooms_alphabetical_with_empty_last() {
+        let room = |id: &str, name: Option<RoomDisplayName>| RoomInfo {
+            room_id: id.to_string(),
+            room_name: name,
+            unread_count: 0,
+        };
+        let mut list = vec![
+            room("!1", Some(RoomDisplayName::Empty)),
+            room("!2", Some(RoomDisplayName::Named("zulu".to_string()))),
+            room("!3", None),
+            room("!4", Some(RoomDisplayName::EmptyWas("Aardvark".to_string()))),
+            room("!5", Some(RoomDisplayName::Aliased("#Mike:matrix.org".to_string()))),
+            room("!6", Some(RoomDisplayName::Calculated("bravo and Charlie".to_string()))),
+            room("!7", Some(RoomDisplayName::Named("Alpha".to_string()))),
+        ];
+
+        sort_rooms(&mut list);
+
+        let order: Vec<&str> = list.iter().map(|room| room.room_id.as_str()).collect();
+        // #Mike (sorted by its '#'), Alpha, bravo and Charlie, zulu,
+        // then the placeholders, then the room whose name isn't loaded
+        assert_eq!(order, vec!["!5", "!7", "!6", "!2", "!1", "!4", "!3"]);
+    }
+
+    #[test]
+    fn test_ord_agrees_with_eq() {
+        let named = RoomDisplayName::Named("General".to_string());
+        let calculated = RoomDisplayName::Calculated("General".to_string());
+        let lower = RoomDisplayName::Named("general".to_string());
+
+        assert_eq!(named.cmp(&named.clone()), Ordering::Equal);
+        assert_ne!(named.cmp(&calculated), Ordering::Equal);
+        assert_ne!(named.cmp(&lower), Ordering::Equal);
+        assert!(RoomDisplayName::Named("b".to_string()) < RoomDisplayName::Empty);
+    }
+
+    #[test]
+    fn test_truncated_ascii() {
+        let name = RoomDisplayName::Named("General Discussion".to_string());
+        assert_eq!(name.truncated(8), "General…");
+        assert_eq!(name.truncated(8).chars().count(), 8);
+    }
+
+    #[test]
+    fn test_truncated_keeps_emoji_whole() {
+        // 👩‍💻 is several chars joined by ZWJ but one grapheme
+        let name = RoomDisplayName::Named("Dev 👩‍💻🦀 chat".to_string());
+        assert_eq!(name.truncated(6), "Dev 👩‍💻…");
+
+        let crabs = RoomDisplayName::Calculated("🦀🦀🦀🦀".to_string());
+        assert_eq!(crabs.truncated(3), "🦀🦀…");
+    }
+
+    #[test]
+    fn test_truncated_short_names_unchanged() {
+        let name = RoomDisplayName::Named("Rust".to_string());
+        assert_eq!(name.truncated(4), "Rust");
+        assert_eq!(name.truncated(40), "Rust");
+
+        assert_eq!(RoomDisplayName::Empty.truncated(20), "Unnamed Room");
+    }
+
+    /// A list holding a single room; `None` unread count means nothing unread
+    fn list_with(room_id: &str, name: Option<RoomDisplayName>, unread_count: Option<u32>) -> RoomsList {
+        let mut rooms = RoomsList::new();
+        rooms.rooms.insert(room_id.to_string(), RoomInfo {
+            room_id: room_id.to_string(),
+            room_name: name,
+            unread_count: unread_count.unwrap_or(0),
+        });
+        rooms
+    }
+
+    #[test]
+    fn test_named_survives_calculated_update() {
+        let mut rooms = list_with("!r", Some(RoomDisplayName::Named("General".to_string())), None);
+
+        rooms.update_room_name("!r".to_string(), RoomDisplayName::Calculated("Alice and Bob".to_string()));
+        assert_eq!(rooms.get_display_name("!r"), "General");
+
+        rooms.update_room_name("!r".to_string(), RoomDisplayName::Aliased("#general:matrix.org".to_string()));
+        assert_eq!(rooms.get_display_name("!r"), "General");
+
+        // Same priority is a normal rename
+        rooms.update_room_name("!r".to_string(), RoomDisplayName::Named("Lobby".to_string()));
+        assert_eq!(rooms.get_display_name("!r"), "Lobby");
+    }
+
+    #[test]
+    fn test_calculated_upgraded_by_named() {
+        let mut rooms = list_with("!r", Some(RoomDisplayName::Calculated("Alice and Bob".to_string())), None);
+
+        rooms.update_room_name("!r".to_string(), RoomDisplayName::Named("Project X".to_string()));
+        assert_eq!(rooms.get_display_name("!r"), "Project X");
+    }
+
+    #[test]
+    fn test_badge_hidden_when_nothing_unread() {
+        let rooms = list_with("!r", Some(RoomDisplayName::Named("General".to_string())), None);
+        assert_eq!(rooms.display_with_badge("!r"), "General");
+    }
+
+    #[test]
+    fn test_badge_shows_unread_count() {
+        let rooms = list_with("!r", Some(RoomDisplayName::Named("General".to_string())), Some(3));
+        assert_eq!(rooms.display_with_badge("!r"), "General (3)");
+    }
+
+    #[test]
+    fn test_badge_on_unnamed_rooms() {
+        let rooms = list_with("!r", Some(RoomDisplayName::Empty), Some(2));
+        assert_eq!(rooms.display_with_badge("!r"), "Unnamed Room (2)");
+
+        let rooms = list_with("!r", Some(RoomDisplayName::EmptyWas("Old".to_string())), Some(1));
+        assert_eq!(rooms.display_with_badge("!r"), "Empty (was Old) (1)");
+
+        let rooms = list_with("!r", None, Some(5));
+        assert_eq!(rooms.display_with_badge("!r"), "Invite to Unnamed Room (5)");
+    }
+
+    #[test]
+    fn test_serde_round_trips_every_variant() {
+        let names = [
+            RoomDisplayName::Named("General".to_string()),
+            RoomDisplayName::Calculated("Alice and Bob".to_string()),
+            RoomDisplayName::Aliased("#rust:matrix.org".to_string()),
+            RoomDisplayName::EmptyWas("Old Room".to_string()),
+            RoomDisplayName::Empty,
+        ];
+
+        for name in names {
+            let json = serde_json::to_string(&name).unwrap();
+            let back: RoomDisplayName = serde_json::from_str(&json).unwrap();
+            assert_eq!(back, name, "round trip through {}", json);
+        }
+    }
+
+    #[test]
+    fn test_serde_tagged_representation() {
+        let named = serde_json::to_value(RoomDisplayName::Named("General".to_string())).unwrap();
+        assert_eq!(named, serde_json::json!({"type": "Named", "value": "General"}));
+
+        // Empty has no value field at all
+        let empty = serde_json::to_value(RoomDisplayName::Empty).unwrap();
+        assert_eq!(empty, serde_json::json!({"type": "Empty"}));
+    }
+
+    #[test]
+    fn test_serde_unknown_tag_is_an_error() {
+        let result = serde_json::from_str::<RoomDisplayName>(r#"{"type":"Nicknamed","value":"x"}"#);
+        assert!(result.is_err(), "unknown tags must not silently become Empty");
+    }
+}
@@ -0,0 +1,327 @@
+//! Sealed Trait Pattern - 密封 trait 模式示例
+//!
+//! 这个模式用于防止外部 crate 实现某个 trait，
+//! 同时保持 trait 本身是 public 的（可以被使用，但不能被实现）
+//!
+//! 示例代码在这个库里，`src/main.rs` 演示用法；`tests/ui/` 下的
+//! trybuild 用例以外部 crate 的身份验证伪造和混用令牌都无法编译。
+
+// 令牌故意不实现 Default：否则泛型代码可以用 `T::default()` 凭空造出令牌
+#![allow(clippy::new_without_default)]
+
+// ========== 基本的 Sealed Trait 实现 ==========
+
+mod private {
+    // 这个模块是私有的，外部无法访问
+    pub trait Sealed {}
+
+    // 只有在这个模块内部才能实现 Sealed
+    impl Sealed for super::UiThreadMarker {}
+}
+
+// 公开的 trait，但继承自私有的 Sealed trait
+// 外部代码可以使用这个 trait，但无法实现它
+pub trait UiThread: private::Sealed {
+    // trait 方法...
+}
+
+// 我们自己的类型可以实现 UiThread（因为它已经实现了 Sealed）
+pub struct UiThreadMarker;
+
+impl UiThread for UiThreadMarker {
+    // 实现细节...
+}
+
+// ========== 用宏生成密封的线程标记 ==========
+
+/// 生成密封 trait、令牌结构体（私有字段 + `new()`）以及对应的 impl，
+/// 省去每个新令牌都要手写一遍 `mod private { ... }` 的样板代码。
+///
+/// ```ignore
+/// sealed_marker!(mod ui_marker: pub UiThread => UiThreadToken);
+/// ```
+///
+/// 展开的内容放在以 `mod` 参数命名的私有子模块里再重新导出，因此即使在同一个文件中，
+/// 调用方模块也无法访问 `Sealed`、无法为其他类型实现这个 trait。
+/// 子模块名由调用方给出，同一个模块里可以调用多次，只要每次的名字不同。
+#[macro_export]
+macro_rules! sealed_marker {
+    (mod $module:ident: $vis:vis $trait_name:ident => $token:ident) => {
+        $vis use self::$module::{$token, $trait_name};
+
+        mod $module {
+            mod private {
+                pub trait Sealed {}
+                impl Sealed for super::$token {}
+            }
+
+            pub trait $trait_name: private::Sealed {}
+
+            pub struct $token {
+                _private: (),
+            }
+
+            impl $token {
+                pub fn new() -> Self {
+                    $token { _private: () }
+                }
+            }
+
+            impl $trait_name for $token {}
+        }
+    };
+}
+
+// ========== 用于 UI 线程安全的完整示例 ==========
+
+use std::{
+    cell::{Cell, RefCell},
+    collections::HashMap,
+    rc::Rc,
+};
+
+#[derive(Clone, Debug)]
+pub struct RoomData {
+    pub id: String,
+    pub name: String,
+}
+
+thread_local! {
+    static ROOM_CACHE: Rc<RefCell<HashMap<String, RoomData>>> =
+        Rc::new(RefCell::new(HashMap::new()));
+    static FRAME_COUNT: Cell<u64> = const { Cell::new(0) };
+}
+
+// ✅ 方案 1: Sealed Trait 模式
+pub mod sealed_approach {
+    use super::*;
+
+    mod private {
+        pub trait Sealed {}
+        // 两个令牌的 Sealed 实现都在 crate 内部，外部无法伪造任何一个
+        impl Sealed for super::MainThreadToken {}
+        impl Sealed for super::RenderThreadToken {}
+    }
+
+    /// 只有实现了这个 trait 的类型才能访问 UI 函数
+    /// 由于 Sealed，外部无法实现这个 trait
+    pub trait UiThread: private::Sealed {}
+
+    /// 主线程令牌 - 处理事件、修改 UI 状态
+    pub struct MainThreadToken {
+        _private: (),
+    }
+
+    /// 渲染线程令牌 - 只负责绘制
+    pub struct RenderThreadToken {
+        _private: (),
+    }
+
+    impl UiThread for MainThreadToken {}
+    impl UiThread for RenderThreadToken {}
+
+    impl MainThreadToken {
+        pub fn new() -> Self {
+            MainThreadToken { _private: () }
+        }
+    }
+
+    impl RenderThreadToken {
+        pub fn new() -> Self {
+            RenderThreadToken { _private: () }
+        }
+    }
+
+    /// 使用泛型 + trait bound 来约束
+    /// 只有实现了 UiThread 的类型才能调用这个函数
+    pub fn add_room<T: UiThread>(_proof: &T, room: RoomData) {
+        ROOM_CACHE.with(|cache| {
+            cache.borrow_mut().insert(room.id.clone(), room);
+        });
+    }
+
+    pub fn get_room<T: UiThread>(_proof: &T, room_id: &str) -> Option<RoomData> {
+        ROOM_CACHE.with(|cache| {
+            cache.borrow().get(room_id).cloned()
+        })
+    }
+
+    /// 要求具体的标记类型：只有渲染线程能调用
+    /// 返回到目前为止绘制的帧数
+    pub fn render_only(_proof: &RenderThreadToken) -> u64 {
+        FRAME_COUNT.with(|frames| {
+            frames.set(frames.get() + 1);
+            frames.get()
+        })
+    }
+
+    /// 要求具体的标记类型：只有主线程能调用
+    pub fn main_only(proof: &MainThreadToken, room_id: &str, name: &str) {
+        add_room(proof, RoomData {
+            id: room_id.to_string(),
+            name: name.to_string(),
+        });
+    }
+}
+
+// ✅ 方案 1b: 用 sealed_marker! 宏生成同样的结构
+pub mod macro_approach {
+    use super::*;
+
+    sealed_marker!(mod audio_marker: pub AudioThread => AudioThreadToken);
+
+    pub fn add_room<T: AudioThread>(_proof: &T, room: RoomData) {
+        ROOM_CACHE.with(|cache| {
+            cache.borrow_mut().insert(room.id.clone(), room);
+        });
+    }
+}
+
+// ✅ 方案 2: 简单的 Witness Type（我们之前使用的）
+pub mod witness_approach {
+    use super::*;
+
+    /// 简单的见证者类型
+    pub struct UiContext {
+        _private: (),
+    }
+
+    impl UiContext {
+        pub fn new() -> Self {
+            UiContext { _private: () }
+        }
+    }
+
+    /// 直接要求具体的类型
+    pub fn add_room(_ui: &UiContext, room: RoomData) {
+        ROOM_CACHE.with(|cache| {
+            cache.borrow_mut().insert(room.id.clone(), room);
+        });
+    }
+
+    pub fn get_room(_ui: &UiContext, room_id: &str) -> Option<RoomData> {
+        ROOM_CACHE.with(|cache| {
+            cache.borrow().get(room_id).cloned()
+        })
+    }
+}
+
+// ========== 为什么 Sealed Trait 是"密封"的 ==========
+
+// 假设有外部代码尝试实现 UiThread:
+/*
+// ❌ 这段代码无法编译！
+pub struct MyFakeUiThread;
+
+impl sealed_approach::UiThread for MyFakeUiThread {}
+//                                 ^^^^^^^^^^^^^^^^
+// ERROR: the trait `private::Sealed` is not implemented for `MyFakeUiThread`
+//
+// 因为 MyFakeUiThread 无法实现 private::Sealed（private 模块在外部不可见）
+// 所以也就无法实现 UiThread（因为 UiThread: private::Sealed）
+*/
+
+// ========== 为什么令牌不能混用 ==========
+
+// 通用函数接受任意 UiThread，但专属函数要求具体的令牌类型:
+//
+//     let main_token = sealed_approach::MainThreadToken::new();
+//     sealed_approach::render_only(&main_token);
+//     //                           ^^^^^^^^^^^ expected `&RenderThreadToken`, found `&MainThreadToken`
+//
+// tests/ui/mixed_tokens.rs 就是这段代码，由 trybuild 断言它编译失败。
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sealed_trait_approach() {
+        use sealed_approach::*;
+
+        let token = MainThreadToken::new();
+
+        add_room(&token, RoomData {
+            id: "test".to_string(),
+            name: "Test".to_string(),
+        });
+
+        assert!(get_room(&token, "test").is_some());
+    }
+
+    #[test]
+    fn test_main_thread_token_functions() {
+        use sealed_approach::*;
+
+        let main_token = MainThreadToken::new();
+        main_only(&main_token, "lobby", "Lobby");
+
+        assert_eq!(get_room(&main_token, "lobby").unwrap().name, "Lobby");
+    }
+
+    #[test]
+    fn test_render_thread_token_functions() {
+        use sealed_approach::*;
+
+        let render = RenderThreadToken::new();
+        assert_eq!(render_only(&render), 1);
+        assert_eq!(render_only(&render), 2);
+
+        // 通用函数同样接受渲染线程令牌
+        add_room(&render, RoomData {
+            id: "overlay".to_string(),
+            name: "Overlay".to_string(),
+        });
+        assert!(get_room(&render, "overlay").is_some());
+    }
+
+    #[test]
+    fn test_sealed_marker_macro() {
+        mod network {
+            sealed_marker!(mod network_marker: pub NetworkThread => NetworkThreadToken);
+        }
+        use network::{NetworkThread, NetworkThreadToken};
+
+        fn guarded<T: NetworkThread>(_proof: &T) -> &'static str {
+            "network"
+        }
+
+        let token = NetworkThreadToken::new();
+        assert_eq!(guarded(&token), "network");
+
+        // 伪造令牌或在外部实现 trait 都无法编译，见 tests/ui/forged_macro_*.rs
+    }
+
+    #[test]
+    fn test_sealed_marker_twice_in_one_module() {
+        mod threads {
+            sealed_marker!(mod network_marker: pub NetworkThread => NetworkThreadToken);
+            sealed_marker!(mod disk_marker: pub DiskThread => DiskThreadToken);
+        }
+        use threads::{DiskThread, DiskThreadToken, NetworkThread, NetworkThreadToken};
+
+        fn network_only<T: NetworkThread>(_proof: &T) -> &'static str {
+            "network"
+        }
+        fn disk_only<T: DiskThread>(_proof: &T) -> &'static str {
+            "disk"
+        }
+
+        assert_eq!(network_only(&NetworkThreadToken::new()), "network");
+        assert_eq!(disk_only(&DiskThreadToken::new()), "disk");
+    }
+
+    #[test]
+    fn test_witness_type_approach() {
+        use witness_approach::*;
+
+        let ui = UiContext::new();
+
+        add_room(&ui, RoomData {
+            id: "test".to_string(),
+            name: "Test".to_string(),
+        });
+
+        assert!(get_room(&ui, "test").is_some());
+    }
+}
@@ -0,0 +1,80 @@
+//! Sealed Trait Pattern 演示程序，类型定义在 `src/lib.rs`
+
+use sealed_trait_example::{macro_approach, sealed_approach, witness_approach, RoomData};
+
+// ========== 使用示例对比 ==========
+
+fn main() {
+    println!("=== Sealed Trait Pattern vs Witness Type Pattern ===\n");
+
+    // 使用 Sealed Trait 方式
+    {
+        use sealed_approach::*;
+
+        let token = MainThreadToken::new();
+
+        add_room(&token, RoomData {
+            id: "room1".to_string(),
+            name: "General".to_string(),
+        });
+
+        let room = get_room(&token, "room1");
+        println!("[Sealed Trait] Room: {:?}", room);
+
+        main_only(&token, "room3", "Announcements");
+
+        // 渲染线程有自己的令牌：通用函数可用，专属函数也可用
+        let render = RenderThreadToken::new();
+        println!("[Sealed Trait] Render sees: {:?}", get_room(&render, "room1"));
+        println!("[Sealed Trait] Frames drawn: {}", render_only(&render));
+    }
+
+    // 使用宏生成的令牌
+    {
+        use macro_approach::*;
+
+        let token = AudioThreadToken::new();
+
+        add_room(&token, RoomData {
+            id: "room4".to_string(),
+            name: "Music".to_string(),
+        });
+        println!("[Macro Marker] Room added with AudioThreadToken");
+    }
+
+    // 使用简单 Witness Type 方式
+    {
+        use witness_approach::*;
+
+        let ui = UiContext::new();
+
+        add_room(&ui, RoomData {
+            id: "room2".to_string(),
+            name: "Random".to_string(),
+        });
+
+        let room = get_room(&ui, "room2");
+        println!("[Witness Type] Room: {:?}", room);
+    }
+
+    println!("\n=== 模式对比 ===");
+    println!("\n【Sealed Trait Pattern】");
+    println!("✅ 优点:");
+    println!("  • 更灵活 - 可以为多个类型实现 trait（如主线程、渲染线程令牌）");
+    println!("  • API 更通用 - 使用泛型 <T: UiThread>");
+    println!("  • 防止外部实现 - 通过 private::Sealed 限制");
+    println!("❌ 缺点:");
+    println!("  • 更复杂 - 需要额外的 mod private");
+    println!("  • 对新手不友好 - 理解成本高");
+    println!("  • 函数签名更长 - pub fn add_room<T: UiThread>(...)");
+
+    println!("\n【Witness Type Pattern】");
+    println!("✅ 优点:");
+    println!("  • 简单直接 - 只需要一个结构体");
+    println!("  • 易于理解 - 新手友好");
+    println!("  • 清晰的 API - pub fn add_room(_ui: &UiContext, ...)");
+    println!("  • 零运行时开销 - 类型擦除");
+    println!("❌ 缺点:");
+    println!("  • 不太灵活 - 只能用一个具体类型");
+    println!("  • 外部可以创建 UiContext（虽然访问的是不同的 thread_local）");
+}
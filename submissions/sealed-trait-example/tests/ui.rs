@@ -0,0 +1,8 @@
+//! 编译失败用例：每个 tests/ui/*.rs 都以外部 crate 的身份使用本库，
+//! 必须编译失败，错误信息与同名 .stderr 文件一致。
+
+#[test]
+fn ui() {
+    let t = trybuild::TestCases::new();
+    t.compile_fail("tests/ui/*.rs");
+}
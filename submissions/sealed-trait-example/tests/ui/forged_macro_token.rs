@@ -0,0 +1,6 @@
+// sealed_marker! 生成的令牌同样不能绕过 new() 直接构造
+use sealed_trait_example::macro_approach::AudioThreadToken;
+
+fn main() {
+    let _forged = AudioThreadToken { _private: () };
+}
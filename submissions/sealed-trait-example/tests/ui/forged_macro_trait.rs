@@ -0,0 +1,8 @@
+// sealed_marker! 生成的 trait 同样不能在外部实现
+use sealed_trait_example::macro_approach::AudioThread;
+
+struct FakeAudioToken;
+
+impl AudioThread for FakeAudioToken {}
+
+fn main() {}
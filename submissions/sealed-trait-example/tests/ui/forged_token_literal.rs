@@ -0,0 +1,6 @@
+// 外部 crate 不能绕过 new() 直接构造令牌：`_private` 字段不可见
+use sealed_trait_example::sealed_approach::RenderThreadToken;
+
+fn main() {
+    let _forged = RenderThreadToken { _private: () };
+}
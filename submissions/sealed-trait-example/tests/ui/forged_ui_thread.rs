@@ -0,0 +1,8 @@
+// 外部 crate 不能为自己的类型实现 UiThread：拿不到 private::Sealed
+use sealed_trait_example::sealed_approach::UiThread;
+
+struct FakeThreadToken;
+
+impl UiThread for FakeThreadToken {}
+
+fn main() {}
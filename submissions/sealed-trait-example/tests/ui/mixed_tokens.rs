@@ -0,0 +1,10 @@
+// 主线程令牌不能调用渲染线程专属函数，反之亦然
+use sealed_trait_example::sealed_approach::{main_only, render_only, MainThreadToken, RenderThreadToken};
+
+fn main() {
+    let main_token = MainThreadToken::new();
+    render_only(&main_token);
+
+    let render = RenderThreadToken::new();
+    main_only(&render, "room", "Room");
+}
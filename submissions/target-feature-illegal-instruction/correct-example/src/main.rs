@@ -1,4 +1,5 @@
 use std::hint::black_box;
+use std::sync::OnceLock;
 
 fn main() {
     let lhs = black_box([1.0f32, 2.0, 3.0, 4.0, 5.0, 6.0, 7.0, 8.0]);
@@ -7,25 +8,228 @@ fn main() {
     // Runtime feature detection keeps the binary portable across CPUs with
     // different SIMD capability levels.
     let dot = dot_product(&lhs, &rhs);
-    println!("dot_product: {dot:.4}");
+    println!("dot_product ({}): {dot:.4}", dot_product_feature());
+
+    let long_lhs: Vec<f32> = black_box((0..21).map(|i| i as f32 * 0.25).collect());
+    let long_rhs: Vec<f32> = black_box((0..21).map(|i| (i % 4) as f32 - 1.5).collect());
+    let dot = dot_product_slice(&long_lhs, &long_rhs);
+    println!("dot_product_slice (len 21): {dot:.4}");
+
+    let wide_lhs: Vec<f64> = black_box((0..11).map(|i| i as f64 / 3.0).collect());
+    let wide_rhs: Vec<f64> = black_box((0..11).map(|i| 1.0 - i as f64 / 8.0).collect());
+    let dot = dot_product_f64(&wide_lhs, &wide_rhs);
+    println!("dot_product_f64 (len 11): {dot:.6}");
+
+    let rows = black_box([lhs, rhs, [1.0; 8]]);
+    let mut scores = [0.0f32; 3];
+    dot_products(&rows, &rhs, &mut scores);
+    println!("dot_products: {scores:?}");
+}
+
+/// Signature shared by every dot-product implementation. SIMD paths are
+/// `unsafe` because they must only run on CPUs with their target feature.
+type DotFn = unsafe fn(&[f32; 8], &[f32; 8]) -> f32;
+
+/// A SIMD implementation of `DotFn` and the runtime check for its feature.
+struct DotPath {
+    feature: &'static str,
+    detected: fn() -> bool,
+    path: DotFn,
+}
+
+/// Every SIMD path for this architecture, widest first.
+const DOT_PRODUCT_PATHS: &[DotPath] = &[
+    #[cfg(target_arch = "x86_64")]
+    DotPath {
+        feature: "avx512f",
+        detected: || std::is_x86_feature_detected!("avx512f"),
+        path: simd::dot_product_avx512,
+    },
+    #[cfg(target_arch = "x86_64")]
+    DotPath {
+        feature: "avx2",
+        detected: || std::is_x86_feature_detected!("avx2"),
+        path: simd::dot_product_avx2,
+    },
+    #[cfg(target_arch = "x86_64")]
+    DotPath {
+        feature: "sse4.1",
+        detected: || std::is_x86_feature_detected!("sse4.1"),
+        path: simd::dot_product_sse41,
+    },
+    #[cfg(target_arch = "aarch64")]
+    DotPath {
+        feature: "neon",
+        detected: || std::arch::is_aarch64_feature_detected!("neon"),
+        path: neon::dot_product_neon,
+    },
+];
+
+/// Picks the widest implementation the running CPU supports.
+fn resolve_dot_product() -> DotFn {
+    DOT_PRODUCT_PATHS
+        .iter()
+        .find(|candidate| (candidate.detected)())
+        .map_or(dot_product_scalar, |candidate| candidate.path)
+}
+
+/// The implementation chosen for this process, resolved on first use.
+fn dot_product_impl() -> DotFn {
+    static DISPATCH: OnceLock<DotFn> = OnceLock::new();
+    *DISPATCH.get_or_init(resolve_dot_product)
+}
+
+/// Feature behind the cached path, or `"scalar"` for the fallback.
+fn dot_product_feature() -> &'static str {
+    let chosen = dot_product_impl();
+    DOT_PRODUCT_PATHS
+        .iter()
+        .find(|candidate| std::ptr::fn_addr_eq(candidate.path, chosen))
+        .map_or("scalar", |candidate| candidate.feature)
 }
 
 #[inline(always)]
 fn dot_product(lhs: &[f32; 8], rhs: &[f32; 8]) -> f32 {
+    // SAFETY: resolve_dot_product only returns a SIMD path after detecting
+    // its feature on the running CPU.
+    unsafe { dot_product_impl()(lhs, rhs) }
+}
+
+/// Slice counterpart of `DotFn`; callers must pass equal-length slices.
+type DotSliceFn = unsafe fn(&[f32], &[f32]) -> f32;
+
+fn resolve_dot_product_slice() -> DotSliceFn {
+    #[cfg(target_arch = "x86_64")]
+    {
+        if std::is_x86_feature_detected!("avx2") && std::is_x86_feature_detected!("fma") {
+            return simd::dot_product_slice_fma;
+        }
+    }
+
+    dot_product_slice_chunked
+}
+
+/// Dot product of two equal-length slices.
+///
+/// With FMA the multiply and add are fused into a single rounding, so the
+/// result can differ from the non-FMA path in the last few bits; both stay
+/// within `1e-5 * sum(|lhs[i] * rhs[i]|)` of the scalar reference.
+///
+/// # Panics
+///
+/// Panics if the slices have different lengths.
+fn dot_product_slice(lhs: &[f32], rhs: &[f32]) -> f32 {
+    static DISPATCH: OnceLock<DotSliceFn> = OnceLock::new();
+    assert_eq!(lhs.len(), rhs.len(), "dot_product_slice: length mismatch");
+
+    let dot = *DISPATCH.get_or_init(resolve_dot_product_slice);
+    // SAFETY: the FMA path is only returned after detecting AVX2 and FMA, and
+    // the lengths were checked above.
+    unsafe { dot(lhs, rhs) }
+}
+
+/// Full 8-wide chunks through the dispatched `dot_product`, the tail in scalar.
+fn dot_product_slice_chunked(lhs: &[f32], rhs: &[f32]) -> f32 {
+    let dot = dot_product_impl();
+    let lhs_chunks = lhs.chunks_exact(8);
+    let rhs_chunks = rhs.chunks_exact(8);
+    let tail: f32 = lhs_chunks
+        .remainder()
+        .iter()
+        .zip(rhs_chunks.remainder())
+        .map(|(l, r)| l * r)
+        .sum();
+
+    lhs_chunks
+        .zip(rhs_chunks)
+        .map(|(l, r)| {
+            let (l, r) = (l.try_into().unwrap(), r.try_into().unwrap());
+            // SAFETY: see dot_product
+            unsafe { dot(l, r) }
+        })
+        .sum::<f32>()
+        + tail
+}
+
+/// Batched counterpart of `DotFn`; callers must pass `out.len() == rows.len()`.
+type DotBatchFn = unsafe fn(&[[f32; 8]], &[f32; 8], &mut [f32]);
+
+fn resolve_dot_products() -> DotBatchFn {
     #[cfg(target_arch = "x86_64")]
     {
         if std::is_x86_feature_detected!("avx2") {
-            return unsafe { simd::dot_product_avx2(lhs, rhs) };
+            return simd::dot_products_avx2;
         }
     }
 
-    dot_product_scalar(lhs, rhs)
+    dot_products_generic
+}
+
+/// Writes `dot(rows[i], weights)` into `out[i]` for every row, resolving the
+/// implementation once per process and reusing the loaded weights across rows.
+///
+/// # Panics
+///
+/// Panics if `out` and `rows` have different lengths.
+fn dot_products(rows: &[[f32; 8]], weights: &[f32; 8], out: &mut [f32]) {
+    static DISPATCH: OnceLock<DotBatchFn> = OnceLock::new();
+    assert_eq!(
+        rows.len(),
+        out.len(),
+        "dot_products: output length mismatch"
+    );
+
+    let batch = *DISPATCH.get_or_init(resolve_dot_products);
+    // SAFETY: the AVX2 batch is only returned after detecting AVX2, and the
+    // lengths were checked above.
+    unsafe { batch(rows, weights, out) }
+}
+
+fn dot_products_generic(rows: &[[f32; 8]], weights: &[f32; 8], out: &mut [f32]) {
+    let dot = dot_product_impl();
+    for (row, slot) in rows.iter().zip(out) {
+        // SAFETY: see dot_product
+        *slot = unsafe { dot(row, weights) };
+    }
 }
 
 fn dot_product_scalar(lhs: &[f32; 8], rhs: &[f32; 8]) -> f32 {
     lhs.iter().zip(rhs.iter()).map(|(l, r)| l * r).sum()
 }
 
+/// Double-precision counterpart of `DotFn`, over equal-length slices.
+type DotF64Fn = unsafe fn(&[f64], &[f64]) -> f64;
+
+fn resolve_dot_product_f64() -> DotF64Fn {
+    #[cfg(target_arch = "x86_64")]
+    {
+        if std::is_x86_feature_detected!("avx2") {
+            return simd::dot_product_f64_avx2;
+        }
+    }
+
+    dot_product_f64_scalar
+}
+
+/// Double-precision dot product of two equal-length slices.
+///
+/// # Panics
+///
+/// Panics if the slices have different lengths.
+fn dot_product_f64(lhs: &[f64], rhs: &[f64]) -> f64 {
+    static DISPATCH: OnceLock<DotF64Fn> = OnceLock::new();
+    assert_eq!(lhs.len(), rhs.len(), "dot_product_f64: length mismatch");
+
+    let dot = *DISPATCH.get_or_init(resolve_dot_product_f64);
+    // SAFETY: resolve_dot_product_f64 only returns the AVX2 path after
+    // detecting AVX2, and the lengths were checked above.
+    unsafe { dot(lhs, rhs) }
+}
+
+fn dot_product_f64_scalar(lhs: &[f64], rhs: &[f64]) -> f64 {
+    lhs.iter().zip(rhs).map(|(l, r)| l * r).sum()
+}
+
 #[cfg(target_arch = "x86_64")]
 mod simd {
     use std::arch::x86_64::*;
@@ -38,6 +242,95 @@ mod simd {
         horizontal_sum(mul)
     }
 
+    /// Pre-AVX2 hardware: two 4-lane halves, each reduced by SSE4.1's `dpps`.
+    ///
+    /// The dispatcher only reaches this on CPUs without AVX2. To benchmark it on
+    /// a newer machine, call it directly (after checking `sse4.1`) instead of
+    /// going through `dot_product`; compile-time `-C target-feature` flags do
+    /// not change what the runtime detection reports.
+    #[target_feature(enable = "sse4.1")]
+    pub unsafe fn dot_product_sse41(lhs: &[f32; 8], rhs: &[f32; 8]) -> f32 {
+        // 0xf1: multiply all four lanes, write the sum to lane 0
+        const ALL_LANES_TO_FIRST: i32 = 0xf1;
+        let low =
+            _mm_dp_ps::<ALL_LANES_TO_FIRST>(_mm_loadu_ps(lhs.as_ptr()), _mm_loadu_ps(rhs.as_ptr()));
+        let high = _mm_dp_ps::<ALL_LANES_TO_FIRST>(
+            _mm_loadu_ps(lhs.as_ptr().add(4)),
+            _mm_loadu_ps(rhs.as_ptr().add(4)),
+        );
+        _mm_cvtss_f32(_mm_add_ss(low, high))
+    }
+
+    /// All eight lanes fit in the lower half of a 512-bit register, so a
+    /// masked load zeroes the upper half and one multiply covers the input.
+    #[target_feature(enable = "avx512f")]
+    pub unsafe fn dot_product_avx512(lhs: &[f32; 8], rhs: &[f32; 8]) -> f32 {
+        const LOW_EIGHT: __mmask16 = 0x00ff;
+        let a = _mm512_maskz_loadu_ps(LOW_EIGHT, lhs.as_ptr());
+        let b = _mm512_maskz_loadu_ps(LOW_EIGHT, rhs.as_ptr());
+        let mul = _mm512_mul_ps(a, b);
+        _mm512_reduce_add_ps(mul)
+    }
+
+    /// Loads `weights` into a register once and reuses it for every row.
+    #[target_feature(enable = "avx2")]
+    pub unsafe fn dot_products_avx2(rows: &[[f32; 8]], weights: &[f32; 8], out: &mut [f32]) {
+        let w = _mm256_loadu_ps(weights.as_ptr());
+        for (row, slot) in rows.iter().zip(out) {
+            let mul = _mm256_mul_ps(_mm256_loadu_ps(row.as_ptr()), w);
+            *slot = horizontal_sum(mul);
+        }
+    }
+
+    /// Eight lanes per step with `_mm256_fmadd_ps`, so each multiply-add
+    /// rounds once instead of twice. Callers must pass equal-length slices.
+    #[target_feature(enable = "avx2,fma")]
+    pub unsafe fn dot_product_slice_fma(lhs: &[f32], rhs: &[f32]) -> f32 {
+        let lhs_chunks = lhs.chunks_exact(8);
+        let rhs_chunks = rhs.chunks_exact(8);
+        let tail: f32 = lhs_chunks
+            .remainder()
+            .iter()
+            .zip(rhs_chunks.remainder())
+            .map(|(l, r)| l * r)
+            .sum();
+
+        let mut acc = _mm256_setzero_ps();
+        for (l, r) in lhs_chunks.zip(rhs_chunks) {
+            let a = _mm256_loadu_ps(l.as_ptr());
+            let b = _mm256_loadu_ps(r.as_ptr());
+            acc = _mm256_fmadd_ps(a, b, acc);
+        }
+        horizontal_sum(acc) + tail
+    }
+
+    /// Four `f64` lanes per step, with the 0–3 element tail done in scalar.
+    /// Callers must pass slices of equal length.
+    #[target_feature(enable = "avx2")]
+    pub unsafe fn dot_product_f64_avx2(lhs: &[f64], rhs: &[f64]) -> f64 {
+        let lhs_chunks = lhs.chunks_exact(4);
+        let rhs_chunks = rhs.chunks_exact(4);
+        let tail: f64 = lhs_chunks
+            .remainder()
+            .iter()
+            .zip(rhs_chunks.remainder())
+            .map(|(l, r)| l * r)
+            .sum();
+
+        let mut acc = _mm256_setzero_pd();
+        for (l, r) in lhs_chunks.zip(rhs_chunks) {
+            let a = _mm256_loadu_pd(l.as_ptr());
+            let b = _mm256_loadu_pd(r.as_ptr());
+            acc = _mm256_add_pd(acc, _mm256_mul_pd(a, b));
+        }
+
+        let high = _mm256_extractf128_pd(acc, 1);
+        let low = _mm256_castpd256_pd128(acc);
+        let pair = _mm_add_pd(high, low);
+        let sum = _mm_add_sd(pair, _mm_unpackhi_pd(pair, pair));
+        _mm_cvtsd_f64(sum) + tail
+    }
+
     #[inline(always)]
     unsafe fn horizontal_sum(v: __m256) -> f32 {
         let high = _mm256_extractf128_ps(v, 1);
@@ -50,3 +343,214 @@ mod simd {
         _mm_cvtss_f32(sums)
     }
 }
+
+#[cfg(target_arch = "aarch64")]
+mod neon {
+    use std::arch::aarch64::*;
+
+    #[target_feature(enable = "neon")]
+    pub unsafe fn dot_product_neon(lhs: &[f32; 8], rhs: &[f32; 8]) -> f32 {
+        let low = vmulq_f32(vld1q_f32(lhs.as_ptr()), vld1q_f32(rhs.as_ptr()));
+        let high = vmulq_f32(
+            vld1q_f32(lhs.as_ptr().add(4)),
+            vld1q_f32(rhs.as_ptr().add(4)),
+        );
+        vaddvq_f32(vaddq_f32(low, high))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Dyadic inputs keep every product and partial sum exact, so all paths
+    // must agree bit-for-bit regardless of summation order.
+    const LHS: [f32; 8] = [1.0, 2.0, 3.0, 4.0, 5.0, 6.0, 7.0, 8.0];
+    const RHS: [f32; 8] = [0.5, 1.5, -2.0, 3.25, 4.75, -5.5, 6.125, 7.875];
+
+    #[test]
+    #[cfg(target_arch = "x86_64")]
+    fn sse41_path_matches_scalar() {
+        if !std::is_x86_feature_detected!("sse4.1") {
+            return;
+        }
+        let rhs = [-1.25, 0.0, 2.5, -3.0, 0.125, 9.0, -0.75, 4.0];
+        assert_eq!(
+            unsafe { simd::dot_product_sse41(&LHS, &rhs) },
+            dot_product_scalar(&LHS, &rhs)
+        );
+    }
+
+    #[test]
+    #[cfg(target_arch = "aarch64")]
+    fn neon_path_matches_scalar() {
+        if !std::arch::is_aarch64_feature_detected!("neon") {
+            return;
+        }
+        assert_eq!(
+            unsafe { neon::dot_product_neon(&LHS, &RHS) },
+            dot_product_scalar(&LHS, &RHS)
+        );
+    }
+
+    fn slice_inputs(len: usize) -> (Vec<f32>, Vec<f32>) {
+        let lhs = (0..len).map(|i| (i % 7) as f32 - 3.0).collect();
+        let rhs = (0..len).map(|i| (i % 5) as f32 * 0.5).collect();
+        (lhs, rhs)
+    }
+
+    fn scalar_reference(lhs: &[f32], rhs: &[f32]) -> f32 {
+        lhs.iter().zip(rhs).map(|(l, r)| l * r).sum()
+    }
+
+    #[test]
+    fn slice_exact_multiple_of_eight() {
+        for len in [8, 64] {
+            let (lhs, rhs) = slice_inputs(len);
+            assert_eq!(dot_product_slice(&lhs, &rhs), scalar_reference(&lhs, &rhs));
+        }
+    }
+
+    #[test]
+    fn slice_with_remainder() {
+        for len in [3, 13, 37] {
+            let (lhs, rhs) = slice_inputs(len);
+            assert_eq!(dot_product_slice(&lhs, &rhs), scalar_reference(&lhs, &rhs));
+        }
+    }
+
+    #[test]
+    fn empty_slice_is_zero() {
+        assert_eq!(dot_product_slice(&[], &[]), 0.0);
+    }
+
+    #[test]
+    #[should_panic(expected = "length mismatch")]
+    fn slice_length_mismatch_panics() {
+        dot_product_slice(&[1.0; 9], &[1.0; 8]);
+    }
+
+    fn f64_inputs(len: usize) -> (Vec<f64>, Vec<f64>) {
+        let lhs = (0..len).map(|i| (i as f64 * 0.37).sin()).collect();
+        let rhs = (0..len).map(|i| (i as f64 * 1.13).cos() * 2.5).collect();
+        (lhs, rhs)
+    }
+
+    #[test]
+    fn f64_matches_scalar_within_epsilon() {
+        // 4-lane multiples and lengths that leave a 1..3 element tail
+        for len in [0, 4, 7, 16, 33, 1001] {
+            let (lhs, rhs) = f64_inputs(len);
+            let expected = dot_product_f64_scalar(&lhs, &rhs);
+            let actual = dot_product_f64(&lhs, &rhs);
+            assert!(
+                (actual - expected).abs() <= 1e-12 * expected.abs().max(1.0),
+                "len {len}: {actual} vs {expected}"
+            );
+        }
+    }
+
+    #[test]
+    #[cfg(target_arch = "x86_64")]
+    fn f64_avx2_path_handles_remainder() {
+        if !std::is_x86_feature_detected!("avx2") {
+            return;
+        }
+        let (lhs, rhs) = f64_inputs(10);
+        let simd = unsafe { simd::dot_product_f64_avx2(&lhs, &rhs) };
+        let scalar = dot_product_f64_scalar(&lhs, &rhs);
+        assert!((simd - scalar).abs() <= 1e-12, "{simd} vs {scalar}");
+    }
+
+    /// Relative error bound documented on `dot_product_slice`.
+    const FMA_TOLERANCE: f32 = 1e-5;
+
+    #[test]
+    fn slice_paths_within_tolerance_of_scalar() {
+        #[cfg(target_arch = "x86_64")]
+        let has_fma = std::is_x86_feature_detected!("avx2") && std::is_x86_feature_detected!("fma");
+
+        for len in [8, 29, 1000] {
+            let lhs: Vec<f32> = (0..len).map(|i| (i as f32 * 0.31).sin()).collect();
+            let rhs: Vec<f32> = (0..len).map(|i| (i as f32 * 0.77).cos()).collect();
+            let tolerance = FMA_TOLERANCE
+                * lhs
+                    .iter()
+                    .zip(&rhs)
+                    .map(|(l, r)| (l * r).abs())
+                    .sum::<f32>();
+            let expected = scalar_reference(&lhs, &rhs);
+
+            let dispatched = dot_product_slice(&lhs, &rhs);
+            assert!(
+                (dispatched - expected).abs() <= tolerance,
+                "len {len}: {dispatched} vs {expected}"
+            );
+
+            #[cfg(target_arch = "x86_64")]
+            if has_fma {
+                let fma = unsafe { simd::dot_product_slice_fma(&lhs, &rhs) };
+                assert!(
+                    (fma - expected).abs() <= tolerance,
+                    "len {len}: fma {fma} vs {expected}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn batched_rows_match_single_row_scalar() {
+        let rows = [
+            LHS,
+            RHS,
+            [0.0; 8],
+            [1.0, -1.0, 1.0, -1.0, 1.0, -1.0, 1.0, -1.0],
+            [0.25, 0.5, 0.75, 1.0, 1.25, 1.5, 1.75, 2.0],
+        ];
+        let mut out = [f32::NAN; 5];
+        dot_products(&rows, &RHS, &mut out);
+
+        for (row, actual) in rows.iter().zip(out) {
+            assert_eq!(actual, dot_product_scalar(row, &RHS));
+        }
+
+        #[cfg(target_arch = "x86_64")]
+        if std::is_x86_feature_detected!("avx2") {
+            let mut avx2_out = [f32::NAN; 5];
+            unsafe { simd::dot_products_avx2(&rows, &RHS, &mut avx2_out) };
+            assert_eq!(avx2_out, out);
+        }
+    }
+
+    #[test]
+    fn dispatch_caches_top_detected_path() {
+        let resolved = dot_product_impl();
+        assert!(std::ptr::fn_addr_eq(resolved, dot_product_impl()));
+
+        match DOT_PRODUCT_PATHS
+            .iter()
+            .find(|candidate| (candidate.detected)())
+        {
+            Some(top) => assert!(
+                std::ptr::fn_addr_eq(resolved, top.path),
+                "expected the {} path",
+                top.feature
+            ),
+            None => assert!(std::ptr::fn_addr_eq(resolved, dot_product_scalar as DotFn)),
+        }
+    }
+
+    #[test]
+    fn available_paths_match_scalar() {
+        let expected = dot_product_scalar(&LHS, &RHS);
+        assert_eq!(dot_product(&LHS, &RHS), expected);
+
+        for candidate in DOT_PRODUCT_PATHS {
+            if (candidate.detected)() {
+                // SAFETY: only called after its feature was detected
+                let value = unsafe { (candidate.path)(&LHS, &RHS) };
+                assert_eq!(value, expected, "{} path", candidate.feature);
+            }
+        }
+    }
+}
@@ -19,19 +19,38 @@
 //! 4. **Clear API Contracts**: Function signatures communicate thread requirements
 
 use std::{
-    cell::RefCell,
+    cell::{Cell, RefCell},
     collections::HashMap,
-    rc::Rc,
-    thread,
+    marker::PhantomData,
+    rc::{Rc, Weak},
+    sync::OnceLock,
+    thread::{self, ThreadId},
     time::Duration,
 };
 
 // ✅ SOLUTION 1: Use RefCell (not Mutex) for single-threaded interior mutability
 // RefCell is cheaper than Mutex and clearly signals single-threaded access
 thread_local! {
-    static ROOM_CACHE: Rc<RefCell<HashMap<String, RoomData>>> = Rc::new(RefCell::new(HashMap::new()));
+    static ROOM_CACHE: UiCache<RoomData> = UiCache::new();
+    // `None` means the running total may be stale and must be recomputed
+    static TOTAL_UNREAD: Cell<Option<u32>> = const { Cell::new(Some(0)) };
+    static ROOM_SUBSCRIBERS: RefCell<Vec<RoomCallback>> = const { RefCell::new(Vec::new()) };
+    // The map behind the last `get_room_cache` handle, to tell whether any handle is still alive
+    static RAW_ROOM_CACHE: RefCell<Weak<RefCell<HashMap<String, RoomData>>>> =
+        const { RefCell::new(Weak::new()) };
 }
 
+/// A change to the room cache, delivered to [`subscribe`]d callbacks.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum RoomEvent {
+    Added { room_id: String },
+    Updated { room_id: String },
+    Removed { room_id: String },
+}
+
+/// Callback invoked on every [`RoomEvent`]; `Rc` because it never leaves the UI thread.
+pub type RoomCallback = Rc<dyn Fn(&RoomEvent)>;
+
 #[derive(Clone, Debug)]
 pub struct RoomData {
     pub id: String,
@@ -43,64 +62,290 @@ pub struct RoomData {
 // This type is !Send + !Sync, so it cannot be sent to other threads
 #[derive(Clone)]
 pub struct UiContext {
-    // PhantomData could be used here, but we keep it simple
-    _private: (),
+    // Rc is !Send + !Sync, so a context created on the UI thread stays there
+    _not_send: PhantomData<Rc<()>>,
 }
 
+/// The thread that first obtained a context through [`UiContext::try_new`].
+static UI_THREAD: OnceLock<ThreadId> = OnceLock::new();
+
 impl UiContext {
     /// Creates a new UiContext.
     ///
     /// IMPORTANT: This should only be called once at the start of the UI thread.
     /// In a real application, this would be created by the UI framework
-    /// and passed down through the call stack.
+    /// and passed down through the call stack. Prefer [`UiContext::try_new`],
+    /// which checks the calling thread.
     pub fn new() -> Self {
-        UiContext { _private: () }
+        UiContext { _not_send: PhantomData }
+    }
+
+    /// Creates a UiContext only if called from the UI thread.
+    ///
+    /// The first successful call records the current thread as the UI thread;
+    /// calls from any other thread afterwards return `None` instead of handing
+    /// out a witness for a *different* thread_local store.
+    pub fn try_new() -> Option<Self> {
+        let current = thread::current().id();
+        (*UI_THREAD.get_or_init(|| current) == current).then(Self::new)
+    }
+}
+
+impl Default for UiContext {
+    fn default() -> Self {
+        Self::new()
     }
 }
 
-// ✅ SOLUTION 3: All UI-thread-only functions require UiContext
+mod private {
+    // Only this crate can name `Sealed`, so only this crate can implement it
+    pub trait Sealed {}
+
+    impl Sealed for super::UiContext {}
+}
+
+/// Proof that the caller is on a UI-side thread.
+///
+/// The trait is sealed: `UiContext` is the only implementor today, and future
+/// markers (e.g. a render-thread token) must be added inside this crate.
+pub trait UiThread: private::Sealed {}
+
+impl UiThread for UiContext {}
+
+/// A `String`-keyed store for UI-thread-only state, gated on a [`UiThread`] proof.
+///
+/// The same witness pattern protects rooms, drafts, typing indicators, etc.
+/// The `Rc` inside makes the cache `!Send`, so it can't leave its thread.
+pub struct UiCache<V> {
+    entries: Rc<RefCell<HashMap<String, V>>>,
+}
+
+impl<V> UiCache<V> {
+    pub fn new() -> Self {
+        UiCache { entries: Rc::new(RefCell::new(HashMap::new())) }
+    }
+
+    /// Inserts `value`, returning the previous value for `key` if any.
+    pub fn insert(&self, _ui: &impl UiThread, key: String, value: V) -> Option<V> {
+        self.entries.borrow_mut().insert(key, value)
+    }
+
+    pub fn get(&self, _ui: &impl UiThread, key: &str) -> Option<V>
+    where
+        V: Clone,
+    {
+        self.entries.borrow().get(key).cloned()
+    }
+
+    /// Applies `f` to the value for `key`, returning whether it was present.
+    pub fn update(&self, _ui: &impl UiThread, key: &str, f: impl FnOnce(&mut V)) -> bool {
+        match self.entries.borrow_mut().get_mut(key) {
+            Some(value) => {
+                f(value);
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn remove(&self, _ui: &impl UiThread, key: &str) -> Option<V> {
+        self.entries.borrow_mut().remove(key)
+    }
+
+    pub fn clear(&self, _ui: &impl UiThread) {
+        self.entries.borrow_mut().clear();
+    }
+
+    pub fn len(&self, _ui: &impl UiThread) -> usize {
+        self.entries.borrow().len()
+    }
+
+    pub fn is_empty(&self, ui: &impl UiThread) -> bool {
+        self.len(ui) == 0
+    }
+
+    /// Calls `f` for every value while holding a single shared borrow.
+    pub fn for_each(&self, _ui: &impl UiThread, f: impl FnMut(&V)) {
+        self.entries.borrow().values().for_each(f);
+    }
+
+    /// Returns a clone of every value, releasing the borrow before returning.
+    pub fn values(&self, _ui: &impl UiThread) -> Vec<V>
+    where
+        V: Clone,
+    {
+        self.entries.borrow().values().cloned().collect()
+    }
+
+    /// Returns the shared map backing this cache.
+    pub fn shared(&self, _ui: &impl UiThread) -> Rc<RefCell<HashMap<String, V>>> {
+        Rc::clone(&self.entries)
+    }
+}
+
+impl<V> Default for UiCache<V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// ✅ SOLUTION 3: All UI-thread-only functions require a sealed UiThread proof
 // The type system enforces that these can only be called with a valid UiContext
-pub fn add_room(_ui: &UiContext, room: RoomData) {
-    ROOM_CACHE.with(|cache| {
-        cache.borrow_mut().insert(room.id.clone(), room);
+pub fn add_room(ui: &impl UiThread, room: RoomData) {
+    let room_id = room.id.clone();
+    let unread = room.unread_count;
+    let previous = ROOM_CACHE.with(|cache| {
+        cache.insert(ui, room.id.clone(), room)
     });
+    // Re-adding an existing room replaces it, which is an update to subscribers
+    let event = match &previous {
+        Some(_) => RoomEvent::Updated { room_id },
+        None => RoomEvent::Added { room_id },
+    };
+    adjust_total_unread(previous.map_or(0, |room| room.unread_count), unread);
+    notify(ui, event);
 }
 
-pub fn get_room(_ui: &UiContext, room_id: &str) -> Option<RoomData> {
+pub fn get_room(ui: &impl UiThread, room_id: &str) -> Option<RoomData> {
     ROOM_CACHE.with(|cache| {
-        cache.borrow().get(room_id).cloned()
+        cache.get(ui, room_id)
     })
 }
 
-pub fn update_unread_count(_ui: &UiContext, room_id: &str, count: u32) {
-    ROOM_CACHE.with(|cache| {
-        if let Some(room) = cache.borrow_mut().get_mut(room_id) {
+pub fn update_unread_count(ui: &impl UiThread, room_id: &str, count: u32) {
+    let mut previous = 0;
+    let updated = ROOM_CACHE.with(|cache| {
+        cache.update(ui, room_id, |room| {
+            previous = room.unread_count;
             room.unread_count = count;
+        })
+    });
+    if updated {
+        adjust_total_unread(previous, count);
+        notify(ui, RoomEvent::Updated { room_id: room_id.to_string() });
+    }
+}
+
+/// Removes a room from the cache, returning it if it was present.
+pub fn remove_room(ui: &impl UiThread, room_id: &str) -> Option<RoomData> {
+    let removed = ROOM_CACHE.with(|cache| {
+        cache.remove(ui, room_id)
+    });
+    if let Some(room) = &removed {
+        adjust_total_unread(room.unread_count, 0);
+        notify(ui, RoomEvent::Removed { room_id: room_id.to_string() });
+    }
+    removed
+}
+
+/// Returns the number of rooms in the UI thread's cache.
+pub fn room_count(ui: &impl UiThread) -> usize {
+    ROOM_CACHE.with(|cache| {
+        cache.len(ui)
+    })
+}
+
+/// Returns the sum of `unread_count` across all rooms, e.g. for the app badge.
+///
+/// This is O(1) in the common case: the total is kept up to date by `add_room`,
+/// `update_unread_count`, `remove_room`, and `clear_all_rooms`. Once
+/// [`get_room_cache`] has handed out a handle, writes may bypass the running
+/// total, so it is recomputed from the cache until that handle is dropped.
+pub fn total_unread(ui: &impl UiThread) -> u32 {
+    // The cache itself holds one strong reference; any other is a raw handle
+    let shared = RAW_ROOM_CACHE.with(|raw| raw.borrow().strong_count() > 1);
+    match TOTAL_UNREAD.with(Cell::get) {
+        Some(total) if !shared => total,
+        _ => {
+            let mut sum = 0u32;
+            for_each_room(ui, |room| sum = sum.saturating_add(room.unread_count));
+            TOTAL_UNREAD.with(|total| total.set((!shared).then_some(sum)));
+            sum
+        }
+    }
+}
+
+/// Replaces `old` with `new` in the running unread total.
+///
+/// If the total no longer covers `old` (or would overflow), it is marked
+/// stale instead, and the next [`total_unread`] call recomputes it.
+fn adjust_total_unread(old: u32, new: u32) {
+    TOTAL_UNREAD.with(|total| {
+        if let Some(current) = total.get() {
+            total.set(current.checked_sub(old).and_then(|rest| rest.checked_add(new)));
         }
     });
 }
 
+/// Calls `f` for every room while holding a single shared borrow.
+///
+/// The borrow never escapes this function, so callers can't accidentally hold
+/// it across a later `borrow_mut()`. `f` must not mutate the cache itself.
+pub fn for_each_room(ui: &impl UiThread, f: impl FnMut(&RoomData)) {
+    ROOM_CACHE.with(|cache| {
+        cache.for_each(ui, f);
+    });
+}
+
+/// Returns a snapshot of every room, releasing the borrow before returning.
+pub fn collect_rooms(ui: &impl UiThread) -> Vec<RoomData> {
+    ROOM_CACHE.with(|cache| {
+        cache.values(ui)
+    })
+}
+
+/// Registers `f` to be called after every change to the room cache.
+///
+/// Subscribers live in UI-thread-local storage, so no locking is needed.
+pub fn subscribe(_ui: &impl UiThread, f: RoomCallback) {
+    ROOM_SUBSCRIBERS.with(|subscribers| {
+        subscribers.borrow_mut().push(f);
+    });
+}
+
+/// Delivers `event` to every subscriber.
+///
+/// The cache borrow is already released and the subscriber list is cloned
+/// first, so callbacks may read the cache or subscribe again without panicking.
+fn notify(_ui: &impl UiThread, event: RoomEvent) {
+    let subscribers = ROOM_SUBSCRIBERS.with(|subscribers| subscribers.borrow().clone());
+    for subscriber in subscribers {
+        subscriber(&event);
+    }
+}
+
 /// Returns an Rc clone of the room cache for the current thread.
 ///
 /// This function also requires UiContext, ensuring it's only called from the UI thread.
-pub fn get_room_cache(_ui: &UiContext) -> Rc<RefCell<HashMap<String, RoomData>>> {
-    ROOM_CACHE.with(|cache| Rc::clone(cache))
+/// Writes made through the returned handle bypass [`subscribe`]d callbacks
+/// and force [`total_unread`] to recompute; prefer the functions above.
+pub fn get_room_cache(ui: &impl UiThread) -> Rc<RefCell<HashMap<String, RoomData>>> {
+    TOTAL_UNREAD.with(|total| total.set(None));
+    let handle = ROOM_CACHE.with(|cache| cache.shared(ui));
+    RAW_ROOM_CACHE.with(|raw| *raw.borrow_mut() = Rc::downgrade(&handle));
+    handle
 }
 
 /// Clears all rooms from the cache.
 ///
 /// This function requires UiContext, making it clear that it affects UI-thread-local state.
-pub fn clear_all_rooms(_ui: &UiContext) {
+pub fn clear_all_rooms(ui: &impl UiThread) {
     ROOM_CACHE.with(|cache| {
-        cache.borrow_mut().clear();
+        cache.clear(ui);
     });
+    TOTAL_UNREAD.with(|total| total.set(Some(0)));
 }
 
 fn simulate_ui_thread() {
     println!("[UI Thread] Starting...");
 
-    // ✅ Create the UiContext witness - only possible on this thread
-    let ui = UiContext::new();
+    // ✅ Create the UiContext witness - this thread becomes the UI thread
+    let ui = UiContext::try_new().expect("first caller becomes the UI thread");
+
+    // Redraw whenever room data changes
+    subscribe(&ui, Rc::new(|event: &RoomEvent| {
+        println!("[UI Thread] Redraw requested: {:?}", event);
+    }));
 
     // Add some rooms using the UI context
     add_room(&ui, RoomData {
@@ -130,6 +375,30 @@ fn simulate_ui_thread() {
     if let Some(room) = get_room(&ui, "room1") {
         println!("[UI Thread] Final room state: {:?}", room);
     }
+
+    // Render the whole room list
+    for_each_room(&ui, |room| {
+        println!("[UI Thread] - {} ({} unread)", room.name, room.unread_count);
+    });
+
+    // Leave a room
+    if let Some(room) = remove_room(&ui, "room2") {
+        println!("[UI Thread] Left room: {}", room.name);
+    }
+    println!("[UI Thread] Rooms remaining: {}", room_count(&ui));
+
+    // A snapshot can be held freely while the cache is mutated again
+    for room in collect_rooms(&ui) {
+        update_unread_count(&ui, &room.id, 0);
+    }
+    println!("[UI Thread] Marked all rooms as read (badge: {})", total_unread(&ui));
+
+    // The same witness-gated cache protects other UI-only state, like drafts
+    let drafts: UiCache<String> = UiCache::new();
+    drafts.insert(&ui, "room1".to_string(), "See you tomorrow".to_string());
+    if let Some(draft) = drafts.get(&ui, "room1") {
+        println!("[UI Thread] Draft for room1: {:?}", draft);
+    }
 }
 
 // ✅ SOLUTION 4: Background threads CANNOT call UI functions
@@ -149,6 +418,8 @@ fn simulate_background_thread() {
     //
     // The thread_local! macro ensures each thread has its own storage,
     // so even with UiContext, background threads can't access UI data.
+    // UiContext::try_new() closes the loophole: it returns None here,
+    // because the UI thread has already claimed the witness.
 
     println!("[Background Thread] Cannot access UI data (by design!)");
     println!("[Background Thread] This is enforced at compile time");
@@ -209,6 +480,182 @@ mod tests {
         assert!(get_room(&ui, "test_room").is_none());
     }
 
+    #[test]
+    fn test_remove_room_and_count() {
+        let ui = UiContext::new();
+
+        add_room(&ui, RoomData {
+            id: "keep".to_string(),
+            name: "Keep".to_string(),
+            unread_count: 1,
+        });
+        add_room(&ui, RoomData {
+            id: "leave".to_string(),
+            name: "Leave".to_string(),
+            unread_count: 2,
+        });
+        assert_eq!(room_count(&ui), 2);
+
+        let removed = remove_room(&ui, "leave").expect("room should be removed");
+        assert_eq!(removed.name, "Leave");
+        assert_eq!(removed.unread_count, 2);
+
+        assert_eq!(room_count(&ui), 1);
+        assert!(get_room(&ui, "leave").is_none());
+        assert!(remove_room(&ui, "leave").is_none());
+    }
+
+    #[test]
+    fn test_for_each_room_sums_unread() {
+        let ui = UiContext::new();
+
+        for (id, unread) in [("a", 1), ("b", 4), ("c", 10)] {
+            add_room(&ui, RoomData {
+                id: id.to_string(),
+                name: id.to_uppercase(),
+                unread_count: unread,
+            });
+        }
+
+        let mut total = 0;
+        for_each_room(&ui, |room| total += room.unread_count);
+        assert_eq!(total, 15);
+
+        // The snapshot doesn't hold a borrow, so mutating while iterating is fine
+        for room in collect_rooms(&ui) {
+            update_unread_count(&ui, &room.id, room.unread_count + 1);
+        }
+        let mut total = 0;
+        for_each_room(&ui, |room| total += room.unread_count);
+        assert_eq!(total, 18);
+    }
+
+    #[test]
+    fn test_subscribe_records_room_events() {
+        let ui = UiContext::new();
+        let events = Rc::new(RefCell::new(Vec::new()));
+
+        let recorder = Rc::clone(&events);
+        subscribe(&ui, Rc::new(move |event: &RoomEvent| {
+            recorder.borrow_mut().push(event.clone());
+        }));
+
+        add_room(&ui, RoomData {
+            id: "room".to_string(),
+            name: "Room".to_string(),
+            unread_count: 0,
+        });
+        add_room(&ui, RoomData {
+            id: "room".to_string(),
+            name: "Renamed".to_string(),
+            unread_count: 0,
+        });
+        update_unread_count(&ui, "room", 2);
+        update_unread_count(&ui, "missing", 2);
+        remove_room(&ui, "room");
+        remove_room(&ui, "room");
+
+        let id = || "room".to_string();
+        assert_eq!(*events.borrow(), vec![
+            RoomEvent::Added { room_id: id() },
+            RoomEvent::Updated { room_id: id() },
+            RoomEvent::Updated { room_id: id() },
+            RoomEvent::Removed { room_id: id() },
+        ]);
+    }
+
+    #[test]
+    fn test_generic_ui_cache() {
+        let ui = UiContext::new();
+        let typing: UiCache<u32> = UiCache::new();
+        assert!(typing.is_empty(&ui));
+
+        assert_eq!(typing.insert(&ui, "room1".to_string(), 2), None);
+        assert_eq!(typing.insert(&ui, "room1".to_string(), 3), Some(2));
+        assert_eq!(typing.get(&ui, "room1"), Some(3));
+        assert_eq!(typing.get(&ui, "room2"), None);
+
+        assert!(typing.update(&ui, "room1", |n| *n += 1));
+        assert_eq!(typing.get(&ui, "room1"), Some(4));
+
+        assert_eq!(typing.remove(&ui, "room1"), Some(4));
+        assert_eq!(typing.len(&ui), 0);
+    }
+
+    #[test]
+    fn test_try_new_rejects_other_threads() {
+        // Only this test calls try_new, so the test thread becomes the UI thread
+        assert!(UiContext::try_new().is_some());
+        assert!(UiContext::try_new().is_some(), "UI thread can ask again");
+
+        let from_background = thread::spawn(|| UiContext::try_new().is_none())
+            .join()
+            .unwrap();
+        assert!(from_background, "try_new must fail off the UI thread");
+    }
+
+    #[test]
+    fn test_total_unread_tracks_changes() {
+        let ui = UiContext::new();
+        let room = |id: &str, unread_count| RoomData {
+            id: id.to_string(),
+            name: id.to_string(),
+            unread_count,
+        };
+        let summed = || {
+            let mut sum = 0;
+            for_each_room(&ui, |room| sum += room.unread_count);
+            sum
+        };
+
+        add_room(&ui, room("a", 3));
+        add_room(&ui, room("b", 7));
+        add_room(&ui, room("c", 0));
+        assert_eq!(total_unread(&ui), 10);
+
+        update_unread_count(&ui, "a", 1);
+        assert_eq!(total_unread(&ui), 8);
+
+        remove_room(&ui, "b");
+        assert_eq!(total_unread(&ui), 1);
+
+        // Re-adding an existing room replaces its count rather than adding to it
+        add_room(&ui, room("c", 4));
+        assert_eq!(total_unread(&ui), 5);
+        assert_eq!(total_unread(&ui), summed());
+
+        clear_all_rooms(&ui);
+        assert_eq!(total_unread(&ui), 0);
+    }
+
+    #[test]
+    fn test_total_unread_counts_rooms_written_through_raw_cache() {
+        let ui = UiContext::new();
+        let room = |id: &str, unread_count| RoomData {
+            id: id.to_string(),
+            name: id.to_string(),
+            unread_count,
+        };
+
+        add_room(&ui, room("a", 2));
+        {
+            let cache = get_room_cache(&ui);
+            cache.borrow_mut().insert("b".to_string(), room("b", 5));
+            assert_eq!(total_unread(&ui), 7);
+
+            // Still counted while the handle is alive and keeps writing
+            cache.borrow_mut().get_mut("b").unwrap().unread_count = 6;
+            assert_eq!(total_unread(&ui), 8);
+        }
+
+        // Removing the raw-inserted room must not underflow the running total
+        remove_room(&ui, "b");
+        assert_eq!(total_unread(&ui), 2);
+
+        update_unread_count(&ui, "a", 0);
+        assert_eq!(total_unread(&ui), 0);
+    }
+
     #[test]
     fn test_thread_local_isolation() {
         // This test demonstrates that each thread has its own thread_local storage
@@ -0,0 +1,7 @@
+//! Compile-fail checks for the sealed `UiThread` proof.
+
+#[test]
+fn ui_thread_cannot_be_implemented_externally() {
+    let t = trybuild::TestCases::new();
+    t.compile_fail("tests/ui/*.rs");
+}
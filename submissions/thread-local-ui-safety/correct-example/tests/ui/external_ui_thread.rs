@@ -0,0 +1,10 @@
+// Pull the example in as a module: code outside it can't name `private::Sealed`.
+#[allow(dead_code)]
+#[path = "../../src/main.rs"]
+mod app;
+
+struct FakeUiThread;
+
+impl app::UiThread for FakeUiThread {}
+
+fn main() {}
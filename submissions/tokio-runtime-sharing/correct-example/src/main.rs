@@ -9,57 +9,363 @@
 //! 1. Easy sharing (like Arc approach)
 //! 2. Controlled shutdown (like Mutex approach, but better)
 
-use std::sync::{Arc, OnceLock};
-use std::time::Duration;
 use anyhow::Result;
-use tokio::runtime::Runtime;
-use tokio::sync::oneshot;
+use std::future::Future;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::Duration;
+use tokio::runtime::{self, Runtime};
+use tokio::sync::{oneshot, watch, Notify};
+use tokio::task::{AbortHandle, JoinHandle};
 use tokio_util::sync::CancellationToken;
 
-/// Global runtime and shutdown signal
-static TOKIO_RUNTIME: OnceLock<Arc<Runtime>> = OnceLock::new();
-static SHUTDOWN_TOKEN: OnceLock<CancellationToken> = OnceLock::new();
+/// Counts live tasks so shutdown can wait for tasks whose handles were never collected
+///
+/// Each task holds a [`TaskGuard`] for its whole lifetime; dropping the guard
+/// (on return, cancellation, or panic) decrements the count. The count is also
+/// published as a gauge that dashboards and tests can [`subscribe`] to.
+///
+/// [`subscribe`]: TaskRegistry::subscribe
+#[derive(Clone, Default)]
+struct TaskRegistry {
+    inner: Arc<TaskRegistryInner>,
+}
 
-/// Initialize the runtime and shutdown token
-fn initialize_runtime() {
-    let runtime = Runtime::new().unwrap();
-    TOKIO_RUNTIME.set(Arc::new(runtime)).ok();
-    SHUTDOWN_TOKEN.set(CancellationToken::new()).ok();
-    log::info!("✅ Initialized Arc<Runtime> with CancellationToken");
+#[derive(Default)]
+struct TaskRegistryInner {
+    live: AtomicUsize,
+    idle: Notify,
+    gauge: watch::Sender<usize>,
 }
 
-/// Get a clone of the runtime (safe to clone Arc)
-fn get_runtime() -> Arc<Runtime> {
-    Arc::clone(TOKIO_RUNTIME.get().expect("Runtime not initialized"))
+impl TaskRegistry {
+    /// Records a task as live until the returned guard is dropped
+    ///
+    /// Call this *before* spawning, so the task is counted from the start.
+    fn register(&self) -> TaskGuard {
+        self.inner.live.fetch_add(1, Ordering::SeqCst);
+        self.publish();
+        TaskGuard {
+            registry: self.clone(),
+        }
+    }
+
+    /// Number of registered tasks that have not exited yet
+    fn live_tasks(&self) -> usize {
+        self.inner.live.load(Ordering::SeqCst)
+    }
+
+    /// Live-task gauge, updated whenever a task registers or exits
+    fn subscribe(&self) -> watch::Receiver<usize> {
+        self.inner.gauge.subscribe()
+    }
+
+    /// Pushes the current count to the gauge
+    ///
+    /// Reading the counter inside `send_modify` serializes publishers, so the
+    /// last value published is always the latest count.
+    fn publish(&self) {
+        self.inner
+            .gauge
+            .send_modify(|live| *live = self.inner.live.load(Ordering::SeqCst));
+    }
+
+    /// Waits until no registered task is live, or fails after `timeout`
+    async fn wait_for_idle(&self, timeout: Duration) -> Result<()> {
+        let wait = async {
+            loop {
+                let idle = self.inner.idle.notified();
+                tokio::pin!(idle);
+                // Register interest before checking, so a wakeup can't slip in between
+                idle.as_mut().enable();
+                if self.live_tasks() == 0 {
+                    return;
+                }
+                idle.await;
+            }
+        };
+
+        if tokio::time::timeout(timeout, wait).await.is_err() {
+            anyhow::bail!(
+                "{} task(s) still running after {:?}",
+                self.live_tasks(),
+                timeout
+            );
+        }
+        Ok(())
+    }
 }
 
-/// Get the shutdown token
-fn get_shutdown_token() -> CancellationToken {
-    SHUTDOWN_TOKEN.get().expect("Shutdown token not initialized").clone()
+/// Keeps a task counted in its [`TaskRegistry`] while alive
+struct TaskGuard {
+    registry: TaskRegistry,
+}
+
+impl Drop for TaskGuard {
+    fn drop(&mut self) {
+        let was_last = self.registry.inner.live.fetch_sub(1, Ordering::SeqCst) == 1;
+        self.registry.publish();
+        if was_last {
+            self.registry.inner.idle.notify_waiters();
+        }
+    }
+}
+
+/// How to build the shared runtime
+///
+/// The TSP workload is mostly timers and light CPU work, so embedders may want
+/// fewer worker threads than cores, and a recognizable name in thread dumps.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct RuntimeConfig {
+    /// Number of Tokio worker threads
+    worker_threads: usize,
+    /// Worker threads are named `<prefix>-<n>`
+    thread_name_prefix: String,
+}
+
+impl Default for RuntimeConfig {
+    fn default() -> Self {
+        Self {
+            worker_threads: std::thread::available_parallelism()
+                .map(|n| n.get())
+                .unwrap_or(1),
+            thread_name_prefix: "tsp-runtime".to_string(),
+        }
+    }
+}
+
+/// A runtime together with the token that shuts its tasks down
+///
+/// Keeping both in one value means they can never get out of sync: whoever
+/// holds the runtime also holds the token that belongs to it.
+#[derive(Clone)]
+struct RuntimeHandle {
+    runtime: Arc<Runtime>,
+    token: CancellationToken,
+    tasks: TaskRegistry,
+    /// What the runtime was built from, so a restart can rebuild it the same way
+    config: RuntimeConfig,
+}
+
+impl RuntimeHandle {
+    /// Builds a fresh runtime from `config` with its own, uncancelled token
+    fn with_config(config: RuntimeConfig) -> Self {
+        let prefix = config.thread_name_prefix.clone();
+        let next_id = AtomicUsize::new(0);
+        let runtime = runtime::Builder::new_multi_thread()
+            .worker_threads(config.worker_threads)
+            .thread_name_fn(move || {
+                format!("{}-{}", prefix, next_id.fetch_add(1, Ordering::SeqCst))
+            })
+            .enable_all()
+            .build()
+            .expect("Failed to build Tokio runtime");
+
+        Self {
+            runtime: Arc::new(runtime),
+            token: CancellationToken::new(),
+            tasks: TaskRegistry::default(),
+            config,
+        }
+    }
+
+    /// Get a clone of the runtime (safe to clone Arc)
+    fn runtime(&self) -> Arc<Runtime> {
+        Arc::clone(&self.runtime)
+    }
+
+    /// Get the shutdown token
+    fn token(&self) -> CancellationToken {
+        self.token.clone()
+    }
+
+    /// Get the registry that tracks tasks spawned on this runtime
+    fn tasks(&self) -> TaskRegistry {
+        self.tasks.clone()
+    }
+
+    /// Spawns `fut` on the runtime, racing it against the shutdown token
+    ///
+    /// Resolves to `Some(output)` if the future finished first and `None` if
+    /// the token was cancelled first. The task is tracked in [`Self::tasks`].
+    fn spawn_cancellable<F>(&self, fut: F) -> JoinHandle<Option<F::Output>>
+    where
+        F: Future + Send + 'static,
+        F::Output: Send + 'static,
+    {
+        let token = self.token();
+        let guard = self.tasks.register();
+        self.runtime.spawn(async move {
+            let _guard = guard;
+            tokio::select! {
+                _ = token.cancelled() => None,
+                output = fut => Some(output),
+            }
+        })
+    }
+
+    /// Signals every task spawned through this handle to stop
+    fn cancel(&self) {
+        self.token.cancel();
+    }
+}
+
+impl Default for RuntimeHandle {
+    /// Builds a fresh default runtime with its own, uncancelled token
+    fn default() -> Self {
+        Self::with_config(RuntimeConfig::default())
+    }
+}
+
+/// Process-wide cache of the runtime handed out by `initialize_runtime()`
+///
+/// A lock rather than a `OnceLock`, so `restart_runtime()` can swap it.
+static RUNTIME_HANDLE: RwLock<Option<RuntimeHandle>> = RwLock::new(None);
+
+/// Set while `restart_runtime()` runs, to reject concurrent restarts
+static RESTARTING: AtomicBool = AtomicBool::new(false);
+
+/// Initialize the runtime and shutdown token with defaults (idempotent)
+fn initialize_runtime() -> RuntimeHandle {
+    initialize_runtime_with(RuntimeConfig::default())
+}
+
+/// Initialize the runtime and shutdown token from `config`
+///
+/// Only the first call builds a runtime; later calls return the cached handle
+/// and ignore `config`.
+fn initialize_runtime_with(config: RuntimeConfig) -> RuntimeHandle {
+    RUNTIME_HANDLE
+        .write()
+        .unwrap()
+        .get_or_insert_with(|| {
+            log::info!(
+                "✅ Initialized Arc<Runtime> ({} worker threads) with CancellationToken",
+                config.worker_threads
+            );
+            RuntimeHandle::with_config(config)
+        })
+        .clone()
+}
+
+/// Get the cached runtime handle
+fn runtime_handle() -> RuntimeHandle {
+    RUNTIME_HANDLE
+        .read()
+        .unwrap()
+        .clone()
+        .expect("Runtime not initialized")
+}
+
+/// Clears [`RESTARTING`] however `restart_runtime()` exits
+struct RestartGuard;
+
+impl Drop for RestartGuard {
+    fn drop(&mut self) {
+        RESTARTING.store(false, Ordering::SeqCst);
+    }
+}
+
+/// Replaces the global runtime with a fresh one built from the same config
+///
+/// Cancels the current token, waits for registered tasks to exit, installs a
+/// new runtime and token, then shuts the old runtime down. Must be called from
+/// outside any runtime: dropping a runtime inside async context panics, which
+/// is exactly the bug this example is about. A second call while a restart is
+/// in progress fails instead of racing it.
+fn restart_runtime() -> Result<RuntimeHandle> {
+    if RESTARTING
+        .compare_exchange(false, true, Ordering::SeqCst, Ordering::SeqCst)
+        .is_err()
+    {
+        anyhow::bail!("Runtime restart already in progress");
+    }
+    let _guard = RestartGuard;
+
+    let old = RUNTIME_HANDLE
+        .read()
+        .unwrap()
+        .clone()
+        .ok_or_else(|| anyhow::anyhow!("Runtime not initialized"))?;
+
+    log::info!("Restart: cancelling old token and waiting for tasks to exit");
+    old.cancel();
+    if let Err(e) = old
+        .runtime
+        .block_on(old.tasks.wait_for_idle(TASK_SHUTDOWN_TIMEOUT))
+    {
+        log::warn!("Restart: {}; they will be dropped with the old runtime", e);
+    }
+
+    let fresh = RuntimeHandle::with_config(old.config.clone());
+    *RUNTIME_HANDLE.write().unwrap() = Some(fresh.clone());
+    log::info!("Restart: fresh runtime installed");
+
+    match Arc::try_unwrap(old.runtime) {
+        Ok(runtime) => runtime.shutdown_timeout(TASK_SHUTDOWN_TIMEOUT),
+        Err(_) => {
+            log::warn!("Restart: old runtime still shared; it shuts down with its last clone")
+        }
+    }
+
+    Ok(fresh)
 }
 
 /// Simulates a time-series processing (TSP) worker
 struct TspWorker {
-    runtime: Arc<Runtime>,
-    shutdown_token: CancellationToken,
+    /// Runtime, shutdown token and task registry, always from the same source
+    handle: RuntimeHandle,
+    /// Handles of every task spawned via `spawn_tracked`, drained on shutdown
+    tracked: Mutex<Vec<JoinHandle<()>>>,
 }
 
 impl TspWorker {
     fn new() -> Self {
+        Self::with_handle(&runtime_handle())
+    }
+
+    /// Builds a worker on an explicit runtime instead of the global one
+    fn with_handle(handle: &RuntimeHandle) -> Self {
         Self {
-            runtime: get_runtime(),
-            shutdown_token: get_shutdown_token(),
+            handle: handle.clone(),
+            tracked: Mutex::new(Vec::new()),
         }
     }
 
-    fn start_processing(&self) -> Vec<tokio::task::JoinHandle<()>> {
-        log::info!("TSP worker starting background tasks...");
+    /// Spawns `fut` and keeps its `JoinHandle`, so shutdown can't miss it
+    ///
+    /// The returned `AbortHandle` is cheap to clone and hand out; the worker
+    /// itself stays the owner of the `JoinHandle`.
+    fn spawn_tracked<F>(&self, fut: F) -> AbortHandle
+    where
+        F: Future<Output = ()> + Send + 'static,
+    {
+        let guard = self.handle.tasks.register();
+        let handle = self.handle.runtime.spawn(async move {
+            let _guard = guard;
+            fut.await;
+        });
+        let abort_handle = handle.abort_handle();
+        self.tracked.lock().unwrap().push(handle);
+        abort_handle
+    }
+
+    /// Number of tracked tasks whose handles have not been drained yet
+    fn tracked_tasks(&self) -> usize {
+        self.tracked.lock().unwrap().len()
+    }
+
+    /// Drains the tracked handles for the caller to await
+    fn take_tracked(&self) -> Vec<JoinHandle<()>> {
+        std::mem::take(&mut *self.tracked.lock().unwrap())
+    }
 
-        let mut handles = Vec::new();
+    fn start_processing(&self) {
+        log::info!("TSP worker starting background tasks...");
 
         for i in 0..3 {
-            let shutdown = self.shutdown_token.clone();
-            let handle = self.runtime.spawn(async move {
+            let shutdown = self.handle.token();
+            self.spawn_tracked(async move {
                 loop {
                     tokio::select! {
                         _ = shutdown.cancelled() => {
@@ -72,11 +378,28 @@ impl TspWorker {
                     }
                 }
             });
-            handles.push(handle);
         }
 
-        log::info!("✅ TSP worker started with {} cancellable tasks", 3);
-        handles
+        log::info!(
+            "✅ TSP worker started with {} cancellable tasks",
+            self.tracked_tasks()
+        );
+    }
+
+    /// Blocks on `fut` for at most `timeout`, bailing out early on shutdown
+    ///
+    /// A wedged iteration would otherwise hang the calling thread forever.
+    fn block_on_timeout<F: Future>(&self, fut: F, timeout: Duration) -> Result<F::Output> {
+        let token = self.handle.token.clone();
+        self.handle.runtime.block_on(async {
+            tokio::select! {
+                _ = token.cancelled() => anyhow::bail!("Blocking call aborted by shutdown signal"),
+                result = tokio::time::timeout(timeout, fut) => match result {
+                    Ok(output) => Ok(output),
+                    Err(_) => anyhow::bail!("Blocking call timed out after {:?}", timeout),
+                },
+            }
+        })
     }
 
     fn do_continuous_work(&self) {
@@ -84,37 +407,84 @@ impl TspWorker {
 
         // We can hold the Arc reference as long as needed - no Mutex locking!
         for i in 0..5 {
-            if self.shutdown_token.is_cancelled() {
+            if self.handle.token.is_cancelled() {
                 log::info!("Continuous work stopped due to shutdown signal");
                 break;
             }
 
-            self.runtime.block_on(async {
+            let iteration = async {
                 tokio::time::sleep(Duration::from_millis(50)).await;
                 log::debug!("Work iteration {} (using cloned Arc, no locking needed)", i);
-            });
+            };
+            if let Err(e) = self.block_on_timeout(iteration, Duration::from_secs(1)) {
+                log::warn!("Work iteration {} abandoned: {}", i, e);
+                break;
+            }
         }
 
         log::info!("✅ Continuous work completed efficiently (no repeated locking)");
     }
 }
 
-/// Performs graceful shutdown
-async fn graceful_shutdown(task_handles: Vec<tokio::task::JoinHandle<()>>) -> Result<()> {
+/// Outcome of waiting for tasks during shutdown
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+struct ShutdownReport {
+    /// Tasks that exited cleanly
+    completed: usize,
+    /// Tasks that panicked or were aborted
+    failed: usize,
+    /// Tasks still running when their per-task timeout expired
+    timed_out: usize,
+}
+
+/// How long `graceful_shutdown` waits for each task before giving up on it
+const TASK_SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Awaits each handle for at most `per_task_timeout`, counting the outcomes
+async fn await_tasks(
+    task_handles: Vec<JoinHandle<()>>,
+    per_task_timeout: Duration,
+) -> ShutdownReport {
+    let mut report = ShutdownReport::default();
+    for (i, handle) in task_handles.into_iter().enumerate() {
+        match tokio::time::timeout(per_task_timeout, handle).await {
+            Ok(Ok(())) => {
+                log::info!("  Task {} completed cleanly", i);
+                report.completed += 1;
+            }
+            Ok(Err(e)) => {
+                log::warn!("  Task {} failed: {}", i, e);
+                report.failed += 1;
+            }
+            Err(_) => {
+                log::warn!("  Task {} timed out", i);
+                report.timed_out += 1;
+            }
+        }
+    }
+    report
+}
+
+/// Performs graceful shutdown of everything `worker` spawned on its runtime
+async fn graceful_shutdown(worker: &TspWorker) -> Result<ShutdownReport> {
     log::info!("=== Starting graceful shutdown ===");
 
     // Step 1: Signal all tasks to shutdown
     log::info!("Step 1: Broadcasting shutdown signal via CancellationToken");
-    get_shutdown_token().cancel();
+    worker.handle.cancel();
 
     // Step 2: Wait for all tasks to complete cleanup
     log::info!("Step 2: Waiting for all tasks to complete cleanup...");
-    for (i, handle) in task_handles.into_iter().enumerate() {
-        match tokio::time::timeout(Duration::from_secs(2), handle).await {
-            Ok(Ok(())) => log::info!("  Task {} completed cleanly", i),
-            Ok(Err(e)) => log::warn!("  Task {} failed: {}", i, e),
-            Err(_) => log::warn!("  Task {} timed out", i),
-        }
+    let report = await_tasks(worker.take_tracked(), TASK_SHUTDOWN_TIMEOUT).await;
+
+    // Also wait for registered tasks that were spawned without tracking
+    let tasks = worker.handle.tasks();
+    log::info!(
+        "  Waiting for {} registered task(s) to exit...",
+        tasks.live_tasks()
+    );
+    if let Err(e) = tasks.wait_for_idle(TASK_SHUTDOWN_TIMEOUT).await {
+        log::warn!("  {}", e);
     }
 
     // Step 3: Additional cleanup wait period
@@ -127,9 +497,9 @@ async fn graceful_shutdown(task_handles: Vec<tokio::task::JoinHandle<()>>) -> Re
     let _ = rx.await;
 
     log::info!("Step 4: All tasks completed, safe to shutdown runtime");
-    log::info!("✅ Graceful shutdown complete");
+    log::info!("✅ Graceful shutdown complete: {:?}", report);
 
-    Ok(())
+    Ok(report)
 }
 
 /// Simulates the logout/restart flow
@@ -159,13 +529,26 @@ fn main() -> Result<()> {
     println!("======================================================\n");
 
     // Initialize
-    initialize_runtime();
+    let handle = initialize_runtime();
+
+    // Log the active-task gauge until everything has drained after shutdown
+    let mut gauge = handle.tasks().subscribe();
+    let token = handle.token();
+    handle.runtime().spawn(async move {
+        while gauge.changed().await.is_ok() {
+            let live = *gauge.borrow_and_update();
+            log::info!("Active tasks: {}", live);
+            if live == 0 && token.is_cancelled() {
+                break;
+            }
+        }
+    });
 
     // Create TSP worker
     let tsp_worker = TspWorker::new();
 
     // Start TSP tasks (collect handles for later cleanup)
-    let task_handles = tsp_worker.start_processing();
+    tsp_worker.start_processing();
 
     // Give tasks time to start
     std::thread::sleep(Duration::from_millis(200));
@@ -173,13 +556,33 @@ fn main() -> Result<()> {
     // Do some continuous work
     tsp_worker.do_continuous_work();
 
+    // A one-off task wired to the shutdown token by the handle itself
+    let sync_task = handle.spawn_cancellable(async {
+        loop {
+            tokio::time::sleep(Duration::from_millis(100)).await;
+            log::debug!("Sync heartbeat");
+        }
+    });
+
     // Demonstrate graceful shutdown
-    let rt = get_runtime();
+    let rt = handle.runtime();
     rt.block_on(async {
-        graceful_shutdown(task_handles).await.unwrap();
+        graceful_shutdown(&tsp_worker).await.unwrap();
+        if let Ok(None) = sync_task.await {
+            log::info!("Sync task stopped by the shutdown token");
+        }
         logout_and_restart().await.unwrap();
     });
 
+    // Restart happens outside the runtime, after our own clones are gone
+    drop(tsp_worker);
+    drop(rt);
+    drop(handle);
+    let fresh = restart_runtime()?;
+    fresh.runtime().block_on(async {
+        log::info!("✅ Fresh runtime is serving tasks");
+    });
+
     println!("\n=== Key Benefits of This Approach ===");
     println!("✅ Easy sharing: Arc allows cloning for TSP and other components");
     println!("✅ No lock contention: No Mutex means no blocking");
@@ -195,8 +598,13 @@ fn main() -> Result<()> {
 mod tests {
     use super::*;
 
+    /// Serializes tests that touch the global runtime, since one of them restarts it
+    static GLOBAL_RUNTIME: Mutex<()> = Mutex::new(());
+
     #[test]
     fn test_graceful_shutdown() {
+        let _global = GLOBAL_RUNTIME.lock().unwrap_or_else(|e| e.into_inner());
+
         // Note: We cannot test graceful_shutdown in a #[tokio::test] because
         // that would create nested runtimes, which Tokio doesn't allow.
         // Instead, we test the pattern in the main() function.
@@ -211,16 +619,17 @@ mod tests {
         initialize_runtime();
 
         let tsp_worker = TspWorker::new();
-        let handles = tsp_worker.start_processing();
+        tsp_worker.start_processing();
+        let handles = tsp_worker.take_tracked();
 
         // Give tasks time to start
         std::thread::sleep(Duration::from_millis(100));
 
         // Cancel the shutdown token
-        get_shutdown_token().cancel();
+        runtime_handle().cancel();
 
         // Wait for tasks to complete (using the runtime)
-        let rt = get_runtime();
+        let rt = runtime_handle().runtime();
         rt.block_on(async {
             for handle in handles {
                 let _ = tokio::time::timeout(Duration::from_secs(1), handle).await;
@@ -232,16 +641,21 @@ mod tests {
 
     #[test]
     fn test_runtime_sharing() {
+        let _global = GLOBAL_RUNTIME.lock().unwrap_or_else(|e| e.into_inner());
+
         // Ensure runtime is initialized (idempotent if already initialized)
         initialize_runtime();
 
-        let rt1 = get_runtime();
-        let rt2 = get_runtime();
-        let rt3 = get_runtime();
+        let rt1 = runtime_handle().runtime();
+        let rt2 = runtime_handle().runtime();
+        let rt3 = runtime_handle().runtime();
 
         // All should point to the same runtime
         // Note: Arc count may vary depending on whether other tests ran first
-        assert!(Arc::strong_count(&rt1) >= 3, "At least 3 clones should exist");
+        assert!(
+            Arc::strong_count(&rt1) >= 3,
+            "At least 3 clones should exist"
+        );
 
         // Can use any clone independently
         rt1.block_on(async {
@@ -258,4 +672,214 @@ mod tests {
 
         // Test passes if we can use all clones without panic
     }
+
+    #[test]
+    fn test_runtime_handle_spawn_cancellable() {
+        // A private handle, so cancelling it can't disturb the global token
+        let handle = RuntimeHandle::default();
+
+        let finished = handle.spawn_cancellable(async { 42 });
+        let pending = handle.spawn_cancellable(std::future::pending::<()>());
+
+        let rt = handle.runtime();
+        assert_eq!(rt.block_on(finished).unwrap(), Some(42));
+
+        handle.cancel();
+        assert!(handle.token().is_cancelled());
+
+        let outcome = rt
+            .block_on(async { tokio::time::timeout(Duration::from_secs(1), pending).await })
+            .expect("Cancelled task should finish promptly");
+        assert_eq!(outcome.unwrap(), None, "Task should observe cancellation");
+    }
+
+    #[test]
+    fn test_wait_for_idle_waits_for_all_registered_tasks() {
+        let handle = RuntimeHandle::default();
+        let tasks = handle.tasks();
+
+        for delay_ms in [50, 100, 150] {
+            handle.spawn_cancellable(async move {
+                tokio::time::sleep(Duration::from_millis(delay_ms)).await;
+            });
+        }
+        assert_eq!(
+            tasks.live_tasks(),
+            3,
+            "Tasks are counted as soon as they spawn"
+        );
+
+        let rt = handle.runtime();
+        let started = std::time::Instant::now();
+        rt.block_on(tasks.wait_for_idle(Duration::from_secs(1)))
+            .expect("All tasks should exit within the timeout");
+
+        assert!(started.elapsed() >= Duration::from_millis(150));
+        assert_eq!(tasks.live_tasks(), 0);
+    }
+
+    #[test]
+    fn test_wait_for_idle_times_out_on_stuck_task() {
+        let handle = RuntimeHandle::default();
+        handle.spawn_cancellable(std::future::pending::<()>());
+
+        let rt = handle.runtime();
+        let result = rt.block_on(handle.tasks().wait_for_idle(Duration::from_millis(50)));
+        assert!(result.is_err(), "A stuck task must keep the registry busy");
+
+        // Cancelling releases the task and the registry drains
+        handle.cancel();
+        rt.block_on(handle.tasks().wait_for_idle(Duration::from_secs(1)))
+            .expect("Cancelled task should exit");
+    }
+
+    #[test]
+    fn test_shutdown_report_counts_outcomes() {
+        let handle = RuntimeHandle::default();
+        let rt = handle.runtime();
+
+        let task_handles = vec![
+            rt.spawn(async {}),
+            rt.spawn(async { panic!("TSP task blew up") }),
+            rt.spawn(std::future::pending::<()>()),
+        ];
+
+        let report = rt.block_on(await_tasks(task_handles, Duration::from_millis(100)));
+
+        assert_eq!(
+            report,
+            ShutdownReport {
+                completed: 1,
+                failed: 1,
+                timed_out: 1,
+            }
+        );
+    }
+
+    #[test]
+    fn test_runtime_with_two_worker_threads() {
+        let handle = RuntimeHandle::with_config(RuntimeConfig {
+            worker_threads: 2,
+            thread_name_prefix: "test-tsp".to_string(),
+        });
+        let rt = handle.runtime();
+        assert_eq!(rt.metrics().num_workers(), 2);
+
+        let thread_name =
+            handle.spawn_cancellable(async { std::thread::current().name().map(str::to_owned) });
+        let name = rt.block_on(thread_name).unwrap().flatten().unwrap();
+        assert!(
+            name.starts_with("test-tsp-"),
+            "Unexpected thread name {}",
+            name
+        );
+
+        let pending = handle.spawn_cancellable(std::future::pending::<()>());
+        handle.cancel();
+        assert_eq!(rt.block_on(pending).unwrap(), None);
+    }
+
+    #[test]
+    fn test_spawn_tracked_is_drained_by_shutdown() {
+        let handle = RuntimeHandle::default();
+        let tsp_worker = TspWorker::with_handle(&handle);
+
+        tsp_worker.start_processing();
+        assert_eq!(tsp_worker.tracked_tasks(), 3);
+
+        let report = handle
+            .runtime()
+            .block_on(graceful_shutdown(&tsp_worker))
+            .unwrap();
+
+        assert_eq!(tsp_worker.tracked_tasks(), 0, "Shutdown drains the list");
+        assert_eq!(report.completed, 3);
+        assert_eq!(handle.tasks().live_tasks(), 0);
+    }
+
+    #[test]
+    fn test_restart_runtime_swaps_in_fresh_runtime() {
+        let _global = GLOBAL_RUNTIME.lock().unwrap_or_else(|e| e.into_inner());
+
+        let old = initialize_runtime();
+        let old_token = old.token();
+        let old_task = old.spawn_cancellable(std::future::pending::<()>());
+        drop(old);
+
+        let fresh = restart_runtime().expect("Restart should succeed");
+
+        assert!(old_token.is_cancelled(), "Old token must be cancelled");
+        assert!(old_task.is_finished(), "Old task exited before the swap");
+        assert!(!fresh.token().is_cancelled());
+        assert!(Arc::ptr_eq(&fresh.runtime(), &runtime_handle().runtime()));
+
+        let ran = fresh.spawn_cancellable(async { "ran on fresh runtime" });
+        assert_eq!(
+            fresh.runtime().block_on(ran).unwrap(),
+            Some("ran on fresh runtime")
+        );
+    }
+
+    #[test]
+    fn test_active_task_gauge_drops_to_zero() {
+        let handle = RuntimeHandle::default();
+        let mut gauge = handle.tasks().subscribe();
+        assert_eq!(*gauge.borrow_and_update(), 0);
+
+        for _ in 0..3 {
+            handle.spawn_cancellable(std::future::pending::<()>());
+        }
+        assert_eq!(*gauge.borrow_and_update(), 3, "Gauge counts spawned tasks");
+
+        handle.cancel();
+        let observed = handle.runtime().block_on(async {
+            let mut observed = Vec::new();
+            while observed.last() != Some(&0) {
+                tokio::time::timeout(Duration::from_secs(1), gauge.changed())
+                    .await
+                    .expect("Gauge should keep moving during shutdown")
+                    .unwrap();
+                observed.push(*gauge.borrow_and_update());
+            }
+            observed
+        });
+
+        assert_eq!(observed.last(), Some(&0));
+        assert!(
+            observed.windows(2).all(|w| w[0] > w[1]),
+            "Gauge should only fall during shutdown: {:?}",
+            observed
+        );
+    }
+
+    #[test]
+    fn test_block_on_timeout_fires_for_wedged_future() {
+        let worker = TspWorker::with_handle(&RuntimeHandle::default());
+
+        let started = std::time::Instant::now();
+        let result =
+            worker.block_on_timeout(std::future::pending::<()>(), Duration::from_millis(100));
+
+        let err = result.expect_err("A future that never completes should time out");
+        assert!(err.to_string().contains("timed out"), "{}", err);
+        assert!(started.elapsed() < Duration::from_secs(1));
+
+        assert_eq!(
+            worker
+                .block_on_timeout(async { 7 }, Duration::from_millis(100))
+                .unwrap(),
+            7
+        );
+    }
+
+    #[test]
+    fn test_block_on_timeout_aborts_on_shutdown() {
+        let worker = TspWorker::with_handle(&RuntimeHandle::default());
+        worker.handle.cancel();
+
+        let err = worker
+            .block_on_timeout(std::future::pending::<()>(), Duration::from_secs(30))
+            .expect_err("Cancellation should abort the block");
+        assert!(err.to_string().contains("shutdown"), "{}", err);
+    }
 }
@@ -4,61 +4,213 @@
 //! so the idle path yields control back to the scheduler and does not hog a core.
 
 use env_logger::Env;
+use std::future::Future;
 use std::time::Duration;
-use tokio::sync::mpsc;
-use tokio::time::{Instant, MissedTickBehavior};
+use tokio::sync::{mpsc, watch, Notify};
+use tokio::time::{Instant, Interval, MissedTickBehavior};
+use tokio_util::sync::CancellationToken;
 
 const SPIN_LIMIT: usize = 150_000;
 const PRODUCER_BURSTS: usize = 4;
 const BURST_SIZE: usize = 25;
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Default)]
 struct ConsumerStats {
     processed: usize,
     idle_ticks: usize,
     duration_ms: u128,
+    /// Idle interval in effect when the consumer stopped.
+    idle_interval: Duration,
+    /// Messages still queued at shutdown that were handled before exiting.
+    drained: usize,
 }
 
-async fn run_cooperative_consumer() -> ConsumerStats {
-    let (tx, mut rx) = mpsc::unbounded_channel::<usize>();
+/// Tuning knobs for the consumer loop.
+///
+/// A shorter `idle_interval` notices idleness sooner at the cost of more
+/// wake-ups; a longer one saves CPU during quiet periods.
+#[derive(Debug, Clone)]
+struct ConsumerConfig {
+    /// Delay between idle ticks when no message is ready.
+    idle_interval: Duration,
+    /// Idle ticks after which the consumer assumes it is spinning and stops.
+    spin_limit: usize,
+    /// Enables adaptive backoff: each quiet tick doubles the interval, up to
+    /// this cap, and any message resets it to `idle_interval`.
+    max_idle_interval: Option<Duration>,
+    /// Cancelling this stops the consumer after it drains what is already queued.
+    shutdown: CancellationToken,
+}
 
-    let consumer = tokio::spawn(async move {
-        let mut processed = 0usize;
-        let mut idle_ticks = 0usize;
-        let start = Instant::now();
-        let mut idle_interval = tokio::time::interval(Duration::from_millis(5));
-        // Do not try to "catch up" if the consumer is busy; just wait for the next tick.
-        idle_interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
-
-        loop {
-            tokio::select! {
-                biased;
-                msg = rx.recv() => {
-                    match msg {
-                        Some(value) => {
-                            processed += 1;
-                            if processed.is_multiple_of(50) {
-                                log::debug!("Processed {value}, total {processed}");
-                            }
+impl Default for ConsumerConfig {
+    fn default() -> Self {
+        Self {
+            idle_interval: Duration::from_millis(5),
+            spin_limit: SPIN_LIMIT,
+            max_idle_interval: None,
+            shutdown: CancellationToken::new(),
+        }
+    }
+}
+
+/// Drives the idle branch of the select loop.
+enum IdleTimer {
+    /// Ticks every `idle_interval`, busy or not.
+    Fixed(Interval),
+    /// Sleeps until `deadline`, backing off while the channel stays quiet.
+    Adaptive {
+        min: Duration,
+        max: Duration,
+        current: Duration,
+        deadline: Instant,
+    },
+}
+
+impl IdleTimer {
+    fn new(config: &ConsumerConfig) -> Self {
+        match config.max_idle_interval {
+            None => {
+                let mut interval = tokio::time::interval(config.idle_interval);
+                // Do not try to "catch up" if the consumer is busy; just wait for the next tick.
+                interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+                IdleTimer::Fixed(interval)
+            }
+            Some(max) => IdleTimer::Adaptive {
+                min: config.idle_interval,
+                max: max.max(config.idle_interval),
+                current: config.idle_interval,
+                deadline: Instant::now() + config.idle_interval,
+            },
+        }
+    }
+
+    /// Waits for the next idle tick.
+    async fn tick(&mut self) {
+        match self {
+            IdleTimer::Fixed(interval) => {
+                interval.tick().await;
+            }
+            IdleTimer::Adaptive { deadline, .. } => tokio::time::sleep_until(*deadline).await,
+        }
+    }
+
+    /// An idle tick fired with nothing to do: back off.
+    fn backoff(&mut self) {
+        if let IdleTimer::Adaptive {
+            max,
+            current,
+            deadline,
+            ..
+        } = self
+        {
+            *current = (*current * 2).min(*max);
+            *deadline = Instant::now() + *current;
+        }
+    }
+
+    /// A message arrived: go back to the shortest interval.
+    fn reset(&mut self) {
+        if let IdleTimer::Adaptive {
+            min,
+            current,
+            deadline,
+            ..
+        } = self
+        {
+            *current = *min;
+            *deadline = Instant::now() + *min;
+        }
+    }
+
+    fn current(&self) -> Duration {
+        match self {
+            IdleTimer::Fixed(interval) => interval.period(),
+            IdleTimer::Adaptive { current, .. } => *current,
+        }
+    }
+}
+
+/// Drains `rx` into `handler` until every sender is dropped, yielding on
+/// `config.idle_interval` whenever no message is ready.
+///
+/// The running stats are published to `monitor` on every loop iteration, so a
+/// supervisor can spot a runaway spin long before the consumer returns.
+async fn consume<T: Send + 'static>(
+    mut rx: mpsc::UnboundedReceiver<T>,
+    config: ConsumerConfig,
+    monitor: &watch::Sender<ConsumerStats>,
+    mut handler: impl FnMut(T),
+) -> ConsumerStats {
+    let mut processed = 0usize;
+    let mut idle_ticks = 0usize;
+    let mut drained = 0usize;
+    let start = Instant::now();
+    let mut idle_timer = IdleTimer::new(&config);
+    let snapshot = |processed, idle_ticks, drained, idle_timer: &IdleTimer| ConsumerStats {
+        processed,
+        idle_ticks,
+        duration_ms: start.elapsed().as_millis(),
+        idle_interval: idle_timer.current(),
+        drained,
+    };
+
+    loop {
+        tokio::select! {
+            biased;
+            _ = config.shutdown.cancelled() => {
+                // Refuse new messages, but don't lose the ones already buffered
+                rx.close();
+                while let Ok(value) = rx.try_recv() {
+                    handler(value);
+                    processed += 1;
+                    drained += 1;
+                }
+                log::debug!("Shutdown requested, drained {drained} queued messages");
+                break;
+            }
+            msg = rx.recv() => {
+                match msg {
+                    Some(value) => {
+                        idle_timer.reset();
+                        handler(value);
+                        processed += 1;
+                        if processed.is_multiple_of(50) {
+                            log::debug!("Processed {processed} messages");
                         }
-                        None => break,
                     }
+                    None => break,
                 }
-                _ = idle_interval.tick() => {
-                    idle_ticks += 1;
-                    if idle_ticks >= SPIN_LIMIT {
-                        log::debug!("Reached idle tick guard ({SPIN_LIMIT})");
-                        break;
-                    }
+            }
+            _ = idle_timer.tick() => {
+                idle_timer.backoff();
+                idle_ticks += 1;
+                if idle_ticks >= config.spin_limit {
+                    log::debug!("Reached idle tick guard ({})", config.spin_limit);
+                    break;
                 }
             }
         }
+        monitor.send_replace(snapshot(processed, idle_ticks, drained, &idle_timer));
+    }
 
-        ConsumerStats {
-            processed,
-            idle_ticks,
-            duration_ms: start.elapsed().as_millis(),
-        }
+    let stats = snapshot(processed, idle_ticks, drained, &idle_timer);
+    monitor.send_replace(stats.clone());
+    stats
+}
+
+async fn run_cooperative_consumer(config: ConsumerConfig) -> ConsumerStats {
+    run_monitored_consumer(config, watch::Sender::default()).await
+}
+
+/// Like [`run_cooperative_consumer`], publishing live stats to `monitor`.
+async fn run_monitored_consumer(
+    config: ConsumerConfig,
+    monitor: watch::Sender<ConsumerStats>,
+) -> ConsumerStats {
+    let (tx, rx) = mpsc::unbounded_channel::<usize>();
+
+    let consumer = tokio::spawn(async move {
+        consume(rx, config, &monitor, |value| log::trace!("Handled {value}")).await
     });
 
     let producer = tokio::spawn(async move {
@@ -84,11 +236,107 @@ async fn run_cooperative_consumer() -> ConsumerStats {
     stats
 }
 
+/// Event-driven alternative to [`consume`]: no timer at all.
+///
+/// The interval version wakes every `idle_interval` even if the channel stays
+/// silent forever; that is cheap but never zero. Here the consumer sleeps until
+/// a message arrives or someone calls `wake.notify_one()` (for housekeeping,
+/// say), so a silent period costs no wake-ups and `idle_ticks` only counts
+/// explicit notifications. The trade-off: there is no periodic idle work unless
+/// something outside the loop asks for it.
+async fn consume_event_driven<T: Send + 'static>(
+    mut rx: mpsc::UnboundedReceiver<T>,
+    wake: &Notify,
+    mut handler: impl FnMut(T),
+) -> ConsumerStats {
+    let mut stats = ConsumerStats::default();
+    let start = Instant::now();
+
+    loop {
+        tokio::select! {
+            biased;
+            msg = rx.recv() => {
+                match msg {
+                    Some(value) => {
+                        handler(value);
+                        stats.processed += 1;
+                    }
+                    None => break,
+                }
+            }
+            _ = wake.notified() => {
+                stats.idle_ticks += 1;
+                log::debug!("Woken for idle work ({} so far)", stats.idle_ticks);
+            }
+        }
+    }
+
+    stats.duration_ms = start.elapsed().as_millis();
+    stats
+}
+
+/// Fan-in: every producer gets its own clone of the sender, and the consumer
+/// runs until all of them have finished and dropped their clone.
+async fn run_fan_in_consumer<T, P, Fut>(
+    config: ConsumerConfig,
+    producers: impl IntoIterator<Item = P>,
+    handler: impl FnMut(T),
+) -> ConsumerStats
+where
+    T: Send + 'static,
+    P: FnOnce(mpsc::UnboundedSender<T>) -> Fut,
+    Fut: Future<Output = ()> + Send + 'static,
+{
+    let (tx, rx) = mpsc::unbounded_channel::<T>();
+    let handles: Vec<_> = producers
+        .into_iter()
+        .map(|producer| tokio::spawn(producer(tx.clone())))
+        .collect();
+    // Only the producers' clones keep the channel open now
+    drop(tx);
+
+    let stats = consume(rx, config, &watch::Sender::default(), handler).await;
+    for handle in handles {
+        handle.await.expect("producer task must finish");
+    }
+    stats
+}
+
+/// Sends `bursts` bursts of `BURST_SIZE`, starting after `offset` and pausing
+/// between bursts, so several of these interleave and then all go quiet.
+async fn staggered_producer(tx: mpsc::UnboundedSender<usize>, offset: Duration, bursts: usize) {
+    tokio::time::sleep(offset).await;
+    for burst in 0..bursts {
+        for i in 0..BURST_SIZE {
+            if tx.send(burst * BURST_SIZE + i).is_err() {
+                return;
+            }
+        }
+        tokio::time::sleep(Duration::from_millis(3)).await;
+    }
+    // Everyone pauses together before hanging up
+    tokio::time::sleep(Duration::from_millis(30)).await;
+}
+
 #[tokio::main]
 async fn main() {
     env_logger::Builder::from_env(Env::default().default_filter_or("info")).init();
 
-    let stats = run_cooperative_consumer().await;
+    // A supervisor watching live stats for signs of a runaway spin
+    let (monitor, mut live) = watch::channel(ConsumerStats::default());
+    let spin_warning = ConsumerConfig::default().spin_limit / 2;
+    let supervisor = tokio::spawn(async move {
+        while live.changed().await.is_ok() {
+            let stats = live.borrow_and_update().clone();
+            if stats.idle_ticks > spin_warning {
+                log::warn!("Consumer looks like it is spinning: {stats:?}");
+                break;
+            }
+        }
+    });
+
+    let stats = run_monitored_consumer(ConsumerConfig::default(), monitor).await;
+    supervisor.await.expect("supervisor task must finish");
     log::info!(
         "Processed {} messages in {} ms, idle ticks: {}",
         stats.processed,
@@ -96,6 +344,64 @@ async fn main() {
         stats.idle_ticks
     );
 
+    let adaptive = run_cooperative_consumer(ConsumerConfig {
+        max_idle_interval: Some(Duration::from_millis(40)),
+        ..ConsumerConfig::default()
+    })
+    .await;
+    log::info!(
+        "Adaptive backoff: {} idle ticks, interval backed off to {:?}",
+        adaptive.idle_ticks,
+        adaptive.idle_interval
+    );
+
+    let fan_in = run_fan_in_consumer(
+        ConsumerConfig::default(),
+        (0..3u64).map(|n| {
+            move |tx| staggered_producer(tx, Duration::from_millis(n * 2), PRODUCER_BURSTS)
+        }),
+        drop,
+    )
+    .await;
+    log::info!(
+        "Fan-in from 3 producers: {} messages, {} idle ticks",
+        fan_in.processed,
+        fan_in.idle_ticks
+    );
+
+    // Cancelling mid-stream drains the backlog instead of dropping it
+    let config = ConsumerConfig::default();
+    let shutdown = config.shutdown.clone();
+    let (tx, rx) = mpsc::unbounded_channel::<usize>();
+    for value in 0..BURST_SIZE {
+        tx.send(value).expect("receiver is alive");
+    }
+    shutdown.cancel();
+    let drained = consume(rx, config, &watch::Sender::default(), drop).await;
+    log::info!(
+        "Graceful shutdown drained {} queued messages",
+        drained.drained
+    );
+
+    // Event-driven idling: silence costs nothing, only explicit wakes count
+    let wake = Notify::new();
+    let (tx, rx) = mpsc::unbounded_channel::<usize>();
+    let producer = async {
+        for value in 0..BURST_SIZE {
+            tx.send(value).expect("receiver is alive");
+        }
+        tokio::time::sleep(Duration::from_millis(30)).await;
+        wake.notify_one();
+        tokio::time::sleep(Duration::from_millis(5)).await;
+        drop(tx);
+    };
+    let (event_driven, ()) = tokio::join!(consume_event_driven(rx, &wake, drop), producer);
+    log::info!(
+        "Event-driven consumer: {} messages, {} idle wake-ups",
+        event_driven.processed,
+        event_driven.idle_ticks
+    );
+
     if stats.idle_ticks < SPIN_LIMIT {
         println!(
             "✅ Idle branch cooperates with the scheduler ({} idle ticks < limit {}).",
@@ -113,13 +419,240 @@ async fn main() {
 mod tests {
     use super::*;
 
+    /// Fails the test if the select loop looks like it was spinning.
+    #[track_caller]
+    fn assert_cooperative(stats: &ConsumerStats, max_idle_ticks: usize) {
+        assert!(
+            stats.idle_ticks <= max_idle_ticks,
+            "select loop is not cooperative: {} idle ticks in {} ms (allowed {})",
+            stats.idle_ticks,
+            stats.duration_ms,
+            max_idle_ticks
+        );
+    }
+
+    /// Like [`idle_ticks_over`], but returns idle ticks per millisecond of
+    /// `window`. A cooperative loop stays well below one; a spinning one
+    /// manages thousands.
+    async fn measure_cpu_friendliness(config: ConsumerConfig, window: Duration) -> f64 {
+        let idle_ticks = idle_ticks_over(window, config).await;
+        idle_ticks as f64 / window.as_millis().max(1) as f64
+    }
+
     #[tokio::test(flavor = "multi_thread")]
     async fn prevents_busy_loop() {
-        let stats = run_cooperative_consumer().await;
+        let stats = run_cooperative_consumer(ConsumerConfig::default()).await;
         assert!(
             stats.idle_ticks < SPIN_LIMIT,
             "Expected the interval throttle to avoid spinning, got {} idle ticks",
             stats.idle_ticks
         );
     }
+
+    /// Keeps a channel open but silent for `window`, returning the idle ticks seen.
+    async fn idle_ticks_over(window: Duration, config: ConsumerConfig) -> usize {
+        let (tx, rx) = mpsc::unbounded_channel::<usize>();
+        let consumer =
+            tokio::spawn(async move { consume(rx, config, &watch::Sender::default(), drop).await });
+        tokio::time::sleep(window).await;
+        drop(tx);
+        consumer.await.unwrap().idle_ticks
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn longer_idle_interval_means_fewer_ticks() {
+        let window = Duration::from_millis(200);
+        let default_ticks = idle_ticks_over(window, ConsumerConfig::default()).await;
+        let relaxed_ticks = idle_ticks_over(
+            window,
+            ConsumerConfig {
+                idle_interval: Duration::from_secs(1),
+                ..ConsumerConfig::default()
+            },
+        )
+        .await;
+
+        // ~40 ticks at 5ms versus just the immediate first tick at 1s
+        assert!(
+            relaxed_ticks * 10 <= default_ticks,
+            "expected far fewer ticks: {relaxed_ticks} (1s) vs {default_ticks} (5ms)"
+        );
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn adaptive_backoff_grows_during_silence() {
+        let min = Duration::from_millis(5);
+        let config = ConsumerConfig {
+            idle_interval: min,
+            max_idle_interval: Some(Duration::from_millis(80)),
+            ..ConsumerConfig::default()
+        };
+        let (tx, rx) = mpsc::unbounded_channel::<usize>();
+        let consumer =
+            tokio::spawn(async move { consume(rx, config, &watch::Sender::default(), drop).await });
+
+        // A burst, then a long silence
+        for i in 0..BURST_SIZE {
+            tx.send(i).unwrap();
+        }
+        tokio::time::sleep(Duration::from_millis(300)).await;
+        drop(tx);
+
+        let stats = consumer.await.unwrap();
+        assert_eq!(stats.processed, BURST_SIZE);
+        assert!(
+            stats.idle_interval > min,
+            "idle interval should have backed off, still {:?}",
+            stats.idle_interval
+        );
+        // 5 + 10 + 20 + 40 + 80 + 80 ... ms: far fewer than the ~60 fixed ticks
+        assert!(stats.idle_ticks < 15, "got {} idle ticks", stats.idle_ticks);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn live_stats_climb_while_producing() {
+        let (monitor, mut live) = watch::channel(ConsumerStats::default());
+        let run = tokio::spawn(run_monitored_consumer(ConsumerConfig::default(), monitor));
+
+        let mut seen = Vec::new();
+        while live.changed().await.is_ok() {
+            let processed = live.borrow_and_update().processed;
+            if seen.last() != Some(&processed) {
+                seen.push(processed);
+            }
+        }
+        let stats = run.await.unwrap();
+
+        assert!(
+            seen.windows(2).all(|w| w[0] < w[1]),
+            "processed went backwards: {seen:?}"
+        );
+        assert!(
+            seen.iter().any(|&p| p > 0 && p < stats.processed),
+            "expected intermediate progress before the end: {seen:?}"
+        );
+        assert_eq!(seen.last(), Some(&stats.processed));
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn consumes_string_messages() {
+        let (tx, rx) = mpsc::unbounded_channel::<String>();
+        let producer = tokio::spawn(async move {
+            for burst in 0..PRODUCER_BURSTS {
+                for i in 0..BURST_SIZE {
+                    tx.send(format!("message {burst}-{i}")).unwrap();
+                }
+                tokio::time::sleep(Duration::from_millis(3)).await;
+            }
+        });
+
+        let mut handled = 0usize;
+        let mut bytes = 0usize;
+        let stats = consume(
+            rx,
+            ConsumerConfig::default(),
+            &watch::Sender::default(),
+            |message: String| {
+                handled += 1;
+                bytes += message.len();
+            },
+        )
+        .await;
+        producer.await.unwrap();
+
+        assert_eq!(handled, PRODUCER_BURSTS * BURST_SIZE);
+        assert_eq!(stats.processed, handled);
+        assert!(bytes > 0);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn fan_in_from_three_producers() {
+        let bursts = [2usize, 3, 4];
+        let producers = bursts.iter().enumerate().map(|(n, &count)| {
+            let offset = Duration::from_millis(n as u64 * 2);
+            move |tx| staggered_producer(tx, offset, count)
+        });
+
+        let mut handled = 0usize;
+        let stats = run_fan_in_consumer(ConsumerConfig::default(), producers, |_: usize| {
+            handled += 1
+        })
+        .await;
+
+        let expected: usize = bursts.iter().map(|count| count * BURST_SIZE).sum();
+        assert_eq!(stats.processed, expected);
+        assert_eq!(handled, expected);
+        assert!(
+            stats.idle_ticks < SPIN_LIMIT,
+            "fan-in consumer spun: {} idle ticks",
+            stats.idle_ticks
+        );
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn cancel_drains_queued_messages() {
+        let config = ConsumerConfig::default();
+        let shutdown = config.shutdown.clone();
+        let (tx, rx) = mpsc::unbounded_channel::<usize>();
+
+        let queued = 7;
+        for value in 0..queued {
+            tx.send(value).unwrap();
+        }
+        shutdown.cancel();
+
+        let mut handled = Vec::new();
+        let stats = consume(rx, config, &watch::Sender::default(), |v| handled.push(v)).await;
+
+        assert_eq!(stats.drained, queued);
+        assert_eq!(handled, (0..queued).collect::<Vec<_>>());
+        // The channel is closed, so late sends fail instead of vanishing
+        assert!(tx.send(99).is_err());
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn event_driven_consumer_stays_asleep_during_silence() {
+        let wake = Notify::new();
+        let (tx, rx) = mpsc::unbounded_channel::<usize>();
+
+        let producer = async {
+            tx.send(1).unwrap();
+            // A long silent period: the interval version would tick ~40 times
+            tokio::time::sleep(Duration::from_millis(200)).await;
+            tx.send(2).unwrap();
+            drop(tx);
+        };
+        let (stats, ()) = tokio::join!(consume_event_driven(rx, &wake, drop), producer);
+
+        assert_eq!(stats.processed, 2);
+        assert_eq!(stats.idle_ticks, 0);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn event_driven_consumer_counts_explicit_wakes() {
+        let wake = Notify::new();
+        let (tx, rx) = mpsc::unbounded_channel::<usize>();
+
+        let producer = async {
+            tokio::time::sleep(Duration::from_millis(20)).await;
+            wake.notify_one();
+            tokio::time::sleep(Duration::from_millis(20)).await;
+            drop(tx);
+        };
+        let (stats, ()) = tokio::join!(consume_event_driven(rx, &wake, drop), producer);
+
+        assert_eq!(stats.idle_ticks, 1);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn cooperative_consumer_regression_guard() {
+        let stats = run_cooperative_consumer(ConsumerConfig::default()).await;
+        // ~45 ms of runtime at one tick per 5 ms; leave headroom for slow CI
+        assert_cooperative(&stats, 100);
+
+        let ticks_per_ms =
+            measure_cpu_friendliness(ConsumerConfig::default(), Duration::from_millis(100)).await;
+        // One tick per 5 ms is 0.2/ms; the broken spin loop is orders of magnitude higher
+        assert!(ticks_per_ms < 1.0, "{ticks_per_ms:.2} idle ticks per ms");
+    }
 }
@@ -1,60 +1,147 @@
+mod fd;
+mod sys;
+
 use anyhow::Result;
 use once_cell::sync::OnceCell;
-use tokio::signal::unix::{signal, SignalKind};
+use std::collections::HashMap;
+use std::sync::Mutex;
+use sys::SignalKind;
 use tokio::sync::broadcast;
+use tokio::sync::broadcast::error::RecvError;
 use tokio::task::JoinHandle;
 use tokio::time::{sleep, Duration};
+use tokio_util::sync::CancellationToken;
+
+/// Formats the fd count for log lines, `n/a` where it can't be measured.
+fn fd_label() -> String {
+    fd::open_fd_count().map_or_else(|| "n/a".to_owned(), |count| count.to_string())
+}
 
-#[cfg(target_os = "linux")]
-fn open_fd_count() -> usize {
-    std::fs::read_dir("/proc/self/fd")
-        .map(|dir| dir.count())
-        .unwrap_or(0)
+/// A delivered signal. `seq` counts up from 1 per kind, so a jump between
+/// two events tells a subscriber exactly how many it missed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct SignalEvent {
+    kind: SignalKind,
+    seq: u64,
 }
 
-#[cfg(target_os = "macos")]
-fn open_fd_count() -> usize {
-    std::fs::read_dir("/dev/fd")
-        .map(|dir| dir.count())
-        .unwrap_or(0)
+/// One OS signal stream and the broadcast sender it feeds.
+struct SignalChannel {
+    sender: broadcast::Sender<SignalEvent>,
+    _forwarder: JoinHandle<()>,
 }
 
-#[cfg(not(any(target_os = "linux", target_os = "macos")))]
-fn open_fd_count() -> usize {
-    0
+impl SignalChannel {
+    fn listen(kind: SignalKind, capacity: usize, shutdown: CancellationToken) -> Self {
+        let (tx, _) = broadcast::channel(capacity);
+        let mut stream = sys::listen(kind).expect("initialize single signal listener");
+        let tx_clone = tx.clone();
+
+        let forwarder = tokio::spawn(async move {
+            let mut seq = 0;
+            loop {
+                tokio::select! {
+                    // Returning drops the stream (releasing its fd) and our sender clone
+                    _ = shutdown.cancelled() => break,
+                    received = stream.recv() => {
+                        if received.is_none() {
+                            break;
+                        }
+                        seq += 1;
+                        // Fan out a single signal to every subscriber.
+                        let _ = tx_clone.send(SignalEvent { kind, seq });
+                    }
+                }
+            }
+        });
+
+        SignalChannel {
+            sender: tx,
+            _forwarder: forwarder,
+        }
+    }
 }
 
+/// Registry of signal streams: at most one stream per `SignalKind`, no matter
+/// how many subscribers come and go.
 struct SignalHub {
-    sender: broadcast::Sender<()>,
-    _forwarder: JoinHandle<()>,
+    channels: Mutex<HashMap<SignalKind, SignalChannel>>,
+    capacity: usize,
+    shutdown: CancellationToken,
 }
 
 impl SignalHub {
+    fn new() -> Self {
+        Self::with_capacity(32)
+    }
+
+    /// Each kind buffers up to `capacity` undelivered events per subscriber
+    /// before the slowest one starts lagging.
+    fn with_capacity(capacity: usize) -> Self {
+        SignalHub {
+            channels: Mutex::new(HashMap::new()),
+            capacity,
+            shutdown: CancellationToken::new(),
+        }
+    }
+
     fn global() -> &'static Self {
         static HUB: OnceCell<SignalHub> = OnceCell::new();
 
-        HUB.get_or_init(|| {
-            let (tx, _) = broadcast::channel(32);
-            let mut stream =
-                signal(SignalKind::hangup()).expect("initialize single SIGHUP listener");
-            let tx_clone = tx.clone();
+        HUB.get_or_init(SignalHub::new)
+    }
 
-            let forwarder = tokio::spawn(async move {
-                while stream.recv().await.is_some() {
-                    // Fan out a single signal to every subscriber.
-                    let _ = tx_clone.send(());
-                }
-            });
+    /// Subscribes to `kind`, registering its stream on first use.
+    /// After `shutdown` the returned receiver is already closed.
+    fn subscribe(&self, kind: SignalKind) -> broadcast::Receiver<SignalEvent> {
+        let mut channels = self.channels.lock().unwrap();
+        if self.shutdown.is_cancelled() {
+            return broadcast::channel(1).1;
+        }
+        channels
+            .entry(kind)
+            .or_insert_with(|| SignalChannel::listen(kind, self.capacity, self.shutdown.clone()))
+            .sender
+            .subscribe()
+    }
 
-            SignalHub {
-                sender: tx,
-                _forwarder: forwarder,
-            }
-        })
+    /// Stops every forwarder and drops the senders, so subscribers see
+    /// `RecvError::Closed`. Later calls are no-ops.
+    fn shutdown(&self) {
+        let mut channels = self.channels.lock().unwrap();
+        self.shutdown.cancel();
+        channels.clear();
     }
 
-    fn subscribe(&self) -> broadcast::Receiver<()> {
-        self.sender.subscribe()
+    /// Number of live receivers for `kind`; a steadily growing value points at
+    /// subscribers that are never dropped.
+    fn subscriber_count(&self, kind: SignalKind) -> usize {
+        self.channels
+            .lock()
+            .unwrap()
+            .get(&kind)
+            .map_or(0, |channel| channel.sender.receiver_count())
+    }
+}
+
+impl Drop for SignalHub {
+    // A dropped hub stops its forwarders too, so their streams don't outlive it
+    fn drop(&mut self) {
+        self.shutdown.cancel();
+    }
+}
+
+/// Waits for the next event, returning it with the number of events this
+/// receiver missed because it lagged behind the broadcast buffer.
+/// Returns `None` once the hub has closed the channel.
+async fn recv_event(rx: &mut broadcast::Receiver<SignalEvent>) -> Option<(SignalEvent, u64)> {
+    let mut missed = 0;
+    loop {
+        match rx.recv().await {
+            Ok(event) => return Some((event, missed)),
+            Err(RecvError::Lagged(skipped)) => missed += skipped,
+            Err(RecvError::Closed) => return None,
+        }
     }
 }
 
@@ -63,19 +150,35 @@ async fn main() -> Result<()> {
     println!("Hot-reload daemon (fixed) - single signal stream reused.");
     let hub = SignalHub::global();
 
+    let mut terminate = hub.subscribe(SignalKind::terminate());
+    let mut interrupt = hub.subscribe(SignalKind::interrupt());
+    tokio::spawn(async move {
+        tokio::select! {
+            _ = terminate.recv() => {}
+            _ = interrupt.recv() => {}
+        }
+        println!("[graceful task] shutdown requested.");
+    });
+
     for iteration in 0_usize..256 {
-        let mut rx = hub.subscribe();
+        let mut rx = hub.subscribe(SignalKind::hangup());
 
         tokio::spawn(async move {
-            let _ = rx.recv().await;
-            println!("[graceful task] reload triggered.");
+            if let Some((event, missed)) = recv_event(&mut rx).await {
+                println!(
+                    "[graceful task] reload triggered (seq={}, missed={missed}).",
+                    event.seq
+                );
+            }
         });
 
         println!(
-            "[fixed ] iteration={iteration:>4} | open_fds={}",
-            open_fd_count()
+            "[fixed ] iteration={iteration:>4} | open_fds={} | reload_subscribers={}",
+            fd_label(),
+            hub.subscriber_count(SignalKind::hangup())
         );
 
+        #[cfg(unix)]
         unsafe {
             libc::raise(libc::SIGHUP);
         }
@@ -83,5 +186,207 @@ async fn main() -> Result<()> {
         sleep(Duration::from_millis(25)).await;
     }
 
+    #[cfg(unix)]
+    unsafe {
+        libc::raise(libc::SIGTERM);
+    }
+    sleep(Duration::from_millis(25)).await;
+
+    hub.shutdown();
+    println!("Signal hub shut down; open_fds={}", fd_label());
+
     Ok(())
 }
+
+/// Reruns a test as the only one in a child process, for tests that measure
+/// or disturb process-wide state (open fds, console control events).
+#[cfg(test)]
+mod isolation {
+    use std::process::Command;
+
+    const CHILD_ENV: &str = "SIGNAL_HUB_ISOLATED_TEST";
+
+    /// Whether this process was started by [`rerun_alone`].
+    pub fn in_child() -> bool {
+        std::env::var_os(CHILD_ENV).is_some()
+    }
+
+    /// Runs `test` (its full path, e.g. `tests::name`) alone in a child
+    /// process, after `configure` has adjusted the command, and panics with
+    /// the child's output if it fails.
+    pub fn rerun_alone(test: &str, configure: impl FnOnce(&mut Command)) {
+        let mut command = Command::new(std::env::current_exe().unwrap());
+        command
+            .args(["--exact", test, "--test-threads=1", "--nocapture"])
+            .env(CHILD_ENV, "1");
+        configure(&mut command);
+
+        let output = command.output().expect("spawn the test binary");
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        assert!(
+            output.status.success() && stdout.contains("1 passed"),
+            "`{test}` did not pass in its child process:\n{stdout}{}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use tokio::sync::broadcast::error::TryRecvError;
+
+    async fn raise_and_settle(signum: libc::c_int) {
+        unsafe {
+            libc::raise(signum);
+        }
+        sleep(Duration::from_millis(50)).await;
+    }
+
+    #[tokio::test]
+    async fn subscribers_only_wake_for_their_signal() {
+        let hub = SignalHub::new();
+        let mut term_a = hub.subscribe(SignalKind::terminate());
+        let mut term_b = hub.subscribe(SignalKind::terminate());
+        let mut int = hub.subscribe(SignalKind::interrupt());
+        assert_eq!(hub.channels.lock().unwrap().len(), 2);
+
+        raise_and_settle(libc::SIGTERM).await;
+        let event = term_a.try_recv().unwrap();
+        assert_eq!(event.kind, SignalKind::terminate());
+        assert_eq!(term_b.try_recv(), Ok(event));
+        assert_eq!(int.try_recv(), Err(TryRecvError::Empty));
+
+        raise_and_settle(libc::SIGINT).await;
+        assert_eq!(int.try_recv().unwrap().kind, SignalKind::interrupt());
+        assert_eq!(term_a.try_recv(), Err(TryRecvError::Empty));
+        assert_eq!(term_b.try_recv(), Err(TryRecvError::Empty));
+    }
+
+    #[tokio::test]
+    async fn subscriber_count_tracks_live_receivers() {
+        let hub = SignalHub::new();
+        let kind = SignalKind::user_defined1();
+        assert_eq!(hub.subscriber_count(kind), 0);
+
+        let mut receivers: Vec<_> = (0..5).map(|_| hub.subscribe(kind)).collect();
+        assert_eq!(hub.subscriber_count(kind), 5);
+
+        receivers.truncate(2);
+        assert_eq!(hub.subscriber_count(kind), 2);
+        assert_eq!(hub.subscriber_count(SignalKind::user_defined2()), 0);
+
+        drop(receivers);
+        assert_eq!(hub.subscriber_count(kind), 0);
+    }
+
+    #[tokio::test]
+    async fn shutdown_closes_subscribers() {
+        let hub = SignalHub::new();
+        let mut rx = hub.subscribe(SignalKind::window_change());
+
+        hub.shutdown();
+        hub.shutdown();
+
+        let result = tokio::time::timeout(Duration::from_secs(1), rx.recv())
+            .await
+            .expect("forwarder should drop its sender promptly");
+        assert_eq!(result, Err(RecvError::Closed));
+        assert_eq!(hub.subscriber_count(SignalKind::window_change()), 0);
+
+        let mut late = hub.subscribe(SignalKind::window_change());
+        assert_eq!(late.recv().await, Err(RecvError::Closed));
+    }
+
+    #[tokio::test]
+    async fn subscribe_churn_keeps_fd_count_flat() {
+        // Other tests open and close runtimes (and their fds) concurrently,
+        // so count in a process where this is the only test running
+        if !isolation::in_child() {
+            isolation::rerun_alone("tests::subscribe_churn_keeps_fd_count_flat", |_| {});
+            return;
+        }
+
+        let kind = SignalKind::pipe();
+        // tokio's signal driver keeps some process-wide state after the first
+        // registration of a kind; pay for that before taking the baseline
+        drop(SignalHub::new().subscribe(kind));
+        sleep(Duration::from_millis(20)).await;
+        let baseline = fd::open_fd_count().expect("fd counting supported on this OS");
+
+        let hub = SignalHub::new();
+        for _ in 0..256 {
+            let rx = hub.subscribe(kind);
+            drop(rx);
+        }
+        drop(hub);
+        // Let the cancelled forwarder run and drop its stream
+        sleep(Duration::from_millis(20)).await;
+
+        let after = fd::open_fd_count().unwrap();
+        // Nothing else runs in this process; the leaky pattern would add one
+        // fd per iteration
+        const TOLERANCE: usize = 1;
+        assert!(
+            after <= baseline + TOLERANCE,
+            "fd count went from {baseline} to {after} after 256 subscriptions and dropping the hub"
+        );
+    }
+
+    #[tokio::test]
+    async fn lagging_subscriber_sees_sequence_gap() {
+        let hub = SignalHub::with_capacity(2);
+        let mut slow = hub.subscribe(SignalKind::user_defined2());
+
+        // Four signals into a buffer of two: the first two are overwritten
+        for _ in 0..4 {
+            raise_and_settle(libc::SIGUSR2).await;
+        }
+
+        let (event, missed) = recv_event(&mut slow).await.unwrap();
+        assert_eq!(missed, 2);
+        assert_eq!(event.seq, 3);
+        assert_eq!(event.seq - 1, missed, "sequence gap matches the lag");
+
+        let (event, missed) = recv_event(&mut slow).await.unwrap();
+        assert_eq!((event.seq, missed), (4, 0));
+    }
+}
+
+#[cfg(all(test, windows))]
+mod windows_tests {
+    use super::*;
+    use std::os::windows::process::CommandExt;
+    use windows_sys::Win32::System::Console::{GenerateConsoleCtrlEvent, CTRL_BREAK_EVENT};
+    use windows_sys::Win32::System::Threading::CREATE_NEW_PROCESS_GROUP;
+
+    #[tokio::test]
+    async fn ctrl_break_wakes_hangup_subscriber() {
+        // Ctrl+Break goes to every process in the target console process
+        // group; in a group of its own, the event reaches only this test
+        if !isolation::in_child() {
+            isolation::rerun_alone(
+                "windows_tests::ctrl_break_wakes_hangup_subscriber",
+                |command| {
+                    command.creation_flags(CREATE_NEW_PROCESS_GROUP);
+                },
+            );
+            return;
+        }
+
+        let hub = SignalHub::new();
+        let mut rx = hub.subscribe(SignalKind::hangup());
+
+        // The group id of a process started with CREATE_NEW_PROCESS_GROUP is its pid
+        assert_ne!(
+            unsafe { GenerateConsoleCtrlEvent(CTRL_BREAK_EVENT, std::process::id()) },
+            0
+        );
+
+        let event = tokio::time::timeout(Duration::from_secs(1), rx.recv())
+            .await
+            .expect("Ctrl+Break should reach the subscriber")
+            .unwrap();
+        assert_eq!((event.kind, event.seq), (SignalKind::hangup(), 1));
+    }
+}
@@ -0,0 +1,73 @@
+//! Platform signal sources behind one `SignalKind` / `listen` API, so the hub
+//! above them is identical on Unix and Windows.
+
+#[cfg(unix)]
+mod imp {
+    use std::io;
+
+    use tokio::signal::unix::Signal;
+    pub use tokio::signal::unix::SignalKind;
+
+    pub fn listen(kind: SignalKind) -> io::Result<Signal> {
+        tokio::signal::unix::signal(kind)
+    }
+}
+
+#[cfg(windows)]
+mod imp {
+    use std::io;
+    use tokio::signal::windows::{ctrl_break, ctrl_c, ctrl_close, CtrlBreak, CtrlC, CtrlClose};
+
+    /// Console control events standing in for the Unix signals the daemon
+    /// cares about: Ctrl+Break for reload, Ctrl+C for interrupt, and the
+    /// console closing for terminate.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+    pub struct SignalKind(Event);
+
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+    enum Event {
+        CtrlBreak,
+        CtrlC,
+        CtrlClose,
+    }
+
+    impl SignalKind {
+        pub const fn hangup() -> Self {
+            SignalKind(Event::CtrlBreak)
+        }
+
+        pub const fn interrupt() -> Self {
+            SignalKind(Event::CtrlC)
+        }
+
+        pub const fn terminate() -> Self {
+            SignalKind(Event::CtrlClose)
+        }
+    }
+
+    pub enum Listener {
+        CtrlBreak(CtrlBreak),
+        CtrlC(CtrlC),
+        CtrlClose(CtrlClose),
+    }
+
+    impl Listener {
+        pub async fn recv(&mut self) -> Option<()> {
+            match self {
+                Listener::CtrlBreak(stream) => stream.recv().await,
+                Listener::CtrlC(stream) => stream.recv().await,
+                Listener::CtrlClose(stream) => stream.recv().await,
+            }
+        }
+    }
+
+    pub fn listen(kind: SignalKind) -> io::Result<Listener> {
+        Ok(match kind.0 {
+            Event::CtrlBreak => Listener::CtrlBreak(ctrl_break()?),
+            Event::CtrlC => Listener::CtrlC(ctrl_c()?),
+            Event::CtrlClose => Listener::CtrlClose(ctrl_close()?),
+        })
+    }
+}
+
+pub use imp::{listen, SignalKind};